@@ -61,8 +61,12 @@ fn main() {
 
 // ========================================================================= //
 
-const RSRC_GLOBS: &[&str] =
-    &["data/backgrounds/*.bg", "data/fonts/*.ahf", "data/sprites/**/*.ahi"];
+const RSRC_GLOBS: &[&str] = &[
+    "data/backgrounds/*.bg",
+    "data/fonts/*.ahf",
+    "data/lang/*.toml",
+    "data/sprites/**/*.ahi",
+];
 
 fn generate_rsrc_data_file() -> io::Result<()> {
     let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
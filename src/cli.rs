@@ -0,0 +1,463 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use crate::gui::{Event, KeyMod, Keycode, Window};
+use crate::modes::{run_location, run_settings, run_title_screen};
+use crate::save::plane::PlaneGrid;
+use crate::save::random::{new_seed, Difficulty, Scramble};
+use crate::save::{Access, Game, Location, SaveData};
+
+// ========================================================================= //
+
+/// Descriptions of the subcommands that can be given on the command line in
+/// place of starting the game.  These only read and modify the save file;
+/// they never start SDL.
+pub const COMMAND_USAGE: &str = "Commands:
+    dump                        print the access state of each location
+    set-access LOCATION STATE   change the access state of one location
+    unlock-all                  solve every puzzle that unlocks another
+    reset LOCATION              return one location to its initial state
+    lang-template LANGUAGE      print a text catalogue template for LANGUAGE
+    plane-solve LOCATION        solve each stage of a pipe-laying puzzle
+    scramble LOCATION DIFFICULTY [SEED] [--force]
+                                deal a fresh, scrambled instance of a puzzle
+";
+
+/// How many clock ticks to run each screen for before pressing any keys;
+/// long enough for the screen to finish fading in.
+const TEMPLATE_TICKS: usize = 100;
+
+/// Returns true if the given subcommand only reads the save file, in which
+/// case the file should be loaded as-is rather than created or repaired.
+pub fn is_read_only(args: &[String]) -> bool {
+    args.len() == 1 && args[0] == "dump"
+}
+
+/// Returns true if the given subcommand doesn't use the save file at all, in
+/// which case it needn't be loaded.
+pub fn ignores_save_file(args: &[String]) -> bool {
+    let command = args.first().map(String::as_str);
+    command == Some("lang-template") || command == Some("plane-solve")
+}
+
+/// Runs the given subcommand against the current game slot.  Returns true if
+/// the save data was changed and should be written back to disk.
+pub fn run_command(
+    args: &[String],
+    data: &mut SaveData,
+) -> Result<bool, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["dump"] => {
+            match data.game() {
+                Some(game) => print!("{}", dump(game)),
+                None => {
+                    println!("No game in slot \"{}\".", data.current_slot())
+                }
+            }
+            Ok(false)
+        }
+        ["set-access", location, access] => {
+            let location = parse_location(location)?;
+            let access = parse_access(access)?;
+            set_access(data.game_mut(), location, access);
+            Ok(true)
+        }
+        ["unlock-all"] => {
+            unlock_all(data.game_mut());
+            Ok(true)
+        }
+        ["reset", location] => {
+            let location = parse_location(location)?;
+            reset(data.game_mut(), location);
+            Ok(true)
+        }
+        ["lang-template", language] => {
+            print!("{}", catalog_template(language, Location::all()));
+            Ok(false)
+        }
+        ["scramble", location, difficulty, rest @ ..] => {
+            let location = parse_location(location)?;
+            let difficulty = parse_difficulty(difficulty)?;
+            let force = rest.contains(&"--force");
+            let seed = match rest.iter().find(|&&arg| arg != "--force") {
+                Some(seed) => seed
+                    .parse()
+                    .map_err(|_| format!("Invalid seed: {}", seed))?,
+                None => new_seed(),
+            };
+            let scramble = Scramble::new(seed, difficulty);
+            deal_scramble(data.game_mut(), location, scramble, force)?;
+            Ok(true)
+        }
+        ["plane-solve", location] => {
+            let location = parse_location(location)?;
+            print!("{}", solve_plane_stages(location)?);
+            Ok(false)
+        }
+        _ => Err(format!("Invalid command: {}", args.join(" "))),
+    }
+}
+
+// ========================================================================= //
+
+fn puzzle_locations() -> impl Iterator<Item = Location> {
+    Location::all().iter().copied().filter(|&loc| loc != Location::Map)
+}
+
+pub fn parse_location(key: &str) -> Result<Location, String> {
+    match Location::from_key(key) {
+        Some(Location::Map) | None => Err(format!(
+            "Unknown location: {}\nValid locations are: {}",
+            key,
+            puzzle_locations()
+                .map(Location::key)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+        Some(location) => Ok(location),
+    }
+}
+
+pub fn parse_access(key: &str) -> Result<Access, String> {
+    Access::from_key(key).ok_or_else(|| {
+        format!(
+            "Unknown access state: {}\nValid states are: {}",
+            key,
+            Access::all()
+                .iter()
+                .map(|access| access.key())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    })
+}
+
+pub fn parse_difficulty(key: &str) -> Result<Difficulty, String> {
+    Difficulty::from_key(key).ok_or_else(|| {
+        format!(
+            "Unknown difficulty: {}\nValid difficulties are: {}",
+            key,
+            Difficulty::all()
+                .iter()
+                .map(|difficulty| difficulty.key())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    })
+}
+
+fn dump(game: &Game) -> String {
+    let mut output = String::new();
+    for location in puzzle_locations() {
+        let access = game.puzzle_state(location).access();
+        output.push_str(&format!(
+            "{:<24} {:<20} {}\n",
+            location.key(),
+            location.name(),
+            access.key()
+        ));
+    }
+    output
+}
+
+pub fn set_access(game: &mut Game, location: Location, access: Access) {
+    *game.puzzle_state_mut(location).access_mut() = access;
+    // Reload the game so that the puzzle's state is rebuilt to match its new
+    // access (e.g. a solved puzzle loads with its solved layout).
+    *game = Game::from_toml(game.to_toml());
+}
+
+fn unlock_all(game: &mut Game) {
+    for location in puzzle_locations() {
+        for prereq in location.prereqs() {
+            if !game.has_been_solved(prereq) {
+                set_access(game, prereq, Access::Solved);
+            }
+        }
+    }
+}
+
+fn reset(game: &mut Game, location: Location) {
+    let state = game.puzzle_state_mut(location);
+    state.reset();
+    *state.access_mut() = Access::Unvisited;
+}
+
+/// Replaces the given puzzle with a freshly dealt instance, unless the
+/// player is partway through it and `force` isn't set.
+fn deal_scramble(
+    game: &mut Game,
+    location: Location,
+    scramble: Scramble,
+    force: bool,
+) -> Result<(), String> {
+    if !Game::scramble_locations().contains(&location) {
+        return Err(format!("{} can't be scrambled.", location.key()));
+    }
+    if game.is_in_progress(location) && !force {
+        return Err(format!(
+            "{} is in progress; use --force to replace it.",
+            location.key()
+        ));
+    }
+    game.deal_scramble(location, scramble);
+    Ok(())
+}
+
+fn plane_grid_mut(
+    game: &mut Game,
+    location: Location,
+) -> Result<&mut PlaneGrid, String> {
+    match location {
+        Location::PlaneAndSimple => Ok(game.plane_and_simple.grid_mut()),
+        Location::PlaneAsDay => Ok(game.plane_as_day.grid_mut()),
+        _ => Err(format!("{} has no pipes to lay.", location.key())),
+    }
+}
+
+/// Solves each stage of a pipe-laying puzzle in turn, starting from the
+/// first, and describes the pipes of each solution and whether it's the only
+/// one.  Stops at the first stage that can't be solved.
+fn solve_plane_stages(location: Location) -> Result<String, String> {
+    let mut game = Game::new();
+    let mut output = String::new();
+    for stage in 1.. {
+        let grid = plane_grid_mut(&mut game, location)?;
+        let solution = match grid.solution()? {
+            Some(solution) => solution,
+            None => {
+                output.push_str(&format!("Stage {}: no solution\n", stage));
+                break;
+            }
+        };
+        output.push_str(&format!(
+            "Stage {}: {} solution\n",
+            stage,
+            if solution.is_unique() { "unique" } else { "non-unique" }
+        ));
+        for pipe in solution.pipes() {
+            let points: Vec<String> = pipe
+                .iter()
+                .map(|pt| format!("({}, {})", pt.x(), pt.y()))
+                .collect();
+            output.push_str(&format!("    {}\n", points.join(" ")));
+        }
+        grid.set_pipes(solution.pipes());
+        let advanced = match location {
+            Location::PlaneAndSimple => {
+                game.plane_and_simple.advance_stage_if_done()
+            }
+            Location::PlaneAsDay => game.plane_as_day.advance_stage_if_done(),
+            _ => false,
+        };
+        if !advanced {
+            return Err(format!("Stage {}'s solution was rejected.", stage));
+        }
+        if game.has_been_solved(location) {
+            break;
+        }
+    }
+    Ok(output)
+}
+
+/// Runs the title screen, the settings screen, and the given locations in a
+/// headless window, opening the info boxes along the way, and returns a
+/// catalogue template for the given language covering all the text that was
+/// shown.  Existing translations in the language's catalogue are kept.
+fn catalog_template(language: &str, locations: &[Location]) -> String {
+    let key = |keycode| Event::KeyDown(keycode, KeyMod::none());
+    // Some locations auto-save as they run, so give them somewhere harmless
+    // to do it.
+    let path = std::env::temp_dir().join("syzygy-lang-template/save.toml");
+    let mut window = Window::headless((576, 384));
+    window.set_language(language);
+    window.record_text();
+    // Open and close the About box.
+    window.queue_events(vec![Event::ClockTick; TEMPLATE_TICKS]);
+    window.queue_events(vec![
+        key(Keycode::Down),
+        key(Keycode::Down),
+        key(Keycode::Left),
+        key(Keycode::Return),
+        key(Keycode::Return),
+    ]);
+    run_title_screen(&mut window, &mut SaveData::new(path.clone()));
+    window.queue_events(vec![key(Keycode::Escape)]);
+    run_settings(&mut window, &mut SaveData::new(path.clone()), None);
+    for &location in locations {
+        let accesses: &[Access] = if location == Location::Map {
+            &[Access::Unvisited]
+        } else {
+            &[Access::Unvisited, Access::Unsolved, Access::Solved]
+        };
+        for &access in accesses {
+            let mut data = SaveData::new(path.clone());
+            if location != Location::Map {
+                set_access(data.game_mut(), location, access);
+            }
+            window.queue_events(vec![Event::ClockTick; TEMPLATE_TICKS]);
+            window.queue_events(vec![key(Keycode::F1), Event::ClockTick]);
+            run_location(&mut window, &mut data, location);
+        }
+    }
+    window.text_template()
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        catalog_template, ignores_save_file, is_read_only, parse_access,
+        parse_location, run_command,
+    };
+    use crate::save::{Access, Game, Location, PuzzleState, SaveData};
+
+    fn run(data: &mut SaveData, command: &str) -> Result<bool, String> {
+        let args: Vec<String> =
+            command.split_whitespace().map(str::to_string).collect();
+        run_command(&args, data)
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse_location("log_level"), Ok(Location::LogLevel));
+        assert!(parse_location("map").is_err());
+        assert!(parse_location("nowhere").is_err());
+        assert_eq!(parse_access("begin_replay"), Ok(Access::BeginReplay));
+        assert!(parse_access("open").is_err());
+    }
+
+    #[test]
+    fn read_only_commands() {
+        let args = |command: &str| -> Vec<String> {
+            command.split_whitespace().map(str::to_string).collect()
+        };
+        assert!(is_read_only(&args("dump")));
+        assert!(!is_read_only(&args("unlock-all")));
+        assert!(!is_read_only(&args("reset star_crossed")));
+        assert!(ignores_save_file(&args("lang-template fr")));
+        assert!(ignores_save_file(&args("plane-solve plane_as_day")));
+        assert!(!ignores_save_file(&args("dump")));
+    }
+
+    #[test]
+    fn set_access_and_reset() {
+        let mut data = SaveData::new(PathBuf::new());
+        assert_eq!(run(&mut data, "dump"), Ok(false));
+        assert_eq!(run(&mut data, "set-access star_crossed solved"), Ok(true));
+        let game = data.game().unwrap();
+        assert!(game.has_been_solved(Location::StarCrossed));
+        assert!(!game.has_been_solved(Location::Prolog));
+        assert_eq!(run(&mut data, "reset star_crossed"), Ok(true));
+        let game = data.game().unwrap();
+        assert_eq!(
+            game.puzzle_state(Location::StarCrossed).access(),
+            Access::Unvisited
+        );
+        assert!(run(&mut data, "reset").is_err());
+        assert!(run(&mut data, "set-access star_crossed").is_err());
+    }
+
+    #[test]
+    fn set_access_solved_shows_solution() {
+        let mut data = SaveData::new(PathBuf::new());
+        assert_eq!(run(&mut data, "set-access hex_spangled solved"), Ok(true));
+        let state = &data.game().unwrap().hex_spangled;
+        let mut solved = Game::new().hex_spangled;
+        solved.solve();
+        assert_eq!(state.tokens(), solved.tokens());
+        assert_eq!(state.access(), Access::Solved);
+    }
+
+    #[test]
+    fn template_covers_info_boxes_and_buttons() {
+        let template = catalog_template("en", &[Location::LightSyrup]);
+        assert!(template.contains("\n[light_syrup]\n"));
+        assert!(template.contains(
+            "# Your goal is to turn all twenty-one lights WHITE.\n"
+        ));
+        assert!(template.contains("\n[common]\n# OK\n# TODO\n"));
+        assert!(template.contains("\n[title]\n"));
+        assert!(template.contains("# Screen reader output\n"));
+    }
+
+    #[test]
+    fn solve_plane_stages() {
+        let output = super::solve_plane_stages(Location::PlaneAndSimple);
+        let output = output.unwrap();
+        assert!(output.starts_with("Stage 1: unique solution\n"));
+        assert!(!output.contains("non-unique"));
+        assert!(!output.contains("no solution"));
+        assert!(super::solve_plane_stages(Location::LightSyrup).is_err());
+
+        // Gray nodes must end up particular colors, which the solver can't
+        // check.
+        let game = Game::new();
+        assert!(game.system_syzygy.mezure_pipe_grid().solution().is_err());
+    }
+
+    #[test]
+    fn scramble_persists_and_keeps_progress() {
+        let mut data = SaveData::new(PathBuf::new());
+        let command = "scramble hex_spangled hard 1234";
+        assert_eq!(run(&mut data, command), Ok(true));
+        let game = data.game().unwrap();
+        let tokens = game.hex_spangled.tokens().clone();
+        assert_eq!(game.hex_spangled.access(), Access::Unvisited);
+        let reloaded = Game::from_toml(game.to_toml());
+        assert_eq!(reloaded.hex_spangled.tokens(), &tokens);
+        assert!(!reloaded.hex_spangled.can_reset());
+
+        assert_eq!(run(&mut data, "set-access cube_tangle solved"), Ok(true));
+        assert_eq!(run(&mut data, "scramble cube_tangle easy 1"), Ok(true));
+        let game = data.game().unwrap();
+        assert_eq!(game.cube_tangle.access(), Access::BeginReplay);
+        assert!(game.has_been_solved(Location::CubeTangle));
+
+        assert!(run(&mut data, "scramble light_syrup easy").is_err());
+        assert!(run(&mut data, "scramble hex_spangled tricky").is_err());
+        assert!(run(&mut data, "scramble hex_spangled easy seed").is_err());
+
+        // A puzzle the player is partway through is only replaced when
+        // forced.
+        let command = "set-access wrecked_angle unsolved";
+        assert_eq!(run(&mut data, command), Ok(true));
+        assert!(run(&mut data, "scramble wrecked_angle easy 5").is_err());
+        let command = "scramble wrecked_angle easy 5 --force";
+        assert_eq!(run(&mut data, command), Ok(true));
+        assert!(data.game().unwrap().wrecked_angle.is_scrambled());
+    }
+
+    #[test]
+    fn unlock_all() {
+        let mut data = SaveData::new(PathBuf::new());
+        assert_eq!(run(&mut data, "unlock-all"), Ok(true));
+        let game = data.game().unwrap();
+        for &location in Location::all() {
+            assert!(game.is_unlocked(location), "{:?}", location);
+        }
+        assert!(!game.has_been_solved(Location::Finale));
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,355 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use crate::gui::{
+    Action, Canvas, Element, Event, KeyMod, Keycode, Point, Rect,
+};
+use crate::save::Direction;
+
+// ========================================================================= //
+
+const CURSOR_COLOR: (u8, u8, u8) = (255, 255, 128);
+const HELD_COLOR: (u8, u8, u8) = (128, 255, 255);
+
+// ========================================================================= //
+
+/// A grid that can be played with a `GridCursor`.
+pub trait CursorGrid {
+    /// Returns the number of columns and rows in the grid.
+    fn grid_size(&self) -> (i32, i32);
+
+    /// Returns true if the piece at the given cell can be picked up (and, if
+    /// it supports it, rotated).
+    fn can_pick_up(&self, coords: Point) -> bool;
+}
+
+// ========================================================================= //
+
+/// How a `GridCursor` treats arrow keys while a piece is held.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CursorStyle {
+    /// The arrow keys carry the held piece around the grid, and dropping it
+    /// elsewhere moves it there.
+    Carry,
+    /// The arrow keys push the held piece in that direction.
+    Push,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CursorCmd {
+    /// The held piece was dropped at a new cell (from, to).
+    Drop(Point, Point),
+    /// The held piece was pushed in the given direction.
+    Push(Point, Direction),
+    /// The piece at the given cell should be rotated.
+    Rotate(Point),
+}
+
+// ========================================================================= //
+
+/// A keyboard-driven selection for grid-based puzzles.  The arrow keys move
+/// the selection, space or return picks up or drops a piece, and R rotates
+/// the selected piece.  The cursor stays hidden until a key is pressed, and
+/// hides again as soon as the mouse is used.
+pub struct GridCursor {
+    rect: Rect,
+    cell_size: i32,
+    style: CursorStyle,
+    coords: Point,
+    held: Option<Point>,
+    visible: bool,
+}
+
+impl GridCursor {
+    pub fn new(
+        left: i32,
+        top: i32,
+        cell_size: i32,
+        style: CursorStyle,
+    ) -> GridCursor {
+        GridCursor {
+            rect: Rect::new(left, top, cell_size as u32, cell_size as u32),
+            cell_size,
+            style,
+            coords: Point::new(0, 0),
+            held: None,
+            visible: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn coords(&self) -> Point {
+        self.coords
+    }
+
+    pub fn held(&self) -> Option<Point> {
+        self.held
+    }
+
+    /// Moves the cursor to the given cell, carrying along any held piece (for
+    /// example, after a pushed piece has finished sliding).
+    pub fn follow(&mut self, coords: Point) {
+        self.coords = coords;
+        if self.held.is_some() {
+            self.held = Some(coords);
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.held = None;
+    }
+
+    fn cell_rect(&self, coords: Point, inset: i32) -> Rect {
+        Rect::new(
+            self.rect.left() + coords.x() * self.cell_size + inset,
+            self.rect.top() + coords.y() * self.cell_size + inset,
+            (self.cell_size - 2 * inset) as u32,
+            (self.cell_size - 2 * inset) as u32,
+        )
+    }
+
+    fn move_cursor<S: CursorGrid>(
+        &mut self,
+        grid: &S,
+        dir: Direction,
+    ) -> Action<CursorCmd> {
+        if self.style == CursorStyle::Push {
+            if let Some(coords) = self.held.take() {
+                // The piece can become stuck while held (for example, once
+                // the puzzle is solved), in which case it's let go.
+                if grid.can_pick_up(coords) {
+                    self.held = Some(coords);
+                    return Action::redraw()
+                        .and_return(CursorCmd::Push(coords, dir));
+                }
+            }
+        }
+        let (num_cols, num_rows) = grid.grid_size();
+        let next = self.coords + dir.delta();
+        if next.x() >= 0
+            && next.x() < num_cols
+            && next.y() >= 0
+            && next.y() < num_rows
+        {
+            self.coords = next;
+        }
+        Action::redraw().and_stop()
+    }
+
+    fn pick_up_or_drop<S: CursorGrid>(
+        &mut self,
+        grid: &S,
+    ) -> Action<CursorCmd> {
+        match self.held.take() {
+            Some(from) => {
+                if self.style == CursorStyle::Carry && from != self.coords {
+                    return Action::redraw()
+                        .and_return(CursorCmd::Drop(from, self.coords));
+                }
+            }
+            None => {
+                if grid.can_pick_up(self.coords) {
+                    self.held = Some(self.coords);
+                }
+            }
+        }
+        Action::redraw().and_stop()
+    }
+
+    fn rotate<S: CursorGrid>(&mut self, grid: &S) -> Action<CursorCmd> {
+        let coords = self.held.unwrap_or(self.coords);
+        if grid.can_pick_up(coords) {
+            Action::redraw().and_return(CursorCmd::Rotate(coords))
+        } else {
+            Action::ignore().and_stop()
+        }
+    }
+}
+
+impl<S: CursorGrid> Element<S, CursorCmd> for GridCursor {
+    fn draw(&self, _: &S, canvas: &mut Canvas) {
+        if !self.visible {
+            return;
+        }
+        if let Some(held) = self.held {
+            canvas.draw_rect(HELD_COLOR, self.cell_rect(held, 2));
+            canvas.draw_rect(HELD_COLOR, self.cell_rect(held, 3));
+        }
+        canvas.draw_rect(CURSOR_COLOR, self.cell_rect(self.coords, 0));
+        canvas.draw_rect(CURSOR_COLOR, self.cell_rect(self.coords, 1));
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        grid: &mut S,
+    ) -> Action<CursorCmd> {
+        let dir = match event {
+            &Event::MouseDown(_) => {
+                let redraw = self.visible;
+                self.visible = false;
+                self.held = None;
+                return Action::redraw_if(redraw);
+            }
+            &Event::KeyDown(keycode, keymod) if keymod == KeyMod::none() => {
+                match keycode {
+                    Keycode::Up => Some(Direction::North),
+                    Keycode::Down => Some(Direction::South),
+                    Keycode::Left => Some(Direction::West),
+                    Keycode::Right => Some(Direction::East),
+                    Keycode::Space | Keycode::Return | Keycode::R => None,
+                    _ => return Action::ignore(),
+                }
+            }
+            _ => return Action::ignore(),
+        };
+        if !self.visible {
+            let (num_cols, num_rows) = grid.grid_size();
+            self.coords = Point::new(
+                self.coords.x().min(num_cols - 1),
+                self.coords.y().min(num_rows - 1),
+            );
+            self.visible = true;
+            return Action::redraw().and_stop();
+        }
+        match (dir, event) {
+            (Some(dir), _) => self.move_cursor(grid, dir),
+            (None, &Event::KeyDown(Keycode::R, _)) => self.rotate(grid),
+            (None, _) => self.pick_up_or_drop(grid),
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// Returns the item that pressing an arrow key in the given direction should
+/// move the focus to from an item centered at `from`: the closest one that
+/// lies within 45 degrees of that direction, favoring items that are more
+/// directly in line.  This is for screens whose items aren't laid out in a
+/// grid, such as the map.
+pub fn nearest_in_direction<T: Copy>(
+    items: &[(T, Point)],
+    from: Point,
+    dir: Direction,
+) -> Option<T> {
+    let delta = dir.delta();
+    items
+        .iter()
+        .filter_map(|&(item, center)| {
+            let offset = center - from;
+            let along = offset.x() * delta.x() + offset.y() * delta.y();
+            let across =
+                (offset.x() * delta.y() - offset.y() * delta.x()).abs();
+            if along > 0 && across <= along {
+                Some((along + 2 * across, item))
+            } else {
+                None
+            }
+        })
+        .min_by_key(|&(score, _)| score)
+        .map(|(_, item)| item)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{CursorCmd, CursorGrid, CursorStyle, GridCursor};
+    use crate::gui::{Element, Event, KeyMod, Keycode, Point};
+    use crate::save::Direction;
+
+    struct TestGrid;
+
+    impl CursorGrid for TestGrid {
+        fn grid_size(&self) -> (i32, i32) {
+            (3, 2)
+        }
+
+        fn can_pick_up(&self, coords: Point) -> bool {
+            coords.x() == 0
+        }
+    }
+
+    fn press(cursor: &mut GridCursor, keycode: Keycode) -> Option<CursorCmd> {
+        let event = Event::KeyDown(keycode, KeyMod::none());
+        let action = cursor.handle_event(&event, &mut TestGrid);
+        assert!(action.should_stop());
+        action.value().cloned()
+    }
+
+    #[test]
+    fn carry_and_drop() {
+        let mut cursor = GridCursor::new(0, 0, 32, CursorStyle::Carry);
+        assert_eq!(press(&mut cursor, Keycode::Down), None);
+        assert_eq!(cursor.coords(), Point::new(0, 0));
+        assert_eq!(press(&mut cursor, Keycode::Down), None);
+        assert_eq!(cursor.coords(), Point::new(0, 1));
+        assert_eq!(press(&mut cursor, Keycode::Down), None);
+        assert_eq!(cursor.coords(), Point::new(0, 1));
+        assert_eq!(press(&mut cursor, Keycode::Space), None);
+        assert_eq!(cursor.held(), Some(Point::new(0, 1)));
+        assert_eq!(
+            press(&mut cursor, Keycode::R),
+            Some(CursorCmd::Rotate(Point::new(0, 1)))
+        );
+        assert_eq!(press(&mut cursor, Keycode::Right), None);
+        assert_eq!(press(&mut cursor, Keycode::Up), None);
+        assert_eq!(
+            press(&mut cursor, Keycode::Return),
+            Some(CursorCmd::Drop(Point::new(0, 1), Point::new(1, 0)))
+        );
+        assert_eq!(cursor.held(), None);
+        // Pieces that can't be picked up are left alone.
+        assert_eq!(press(&mut cursor, Keycode::Space), None);
+        assert_eq!(cursor.held(), None);
+        assert_eq!(press(&mut cursor, Keycode::R), None);
+    }
+
+    #[test]
+    fn push_held_piece() {
+        let mut cursor = GridCursor::new(0, 0, 32, CursorStyle::Push);
+        press(&mut cursor, Keycode::Space);
+        assert_eq!(press(&mut cursor, Keycode::Space), None);
+        assert_eq!(
+            press(&mut cursor, Keycode::Right),
+            Some(CursorCmd::Push(Point::new(0, 0), Direction::East))
+        );
+        cursor.follow(Point::new(2, 0));
+        assert_eq!(cursor.held(), Some(Point::new(2, 0)));
+        assert_eq!(cursor.coords(), Point::new(2, 0));
+        let action = cursor
+            .handle_event(&Event::MouseDown(Point::new(5, 5)), &mut TestGrid);
+        assert!(!action.should_stop());
+        assert_eq!(cursor.held(), None);
+    }
+
+    #[test]
+    fn stuck_piece_is_let_go() {
+        let mut cursor = GridCursor::new(0, 0, 32, CursorStyle::Push);
+        press(&mut cursor, Keycode::Space);
+        assert_eq!(press(&mut cursor, Keycode::Space), None);
+        // Pretend the held piece got pushed somewhere it can't be picked up.
+        cursor.follow(Point::new(1, 0));
+        assert_eq!(press(&mut cursor, Keycode::Down), None);
+        assert_eq!(cursor.held(), None);
+        assert_eq!(cursor.coords(), Point::new(1, 1));
+    }
+}
+
+// ========================================================================= //
@@ -65,12 +65,14 @@ pub enum Ast {
 
 impl Ast {
     pub fn compile_scene(resources: &mut Resources, nodes: Vec<Ast>) -> Scene {
-        Scene::new(
+        let mut scene = Scene::new(
             nodes
                 .into_iter()
                 .map(|ast| ast.to_scene_node(resources))
                 .collect(),
-        )
+        );
+        scene.set_pacing(resources.scene_pacing());
+        scene
     }
 
     fn to_scene_node(self, resources: &mut Resources) -> Box<dyn SceneNode> {
@@ -115,7 +117,9 @@ impl Ast {
             }
             Ast::Place(slot, name, index, (x, y)) => {
                 let sprite = resources.get_sprites(name)[index].clone();
-                Box::new(PlaceNode::new(slot, sprite, Point::new(x, y)))
+                let position = Point::new(x, y);
+                let speaker = speaker_name(name);
+                Box::new(PlaceNode::new(slot, sprite, speaker, position))
             }
             Ast::Queue(v1, v2) => Box::new(QueueNode::new((v1, v2))),
             Ast::Remove(slot) => Box::new(RemoveNode::new(slot)),
@@ -157,9 +161,12 @@ impl Ast {
                     }
                 };
                 let sprites = resources.get_sprites(bubble_name);
+                let text = resources.translate(text).to_string();
                 let paragraph =
-                    Paragraph::new(resources, init_font, init_align, text);
-                Box::new(TalkNode::new(slot, sprites, color, pos, paragraph))
+                    Paragraph::new(resources, init_font, init_align, &text);
+                Box::new(TalkNode::new(
+                    slot, style, text, sprites, color, pos, paragraph,
+                ))
             }
             Ast::Wait(duration) => Box::new(WaitNode::new(duration)),
         }
@@ -171,4 +178,20 @@ impl Ast {
 const BLACK: (u8, u8, u8) = (0, 0, 0);
 const WHITE: (u8, u8, u8) = (255, 255, 255);
 
+/// Returns the name of the character whose sprites are under the given
+/// resource name, for labelling their lines in the transcript.
+fn speaker_name(sprite_name: &str) -> Option<&'static str> {
+    match sprite_name {
+        "chars/argony" => Some("Argony"),
+        "chars/elinsa" => Some("Elinsa"),
+        "chars/mezure" => Some("Mezure"),
+        "chars/relyng" => Some("Relyng"),
+        "chars/srb" | "chars/srbdmg" | "finale/tinysrb" => Some("SRB"),
+        "chars/system" => Some("System"),
+        "chars/ugrent" => Some("Ugrent"),
+        "chars/yttris" => Some("Yttris"),
+        _ => None,
+    }
+}
+
 // ========================================================================= //
@@ -28,6 +28,6 @@ pub use self::scene::{
     SetBgNode, SetPosNode, SetSpriteNode, ShakeNode, SlideNode, SoundNode,
     TalkNode, WaitNode,
 };
-pub use self::theater::{TalkPos, Theater};
+pub use self::theater::{TalkPos, Theater, TranscriptLine};
 
 // ========================================================================= //
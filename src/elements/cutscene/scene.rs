@@ -20,11 +20,12 @@
 use std::cmp;
 use std::rc::Rc;
 
-use super::theater::{TalkPos, Theater};
+use super::ast::TalkStyle;
+use super::theater::{TalkPos, Theater, TranscriptLine};
 use crate::elements::Paragraph;
 use crate::gui::{
-    Action, Background, Canvas, Element, Event, Keycode, Point, Sound, Sprite,
-    FRAME_DELAY_MILLIS,
+    Action, Background, Canvas, Element, Event, Keycode, Point, ScenePacing,
+    Sound, Sprite, FRAME_DELAY_MILLIS,
 };
 
 // ========================================================================= //
@@ -32,6 +33,7 @@ use crate::gui::{
 const CLICKS_TO_SHOW_SKIP: i32 = 3;
 const FRAMES_BETWEEN_CLICKS: i32 = 10;
 const FRAMES_TO_HIDE_SKIP: i32 = 50;
+const NORMAL_SPEED_PERCENT: u32 = 100;
 
 // ========================================================================= //
 
@@ -42,6 +44,9 @@ pub struct Scene {
     began: bool,
     skip_clicks: i32,
     skip_click_frames: i32,
+    pacing: ScenePacing,
+    step_credit: u32,
+    paused_frames: i32,
 }
 
 impl Scene {
@@ -52,9 +57,19 @@ impl Scene {
             began: false,
             skip_clicks: 0,
             skip_click_frames: 0,
+            pacing: ScenePacing::normal(),
+            step_credit: 0,
+            paused_frames: 0,
         }
     }
 
+    /// Sets how quickly the scene plays.  Each clock tick advances the scene
+    /// by as many steps as the speed calls for, and if auto-advance is on, a
+    /// paused speech bubble is dismissed once it has been up long enough.
+    pub fn set_pacing(&mut self, pacing: ScenePacing) {
+        self.pacing = pacing;
+    }
+
     pub fn empty() -> Scene {
         Scene::new(Vec::new())
     }
@@ -75,6 +90,26 @@ impl Scene {
         if !theater.drain_queue().is_empty() {
             debug_assert!(false, "Theater queue was not drained.");
         }
+        let mut changed = false;
+        self.step_credit += self.pacing.speed_percent;
+        while self.step_credit >= NORMAL_SPEED_PERCENT {
+            self.step_credit -= NORMAL_SPEED_PERCENT;
+            changed |= self.step(theater);
+        }
+        if self.pacing.auto_advance_millis > 0 && self.is_paused() {
+            self.paused_frames += 1;
+            let delay = self.pacing.auto_advance_millis / FRAME_DELAY_MILLIS;
+            if self.paused_frames >= delay as i32 {
+                self.unpause();
+                changed = true;
+            }
+        } else {
+            self.paused_frames = 0;
+        }
+        changed
+    }
+
+    fn step(&mut self, theater: &mut Theater) -> bool {
         let mut changed = false;
         if self.index < self.nodes.len() {
             changed |= self.nodes[self.index].tick(theater, false);
@@ -117,6 +152,7 @@ impl Scene {
     }
 
     pub fn unpause(&mut self) {
+        self.paused_frames = 0;
         if self.index < self.nodes.len() {
             self.nodes[self.index].unpause();
         }
@@ -647,12 +683,18 @@ impl SceneNode for LightNode {
 pub struct PlaceNode {
     slot: i32,
     sprite: Sprite,
+    speaker: Option<&'static str>,
     position: Point,
 }
 
 impl PlaceNode {
-    pub fn new(slot: i32, sprite: Sprite, position: Point) -> PlaceNode {
-        PlaceNode { slot, sprite, position }
+    pub fn new(
+        slot: i32,
+        sprite: Sprite,
+        speaker: Option<&'static str>,
+        position: Point,
+    ) -> PlaceNode {
+        PlaceNode { slot, sprite, speaker, position }
     }
 }
 
@@ -666,7 +708,8 @@ impl SceneNode for PlaceNode {
     }
 
     fn skip(&mut self, theater: &mut Theater) {
-        theater.place_actor(self.slot, self.sprite.clone(), self.position);
+        let sprite = self.sprite.clone();
+        theater.place_actor(self.slot, sprite, self.speaker, self.position);
     }
 }
 
@@ -980,6 +1023,8 @@ impl SceneNode for SwapNode {
 #[derive(Clone)]
 pub struct TalkNode {
     slot: i32,
+    style: TalkStyle,
+    text: String,
     bubble_sprites: Vec<Sprite>,
     bg_color: (u8, u8, u8),
     talk_pos: TalkPos,
@@ -991,6 +1036,8 @@ pub struct TalkNode {
 impl TalkNode {
     pub fn new(
         slot: i32,
+        style: TalkStyle,
+        text: String,
         bubble_sprites: Vec<Sprite>,
         bg_color: (u8, u8, u8),
         talk_pos: TalkPos,
@@ -998,6 +1045,8 @@ impl TalkNode {
     ) -> TalkNode {
         TalkNode {
             slot,
+            style,
+            text,
             bubble_sprites,
             bg_color,
             talk_pos,
@@ -1006,6 +1055,18 @@ impl TalkNode {
             terminated_by_pause: false,
         }
     }
+
+    fn record(&self, theater: &mut Theater, skipped: bool) {
+        theater.add_transcript_line(TranscriptLine {
+            slot: self.slot,
+            speaker: theater.speaker(self.slot),
+            style: self.style,
+            text: self.text.clone(),
+            bg_color: self.bg_color,
+            paragraph: self.paragraph.clone(),
+            skipped,
+        });
+    }
 }
 
 impl SceneNode for TalkNode {
@@ -1031,6 +1092,7 @@ impl SceneNode for TalkNode {
             self.talk_pos,
             self.paragraph.clone(),
         );
+        self.record(theater, false);
     }
 
     fn tick(&mut self, theater: &mut Theater, keep_twiddling: bool) -> bool {
@@ -1045,6 +1107,9 @@ impl SceneNode for TalkNode {
     }
 
     fn skip(&mut self, theater: &mut Theater) {
+        if self.status == Status::Active {
+            self.record(theater, true);
+        }
         theater.clear_actor_speech(self.slot);
         self.status = Status::Done;
     }
@@ -1114,3 +1179,62 @@ fn seconds_to_frames(seconds: f64) -> i32 {
 }
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{Scene, SceneNode, WaitNode};
+    use crate::elements::{Ast, TalkPos, TalkStyle, Theater};
+    use crate::gui::{ScenePacing, Window};
+
+    #[test]
+    fn speed_multiplies_steps_per_tick() {
+        let nodes: Vec<Box<dyn SceneNode>> =
+            vec![Box::new(WaitNode::new(1.0))];
+        let mut scene = Scene::new(nodes);
+        scene.set_pacing(ScenePacing {
+            speed_percent: 250,
+            auto_advance_millis: 0,
+        });
+        let mut theater = Theater::new();
+        scene.begin(&mut theater);
+        // One second is 25 frames, which takes 10 ticks at 250% speed.
+        for _ in 0..9 {
+            scene.tick(&mut theater);
+        }
+        assert!(!scene.is_finished());
+        scene.tick(&mut theater);
+        assert!(scene.is_finished());
+    }
+
+    #[test]
+    fn auto_advance_dismisses_speech() {
+        let mut window = Window::headless((576, 384));
+        let mut resources = window.resources();
+        let mut scene = Ast::compile_scene(
+            &mut resources,
+            vec![
+                Ast::Place(1, "chars/mezure", 0, (100, 200)),
+                Ast::Talk(1, TalkStyle::Normal, TalkPos::NE, "Hello."),
+            ],
+        );
+        scene.set_pacing(ScenePacing {
+            speed_percent: 100,
+            auto_advance_millis: 400,
+        });
+        let mut theater = Theater::new();
+        scene.begin(&mut theater);
+        // 400ms is 10 frames, counting the one on which the bubble appears.
+        scene.tick(&mut theater);
+        assert!(scene.is_paused());
+        for _ in 0..8 {
+            scene.tick(&mut theater);
+        }
+        assert!(scene.is_paused());
+        scene.tick(&mut theater);
+        assert!(!scene.is_paused());
+        scene.tick(&mut theater);
+        assert!(scene.is_finished());
+    }
+}
+
+// ========================================================================= //
@@ -22,8 +22,9 @@ use std::collections::BTreeMap;
 use std::mem;
 use std::rc::Rc;
 
+use super::ast::TalkStyle;
 use crate::elements::Paragraph;
-use crate::gui::{Background, Canvas, Point, Rect, Sound, Sprite};
+use crate::gui::{Announcer, Background, Canvas, Point, Rect, Sound, Sprite};
 use crate::save::Direction;
 
 // ========================================================================= //
@@ -35,6 +36,8 @@ pub struct Theater {
     sounds: Vec<Sound>,
     shake: i32,
     dark: bool,
+    transcript: Vec<TranscriptLine>,
+    announcer: Announcer,
 }
 
 impl Theater {
@@ -46,6 +49,8 @@ impl Theater {
             sounds: Vec::new(),
             shake: 0,
             dark: false,
+            transcript: Vec::new(),
+            announcer: Announcer::silent(),
         }
     }
 
@@ -53,8 +58,22 @@ impl Theater {
         self.background = Some(background);
     }
 
-    pub fn place_actor(&mut self, slot: i32, sprite: Sprite, position: Point) {
-        self.actors.insert(slot, Actor::new(sprite, position));
+    /// Places an actor in the given slot, replacing any actor already
+    /// there.  The speaker name, if any, labels the actor's lines in the
+    /// transcript.
+    pub fn place_actor(
+        &mut self,
+        slot: i32,
+        sprite: Sprite,
+        speaker: Option<&'static str>,
+        position: Point,
+    ) {
+        self.actors.insert(slot, Actor::new(sprite, speaker, position));
+    }
+
+    /// Returns the name of the character in the given slot, if known.
+    pub fn speaker(&self, slot: i32) -> Option<&'static str> {
+        self.actors.get(&slot).and_then(|actor| actor.speaker)
     }
 
     pub fn remove_actor(&mut self, slot: i32) {
@@ -149,6 +168,35 @@ impl Theater {
         mem::replace(&mut self.sounds, Vec::new())
     }
 
+    /// Sets where lines of dialogue are sent for a screen reader to voice
+    /// as they are displayed.
+    pub fn set_announcer(&mut self, announcer: Announcer) {
+        self.announcer = announcer;
+    }
+
+    pub fn add_transcript_line(&mut self, line: TranscriptLine) {
+        if !line.skipped {
+            self.announcer.announce(&line.text);
+        }
+        // When a scene plays again (e.g. when the player clicks on the same
+        // character twice), its lines share their paragraphs with the first
+        // playing, and are only recorded once.
+        let previous = self
+            .transcript
+            .iter_mut()
+            .find(|old| Rc::ptr_eq(&old.paragraph, &line.paragraph));
+        match previous {
+            Some(old) => old.skipped &= line.skipped,
+            None => self.transcript.push(line),
+        }
+    }
+
+    /// Returns every line of dialogue that has been spoken in this theater
+    /// so far, oldest first, including lines from skipped scenes.
+    pub fn transcript(&self) -> &[TranscriptLine] {
+        &self.transcript
+    }
+
     pub fn shake_offset(&self) -> Point {
         let dx = if self.shake <= 0 {
             0
@@ -286,8 +334,25 @@ fn remove_rect(rects: &mut Vec<Rect>, remove: Rect) {
 
 // ========================================================================= //
 
+/// A line of dialogue from a `TalkNode`, as recorded in the transcript.
+#[derive(Clone)]
+pub struct TranscriptLine {
+    pub slot: i32,
+    /// The name of the character who spoke the line, if known.
+    pub speaker: Option<&'static str>,
+    pub style: TalkStyle,
+    pub text: String,
+    pub bg_color: (u8, u8, u8),
+    pub paragraph: Rc<Paragraph>,
+    /// True if the line was skipped over without ever being displayed.
+    pub skipped: bool,
+}
+
+// ========================================================================= //
+
 struct Actor {
     sprites: Vec<Sprite>,
+    speaker: Option<&'static str>,
     anim_slowdown: i32,
     anim_step: i32,
     position: Point,
@@ -296,9 +361,14 @@ struct Actor {
 }
 
 impl Actor {
-    fn new(sprite: Sprite, position: Point) -> Actor {
+    fn new(
+        sprite: Sprite,
+        speaker: Option<&'static str>,
+        position: Point,
+    ) -> Actor {
         Actor {
             sprites: vec![sprite],
+            speaker,
             anim_slowdown: 0,
             anim_step: 0,
             position,
@@ -22,13 +22,14 @@ use std::rc::Rc;
 
 use super::paragraph::Paragraph;
 use crate::gui::{
-    Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
-    Sprite,
+    Action, Align, Announcer, Canvas, Element, Event, Font, KeyMod, Keycode,
+    Point, Rect, Resources, Sprite,
 };
 
 // ========================================================================= //
 
 const BUTTON_WIDTH: u32 = 50;
+const FOCUS_RING_COLOR: (u8, u8, u8) = (255, 255, 128);
 const BUTTON_HEIGHT: u32 = 20;
 const BUTTON_SPACING: i32 = 6;
 const MARGIN: i32 = 20;
@@ -40,9 +41,17 @@ pub struct DialogBox<A> {
     bg_sprites: Vec<Sprite>,
     paragraph: Paragraph,
     buttons: Vec<DialogButton<A>>,
+    focus: usize,
+    show_focus: bool,
+    cancel: usize,
+    announcer: Announcer,
 }
 
 impl<A: 'static + Clone> DialogBox<A> {
+    /// Creates a dialog box with the given buttons, from left to right.  The
+    /// last button is the default, which the Return key chooses unless the
+    /// player moves the focus with the arrow keys.  The Escape key also
+    /// chooses the default, unless `set_cancel_button` picks another.
     pub fn new(
         resources: &mut Resources,
         visible: Rect,
@@ -71,6 +80,7 @@ impl<A: 'static + Clone> DialogBox<A> {
             rect.center_on(visible.center());
             rect
         };
+        let default = buttons.len().saturating_sub(1);
         let elements = {
             let mut elements: Vec<DialogButton<A>> = Vec::new();
             let top = rect.bottom() - MARGIN - BUTTON_HEIGHT as i32;
@@ -81,6 +91,7 @@ impl<A: 'static + Clone> DialogBox<A> {
                 elements.push(button);
                 left -= BUTTON_WIDTH as i32 + BUTTON_SPACING;
             }
+            elements.reverse();
             elements
         };
         DialogBox {
@@ -88,6 +99,35 @@ impl<A: 'static + Clone> DialogBox<A> {
             bg_sprites: resources.get_sprites("dialog/box"),
             paragraph,
             buttons: elements,
+            focus: default,
+            show_focus: false,
+            cancel: default,
+            announcer: resources.announcer(),
+        }
+    }
+
+    /// Makes the Escape key choose the button at the given index (counting
+    /// from the left), instead of the default button.
+    pub fn set_cancel_button(&mut self, index: usize) {
+        debug_assert!(index < self.buttons.len());
+        self.cancel = index;
+    }
+
+    fn move_focus(&mut self, delta: i32) -> Action<A> {
+        let num_buttons = self.buttons.len() as i32;
+        if num_buttons > 0 {
+            let focus = self.focus as i32 + delta;
+            self.focus = focus.rem_euclid(num_buttons) as usize;
+            self.announcer.announce(&self.buttons[self.focus].label);
+        }
+        self.show_focus = true;
+        Action::redraw().and_stop()
+    }
+
+    fn choose(&self, index: usize) -> Action<A> {
+        match self.buttons.get(index) {
+            Some(button) => Action::redraw().and_return(button.value.clone()),
+            None => Action::ignore(),
         }
     }
 }
@@ -133,10 +173,38 @@ impl<A: 'static + Clone> Element<(), A> for DialogBox<A> {
             }
         }
         self.buttons.draw(state, canvas);
+        if self.show_focus {
+            if let Some(button) = self.buttons.get(self.focus) {
+                let rect = button.rect;
+                canvas.draw_rect(
+                    FOCUS_RING_COLOR,
+                    Rect::new(
+                        rect.x() - 2,
+                        rect.y() - 2,
+                        rect.width() + 4,
+                        rect.height() + 4,
+                    ),
+                );
+            }
+        }
     }
 
     fn handle_event(&mut self, event: &Event, state: &mut ()) -> Action<A> {
-        self.buttons.handle_event(event, state)
+        match event {
+            &Event::KeyDown(Keycode::Left, _) => self.move_focus(-1),
+            &Event::KeyDown(Keycode::Right, _) => self.move_focus(1),
+            &Event::KeyDown(Keycode::Tab, keymod) => {
+                if keymod == KeyMod::shift() {
+                    self.move_focus(-1)
+                } else {
+                    self.move_focus(1)
+                }
+            }
+            &Event::KeyDown(Keycode::Return, _)
+            | &Event::KeyDown(Keycode::Space, _) => self.choose(self.focus),
+            &Event::KeyDown(Keycode::Escape, _) => self.choose(self.cancel),
+            _ => self.buttons.handle_event(event, state),
+        }
     }
 }
 
@@ -157,6 +225,7 @@ impl<A> DialogButton<A> {
         label: String,
         value: A,
     ) -> DialogButton<A> {
+        let label = resources.translate_common(&label).to_string();
         DialogButton {
             sprite: resources.get_sprites("dialog/button")[0].clone(),
             font: resources.get_font("roman"),
@@ -195,4 +264,71 @@ fn round_up_to_16(mut size: i32) -> u32 {
     size as u32
 }
 
+#[cfg(test)]
+mod tests {
+    use super::DialogBox;
+    use crate::gui::{Announcer, Element, Event, KeyMod, Keycode, Window};
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown(keycode, KeyMod::none())
+    }
+
+    fn new_dialog(labels: &[&'static str]) -> DialogBox<&'static str> {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let buttons =
+            labels.iter().map(|&label| (label.to_string(), label)).collect();
+        DialogBox::new(&mut window.resources(), visible, "Text", buttons)
+    }
+
+    #[test]
+    fn return_chooses_focused_button() {
+        let mut dialog = new_dialog(&["Cancel", "Erase"]);
+        let action = dialog.handle_event(&key(Keycode::Return), &mut ());
+        assert_eq!(action.value(), Some(&"Erase"));
+        assert!(dialog
+            .handle_event(&key(Keycode::Left), &mut ())
+            .value()
+            .is_none());
+        let action = dialog.handle_event(&key(Keycode::Return), &mut ());
+        assert_eq!(action.value(), Some(&"Cancel"));
+        dialog.handle_event(&key(Keycode::Tab), &mut ());
+        let action = dialog.handle_event(&key(Keycode::Space), &mut ());
+        assert_eq!(action.value(), Some(&"Erase"));
+    }
+
+    #[test]
+    fn escape_chooses_cancel_button_or_default() {
+        let mut dialog = new_dialog(&["Cancel", "OK"]);
+        dialog.set_cancel_button(0);
+        let action = dialog.handle_event(&key(Keycode::Escape), &mut ());
+        assert_eq!(action.value(), Some(&"Cancel"));
+        let mut dialog = new_dialog(&["Cancel", "OK"]);
+        let action = dialog.handle_event(&key(Keycode::Escape), &mut ());
+        assert_eq!(action.value(), Some(&"OK"));
+        let mut dialog = new_dialog(&["Settings", "OK"]);
+        let action = dialog.handle_event(&key(Keycode::Escape), &mut ());
+        assert_eq!(action.value(), Some(&"OK"));
+        let mut dialog = new_dialog(&["OK"]);
+        let action = dialog.handle_event(&key(Keycode::Escape), &mut ());
+        assert_eq!(action.value(), Some(&"OK"));
+    }
+
+    #[test]
+    fn focused_button_is_announced() {
+        let mut window = Window::headless((576, 384));
+        let (announcer, spoken) = Announcer::capture();
+        window.set_announcer(announcer);
+        let visible = window.visible_rect();
+        let buttons =
+            vec![("Cancel".to_string(), false), ("Erase".to_string(), true)];
+        let mut dialog =
+            DialogBox::new(&mut window.resources(), visible, "Text", buttons);
+        dialog.handle_event(&key(Keycode::Left), &mut ());
+        dialog.handle_event(&key(Keycode::Right), &mut ());
+        let spoken: Vec<String> = spoken.try_iter().collect();
+        assert_eq!(spoken, vec!["Cancel", "Erase"]);
+    }
+}
+
 // ========================================================================= //
@@ -18,12 +18,13 @@
 // +--------------------------------------------------------------------------+
 
 use std::cmp;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::elements::Paragraph;
 use crate::gui::{
-    Action, Align, Canvas, Element, Event, Font, KeyMod, Keycode, Point, Rect,
-    Resources, Sound, Sprite,
+    Action, Align, Announcer, Canvas, Element, Event, Font, KeyMod, Keycode,
+    Point, Rect, Resources, Sound, Sprite,
 };
 use crate::save::{Access, Location};
 
@@ -51,9 +52,11 @@ pub struct HudInput {
     pub can_undo: bool,
     pub can_redo: bool,
     pub can_reset: bool,
+    pub can_hint: bool,
+    pub has_transcript: bool,
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum HudCmd {
     Back,
     Info,
@@ -62,7 +65,9 @@ pub enum HudCmd {
     Reset,
     Replay,
     Solve,
+    Hint,
     Skip,
+    Transcript,
 }
 
 // ========================================================================= //
@@ -88,6 +93,7 @@ impl Hud {
             HudButton::new(resources, location, HudCmd::Redo, cx + 150, bot),
             HudButton::new(resources, location, HudCmd::Reset, cx + 210, bot),
             HudButton::new(resources, location, HudCmd::Replay, cx + 160, bot),
+            HudButton::new(resources, location, HudCmd::Hint, cx - 204, bot),
         ];
         Hud {
             namebox: Hud::namebox(resources, cx, bot),
@@ -130,6 +136,9 @@ impl Element<HudInput, HudCmd> for Hud {
         if !action.should_stop() || event == &Event::ClockTick {
             action.merge(self.buttons.handle_event(event, input));
         }
+        if !action.should_stop() {
+            action.merge(self.namebox.handle_event(event, input));
+        }
         action
     }
 }
@@ -166,7 +175,10 @@ impl HudButton {
             HudCmd::Reset => (10, 54),
             HudCmd::Replay => (12, 60),
             HudCmd::Solve => (14, 54),
-            HudCmd::Skip => panic!("HudButton HudCmd::Skip"),
+            HudCmd::Hint => (16, 46),
+            HudCmd::Skip | HudCmd::Transcript => {
+                panic!("HudButton {:?}", value)
+            }
         };
         let sprite = sprites[index].clone();
         let rect = Rect::new(
@@ -205,7 +217,35 @@ impl HudButton {
             HudCmd::Reset => active && input.can_reset && !solved,
             HudCmd::Replay => active && solved,
             HudCmd::Solve => active && input.access == Access::Replaying,
-            HudCmd::Skip => panic!("HudButton HudCmd::Skip"),
+            HudCmd::Hint => {
+                active && input.can_hint && input.access == Access::Unsolved
+            }
+            HudCmd::Skip | HudCmd::Transcript => {
+                panic!("HudButton {:?}", self.value)
+            }
+        }
+    }
+
+    /// Returns true if pressing the given key should have the same effect as
+    /// clicking this button.  Reset and Replay share a shortcut, since they
+    /// are never both enabled at once.
+    fn is_shortcut(&self, keycode: Keycode, keymod: KeyMod) -> bool {
+        let command = KeyMod::command();
+        match self.value {
+            HudCmd::Back => {
+                keycode == Keycode::Escape && keymod == KeyMod::none()
+            }
+            HudCmd::Info => keycode == Keycode::F1,
+            HudCmd::Undo => keycode == Keycode::Z && keymod == command,
+            HudCmd::Redo => {
+                keycode == Keycode::Z && keymod == command | KeyMod::shift()
+            }
+            HudCmd::Reset | HudCmd::Replay => {
+                keycode == Keycode::R && keymod == command
+            }
+            HudCmd::Solve => keycode == Keycode::S && keymod == command,
+            HudCmd::Hint => keycode == Keycode::F3,
+            HudCmd::Skip | HudCmd::Transcript => false,
         }
     }
 
@@ -275,15 +315,13 @@ impl Element<HudInput, HudCmd> for HudButton {
                 self.flashing = false;
                 self.click_action()
             }
-            &Event::KeyDown(Keycode::Z, keymod) if self.is_enabled(input) => {
-                if keymod == KeyMod::command() && self.value == HudCmd::Undo
-                    || keymod == (KeyMod::command() | KeyMod::shift())
-                        && self.value == HudCmd::Redo
-                {
-                    self.click_action()
-                } else {
-                    Action::ignore()
-                }
+            &Event::KeyDown(keycode, keymod)
+                if self.is_enabled(input)
+                    && self.is_shortcut(keycode, keymod) =>
+            {
+                self.blink_frames = BLINK_FRAMES;
+                self.flashing = false;
+                self.click_action()
             }
             _ => Action::ignore(),
         }
@@ -296,14 +334,36 @@ struct HudNamebox {
     sprites: Vec<Sprite>,
     font: Rc<Font>,
     rect: Rect,
+    announcer: Announcer,
+    announced_name: &'static str,
+    translated_names: HashMap<&'static str, String>,
 }
 
 impl HudNamebox {
     fn new(resources: &mut Resources, rect: Rect) -> HudNamebox {
+        let translated_names = Location::all()
+            .iter()
+            .map(|loc| {
+                (
+                    loc.name(),
+                    resources.translate_common(loc.name()).to_string(),
+                )
+            })
+            .collect();
         HudNamebox {
             sprites: resources.get_sprites("hud/namebox"),
             font: resources.get_font("roman"),
             rect,
+            announcer: resources.announcer(),
+            announced_name: "",
+            translated_names,
+        }
+    }
+
+    fn display_name<'a>(&'a self, input: &HudInput) -> &'a str {
+        match self.translated_names.get(input.name) {
+            Some(name) => name,
+            None => input.name,
         }
     }
 
@@ -312,7 +372,38 @@ impl HudNamebox {
         canvas.draw_sprite(&self.sprites[0], Point::new(0, 0));
         canvas.fill_rect((200, 200, 200), Rect::new(2, 2, 110, 14));
         let start = Point::new(canvas.width() as i32 / 2, 12);
-        canvas.draw_text(&self.font, Align::Center, start, input.name);
+        let name = self.display_name(input);
+        canvas.draw_text(&self.font, Align::Center, start, name);
+    }
+
+    /// Clicking the namebox (or pressing F2) opens the dialogue transcript
+    /// for the current location.  The name shown is also announced for
+    /// screen readers.
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        input: &mut HudInput,
+    ) -> Action<HudCmd> {
+        match event {
+            &Event::ClockTick => {
+                // Announce the name whenever it changes (e.g. as focus moves
+                // around the map).
+                if input.name != self.announced_name {
+                    self.announcer.announce(self.display_name(input));
+                    self.announced_name = input.name;
+                }
+                Action::ignore()
+            }
+            &Event::MouseDown(pt)
+                if input.has_transcript && self.rect.contains_point(pt) =>
+            {
+                Action::redraw().and_return(HudCmd::Transcript)
+            }
+            &Event::KeyDown(Keycode::F2, _) if input.has_transcript => {
+                Action::redraw().and_return(HudCmd::Transcript)
+            }
+            _ => Action::ignore(),
+        }
     }
 }
 
@@ -327,8 +418,9 @@ struct PauseIndicator {
 
 impl PauseIndicator {
     fn new(resources: &mut Resources, visible: Rect) -> PauseIndicator {
+        let text = resources.translate_common(PAUSE_TEXT).to_string();
         let paragraph =
-            Paragraph::new(resources, "roman", Align::Center, PAUSE_TEXT);
+            Paragraph::new(resources, "roman", Align::Center, &text);
         let inner_width = paragraph.min_width();
         let outer_width = inner_width + 2 * PAUSE_TEXT_MARGIN_HORZ;
         let inner_height = paragraph.height();
@@ -375,8 +467,9 @@ struct SkipIndicator {
 
 impl SkipIndicator {
     fn new(resources: &mut Resources, visible: Rect) -> SkipIndicator {
+        let text = resources.translate_common(SKIP_TEXT).to_string();
         let paragraph =
-            Paragraph::new(resources, "roman", Align::Center, SKIP_TEXT);
+            Paragraph::new(resources, "roman", Align::Center, &text);
         let inner_width = paragraph.min_width();
         let outer_width = inner_width + 2 * SKIP_TEXT_MARGIN_HORZ;
         let inner_height = paragraph.height();
@@ -433,3 +526,101 @@ impl Element<HudInput, HudCmd> for SkipIndicator {
 }
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{Hud, HudCmd, HudInput};
+    use crate::gui::{Element, Event, KeyMod, Keycode, Window};
+    use crate::save::{Access, Location};
+
+    fn press(
+        hud: &mut Hud,
+        input: &mut HudInput,
+        event: Event,
+    ) -> Option<HudCmd> {
+        hud.handle_event(&event, input).value().cloned()
+    }
+
+    #[test]
+    fn keyboard_shortcuts() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut hud =
+            Hud::new(&mut window.resources(), visible, Location::Prolog);
+        let mut input = HudInput {
+            name: "Prolog",
+            access: Access::Unsolved,
+            is_paused: false,
+            show_skip: false,
+            active: true,
+            can_undo: true,
+            can_redo: false,
+            can_reset: true,
+            can_hint: true,
+            has_transcript: true,
+        };
+        let cmd = KeyMod::command();
+        let key = |keycode, keymod| Event::KeyDown(keycode, keymod);
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::Z, cmd)),
+            Some(HudCmd::Undo)
+        );
+        assert_eq!(
+            press(
+                &mut hud,
+                &mut input,
+                key(Keycode::Z, cmd | KeyMod::shift())
+            ),
+            None
+        );
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::R, cmd)),
+            Some(HudCmd::Reset)
+        );
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::F1, KeyMod::none())),
+            Some(HudCmd::Info)
+        );
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::Escape, KeyMod::none())),
+            Some(HudCmd::Back)
+        );
+
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::F2, KeyMod::none())),
+            Some(HudCmd::Transcript)
+        );
+
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::F3, KeyMod::none())),
+            Some(HudCmd::Hint)
+        );
+
+        input.can_hint = false;
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::F3, KeyMod::none())),
+            None
+        );
+        input.can_hint = true;
+
+        input.access = Access::Solved;
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::F3, KeyMod::none())),
+            None
+        );
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::R, cmd)),
+            Some(HudCmd::Replay)
+        );
+        assert_eq!(press(&mut hud, &mut input, key(Keycode::Z, cmd)), None);
+
+        input.show_skip = true;
+        input.active = false;
+        assert_eq!(
+            press(&mut hud, &mut input, key(Keycode::Escape, KeyMod::none())),
+            Some(HudCmd::Skip)
+        );
+    }
+}
+
+// ========================================================================= //
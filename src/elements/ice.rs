@@ -18,15 +18,21 @@
 // +--------------------------------------------------------------------------+
 
 use num_integer::div_floor;
+use std::cell::RefCell;
 use std::cmp;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::TryRecvError;
 
+use super::{
+    BackgroundSearch, CursorCmd, CursorGrid, CursorStyle, GridCursor, Hint,
+};
 use crate::gui::{
     Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
     Sound, Sprite,
 };
-use crate::save::ice::{BlockSlide, Object, ObjectGrid, Transform};
+use crate::save::ice::{BlockSlide, Object, ObjectGrid, Symbol, Transform};
 use crate::save::Direction;
 
 // ========================================================================= //
@@ -39,6 +45,9 @@ const SLIDE_START_SPEED: i32 = 5;
 const SLIDE_MAX_SPEED: i32 = 45;
 const SLIDE_ACCEL: i32 = 8;
 
+const HINT_COLOR: (u8, u8, u8) = (255, 128, 255);
+const HINT_EDGE_WIDTH: u32 = 4;
+
 // ========================================================================= //
 
 enum GridSwipe {
@@ -106,14 +115,87 @@ impl SlideAnimation {
 
 // ========================================================================= //
 
+/// A shortest solution, along with the arrangement of blocks and push-pops
+/// before each of its slides and after the last one, so that a player who
+/// follows the hints can be given each next slide without searching again.
+struct CachedSolution {
+    states: Vec<(HashMap<Point, Symbol>, HashMap<Point, Object>)>,
+    moves: Option<Vec<(Point, Direction)>>,
+}
+
+impl CachedSolution {
+    fn solve(mut grid: ObjectGrid, cancelled: &AtomicBool) -> CachedSolution {
+        let moves = grid.solution_unless_cancelled(cancelled);
+        let mut states =
+            vec![(grid.ice_blocks().clone(), grid.objects().clone())];
+        for &(coords, dir) in moves.iter().flatten() {
+            grid.slide_ice_block(coords, dir);
+            states.push((grid.ice_blocks().clone(), grid.objects().clone()));
+        }
+        CachedSolution { states, moves }
+    }
+
+    /// Returns the slide to make next if the grid is in one of the states
+    /// along this solution, or `None` if it has strayed from it.
+    fn next_move(&self, grid: &ObjectGrid) -> Option<NextMove> {
+        let index = self.states.iter().position(|(blocks, objects)| {
+            blocks == grid.ice_blocks() && objects == grid.objects()
+        })?;
+        let slide = self.moves.as_ref().and_then(|moves| moves.get(index));
+        Some(match slide {
+            Some(&(coords, dir)) => NextMove::Slide(coords, dir),
+            None => NextMove::NoMove,
+        })
+    }
+}
+
+/// A search for the solution from a particular arrangement of blocks and
+/// push-pops.
+struct PendingSearch {
+    ice_blocks: HashMap<Point, Symbol>,
+    objects: HashMap<Point, Object>,
+    search: BackgroundSearch<CachedSolution>,
+}
+
+impl PendingSearch {
+    fn start(grid: &ObjectGrid) -> PendingSearch {
+        let grid_clone = grid.clone();
+        PendingSearch {
+            ice_blocks: grid.ice_blocks().clone(),
+            objects: grid.objects().clone(),
+            search: BackgroundSearch::start(move |cancelled| {
+                CachedSolution::solve(grid_clone, cancelled)
+            }),
+        }
+    }
+
+    fn matches(&self, grid: &ObjectGrid) -> bool {
+        &self.ice_blocks == grid.ice_blocks()
+            && &self.objects == grid.objects()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum NextMove {
+    Slide(Point, Direction),
+    NoMove,
+    Searching,
+}
+
+// ========================================================================= //
+
 pub struct GridView {
     rect: Rect,
     obj_sprites: Vec<Sprite>,
     symbol_sprites: Vec<Sprite>,
     drag: Option<GridDrag>,
+    cursor: GridCursor,
     animation: Option<SlideAnimation>,
     font: Rc<Font>,
     letters: HashMap<(i32, i32), char>,
+    hint: Option<(Point, Direction)>,
+    solution: RefCell<Option<CachedSolution>>,
+    search: RefCell<Option<PendingSearch>>,
 }
 
 impl GridView {
@@ -134,9 +216,18 @@ impl GridView {
             obj_sprites: resources.get_sprites("ice/objects"),
             symbol_sprites: resources.get_sprites("ice/symbols"),
             drag: None,
+            cursor: GridCursor::new(
+                left,
+                top,
+                GRID_CELL_SIZE,
+                CursorStyle::Push,
+            ),
             animation: None,
             font: resources.get_font("block"),
             letters: HashMap::new(),
+            hint: None,
+            solution: RefCell::new(None),
+            search: RefCell::new(None),
         }
     }
 
@@ -146,6 +237,10 @@ impl GridView {
 
     pub fn animate_slide(&mut self, slide: &BlockSlide) {
         self.drag = None;
+        self.hint = None;
+        if self.cursor.held().is_some() {
+            self.cursor.follow(slide.to_coords());
+        }
         self.animation = Some(SlideAnimation {
             slide_dir: slide.direction(),
             to_coords: slide.to_coords(),
@@ -158,9 +253,89 @@ impl GridView {
 
     pub fn reset_animation(&mut self) {
         self.drag = None;
+        self.hint = None;
+        self.cursor.release();
         self.animation = None;
     }
 
+    /// Returns the first slide of a shortest solution from the grid's current
+    /// state, or `None` if the grid can no longer be solved or the search for
+    /// a solution hasn't finished yet.
+    pub fn next_move(&self, grid: &ObjectGrid) -> Option<(Point, Direction)> {
+        match self.lookup(grid) {
+            NextMove::Slide(coords, dir) => Some((coords, dir)),
+            NextMove::NoMove | NextMove::Searching => None,
+        }
+    }
+
+    /// Returns the hint to give after `hints_used` earlier hints: the `tip`
+    /// first, and then the next slide, unless the grid has reached a state
+    /// from which it can no longer be solved.  The tip is also given while
+    /// the solution is still being searched for.
+    pub fn hint(
+        &self,
+        grid: &ObjectGrid,
+        hints_used: u32,
+        tip: &'static str,
+    ) -> Hint {
+        match self.lookup(grid) {
+            _ if hints_used == 0 => Hint::Tip(tip),
+            NextMove::Searching => Hint::Tip(tip),
+            NextMove::NoMove => Hint::Tip(STUCK_HINT_TEXT),
+            NextMove::Slide(_, _) => Hint::escalate(hints_used, tip, true),
+        }
+    }
+
+    /// Marks the block that should slide next, and the direction to slide
+    /// it, until the next slide or reset.
+    pub fn highlight_hint(&mut self, grid: &ObjectGrid) {
+        self.hint = self.next_move(grid);
+    }
+
+    /// Finds the next slide from the grid's current state.  Searching a big
+    /// grid takes a while, so the search runs on another thread, and the
+    /// whole solution it finds is kept so that later hints can follow it.
+    fn lookup(&self, grid: &ObjectGrid) -> NextMove {
+        self.poll_search();
+        if let Some(next) =
+            self.solution.borrow().as_ref().and_then(|s| s.next_move(grid))
+        {
+            return next;
+        }
+        let mut search = self.search.borrow_mut();
+        if !search.iter().any(|pending| pending.matches(grid)) {
+            *search = Some(PendingSearch::start(grid));
+        }
+        NextMove::Searching
+    }
+
+    fn poll_search(&self) {
+        let mut search = self.search.borrow_mut();
+        let result = match search.as_ref() {
+            Some(pending) => pending.search.try_result(),
+            None => return,
+        };
+        match result {
+            Ok(solution) => {
+                *self.solution.borrow_mut() = Some(solution);
+                *search = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => *search = None,
+        }
+    }
+
+    /// Waits for any search in progress to finish.
+    #[cfg(test)]
+    pub fn finish_search(&self) {
+        let mut search = self.search.borrow_mut();
+        if let Some(pending) = search.take() {
+            if let Some(solution) = pending.search.wait() {
+                *self.solution.borrow_mut() = Some(solution);
+            }
+        }
+    }
+
     pub fn add_letter(&mut self, coords: (i32, i32), letter: char) {
         self.letters.insert(coords, letter);
     }
@@ -289,6 +464,43 @@ impl GridView {
             canvas.draw_sprite_centered(&self.obj_sprites[0], center);
         }
     }
+
+    fn draw_hint(&self, canvas: &mut Canvas) {
+        if let Some((coords, dir)) = self.hint {
+            let mut canvas = canvas.subcanvas(self.rect);
+            let rect = self.cell_rect(coords);
+            canvas.draw_rect(HINT_COLOR, rect);
+            let inner = Rect::new(
+                rect.x() + 1,
+                rect.y() + 1,
+                rect.width() - 2,
+                rect.height() - 2,
+            );
+            canvas.draw_rect(HINT_COLOR, inner);
+            let size = GRID_CELL_SIZE as u32;
+            let edge = match dir {
+                Direction::East => Rect::new(
+                    rect.right() - HINT_EDGE_WIDTH as i32,
+                    rect.y(),
+                    HINT_EDGE_WIDTH,
+                    size,
+                ),
+                Direction::South => Rect::new(
+                    rect.x(),
+                    rect.bottom() - HINT_EDGE_WIDTH as i32,
+                    size,
+                    HINT_EDGE_WIDTH,
+                ),
+                Direction::West => {
+                    Rect::new(rect.x(), rect.y(), HINT_EDGE_WIDTH, size)
+                }
+                Direction::North => {
+                    Rect::new(rect.x(), rect.y(), size, HINT_EDGE_WIDTH)
+                }
+            };
+            canvas.fill_rect(HINT_COLOR, edge);
+        }
+    }
 }
 
 impl Element<ObjectGrid, (Point, Direction)> for GridView {
@@ -323,6 +535,8 @@ impl Element<ObjectGrid, (Point, Direction)> for GridView {
             canvas.draw_char(&self.font, Align::Center, pt, letter);
         }
         self.draw_ice_blocks(grid, canvas);
+        self.draw_hint(canvas);
+        self.cursor.draw(grid, canvas);
     }
 
     fn handle_event(
@@ -330,6 +544,17 @@ impl Element<ObjectGrid, (Point, Direction)> for GridView {
         event: &Event,
         grid: &mut ObjectGrid,
     ) -> Action<(Point, Direction)> {
+        if self.animation.is_none() {
+            let action = self.cursor.handle_event(event, grid);
+            if action.should_stop() {
+                return match action.value() {
+                    Some(&CursorCmd::Push(coords, dir)) => {
+                        action.but_return((coords, dir))
+                    }
+                    _ => action.but_no_value(),
+                };
+            }
+        }
         match event {
             &Event::ClockTick => {
                 if let Some(mut anim) = self.animation.take() {
@@ -400,3 +625,22 @@ impl Element<ObjectGrid, (Point, Direction)> for GridView {
 }
 
 // ========================================================================= //
+
+const STUCK_HINT_TEXT: &str = "\
+There's no way to solve the puzzle from here.
+Try undoing your last few moves, or resetting
+the puzzle.";
+
+// ========================================================================= //
+
+impl CursorGrid for ObjectGrid {
+    fn grid_size(&self) -> (i32, i32) {
+        self.size()
+    }
+
+    fn can_pick_up(&self, coords: Point) -> bool {
+        self.ice_blocks().contains_key(&coords)
+    }
+}
+
+// ========================================================================= //
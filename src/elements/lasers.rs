@@ -17,15 +17,21 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+use std::cell::RefCell;
 use std::cmp;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::sync::mpsc::TryRecvError;
 
+use super::{
+    color_blind_rgb, color_glyph, draw_color_glyph, BackgroundSearch,
+    CursorCmd, CursorGrid, CursorStyle, GridCursor, Hint, PuzzleCore,
+};
 use crate::gui::{
     Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
     Sound, Sprite, FRAME_DELAY_MILLIS,
 };
-use crate::save::device::{Device, DeviceGrid};
+use crate::save::device::{Device, DeviceGrid, Lasers};
 use crate::save::{Direction, MixedColor};
 
 // ========================================================================= //
@@ -34,6 +40,7 @@ const GRID_CELL_SIZE: i32 = 32;
 const ROTATE_MAX_MILLIS: u32 = 200;
 const LASER_THICKNESS: i32 = 4;
 const ANIM_SLOWDOWN: i32 = 5;
+const HINT_COLOR: (u8, u8, u8) = (255, 128, 255);
 
 // ========================================================================= //
 
@@ -65,11 +72,21 @@ pub struct LaserField {
     sparks_sprites: Vec<Sprite>,
     wall_sprites: Vec<Sprite>,
     drag: Option<GridDrag>,
-    lasers: HashMap<(Point, Direction), (MixedColor, i32)>,
-    sparks: HashMap<(Point, Direction), i32>,
+    cursor: GridCursor,
+    lasers: Lasers,
+    /// Whether `lasers` satisfied every detector when last recalculated.
+    solved: bool,
     anim_counter: i32,
     font: Rc<Font>,
     letters: HashMap<(i32, i32), char>,
+    color_blind: bool,
+    glyph_font: Rc<Font>,
+    hint: Option<LaserCmd>,
+    /// The layout that hints work towards, found the first time a move is
+    /// asked for.  Since devices can be swapped and rotated freely, any
+    /// layout can reach it.
+    solution: RefCell<Option<Option<DeviceGrid>>>,
+    search: RefCell<Option<BackgroundSearch<Option<DeviceGrid>>>>,
 }
 
 impl LaserField {
@@ -92,11 +109,22 @@ impl LaserField {
             sparks_sprites: resources.get_sprites("devices/sparks"),
             wall_sprites: resources.get_sprites("devices/walls"),
             drag: None,
-            lasers: HashMap::new(),
-            sparks: HashMap::new(),
+            cursor: GridCursor::new(
+                left,
+                top,
+                GRID_CELL_SIZE,
+                CursorStyle::Carry,
+            ),
+            lasers: Lasers::default(),
+            solved: false,
             anim_counter: 0,
             font: resources.get_font("block"),
             letters: HashMap::new(),
+            color_blind: resources.color_blind_mode(),
+            glyph_font: resources.get_font("tiny"),
+            hint: None,
+            solution: RefCell::new(None),
+            search: RefCell::new(None),
         };
         laser_field.recalculate_lasers(grid);
         laser_field
@@ -106,48 +134,169 @@ impl LaserField {
         self.letters.insert(coords, letter);
     }
 
-    pub fn satisfied_detector_positions(
-        &self,
-        grid: &DeviceGrid,
-    ) -> HashSet<(i32, i32)> {
-        let mut positions = HashSet::new();
+    /// Returns the next move or rotation towards a layout that satisfies
+    /// every detector, or `None` if the grid already matches that layout (or
+    /// if there is no such layout, or the search for it hasn't finished
+    /// yet).
+    pub fn next_move(&self, grid: &DeviceGrid) -> Option<LaserCmd> {
+        if !self.poll_search(grid) {
+            return None;
+        }
+        let solution = self.solution.borrow();
+        let target = solution.as_ref().and_then(Option::as_ref)?;
         let (num_cols, num_rows) = grid.size();
-        for row in 0..num_rows {
-            for col in 0..num_cols {
-                match grid.get(col, row) {
-                    Some((Device::Detector(color), dir)) => {
-                        let coords = Point::new(col, row);
-                        match self.lasers.get(&(coords, dir)) {
-                            Some(&(laser, _)) if laser == color => {
-                                positions.insert((col, row));
-                            }
-                            _ => {}
-                        }
-                    }
-                    _ => {}
+        let cells: Vec<(i32, i32)> = (0..num_rows)
+            .flat_map(|row| (0..num_cols).map(move |col| (col, row)))
+            .collect();
+        // First get every device into a cell the solution wants it in...
+        for &(col, row) in cells.iter() {
+            let wanted = moveable_at(target, col, row).map(|(dev, _)| dev);
+            let have = moveable_at(grid, col, row).map(|(dev, _)| dev);
+            if wanted == have {
+                continue;
+            }
+            let misplaced = |device: Device| {
+                cells.iter().cloned().find(|&(c, r)| {
+                    let wanted = moveable_at(target, c, r).map(|(d, _)| d);
+                    let have = moveable_at(grid, c, r).map(|(d, _)| d);
+                    have == Some(device) && wanted != Some(device)
+                })
+            };
+            let unfilled = |device: Device| {
+                cells.iter().cloned().find(|&(c, r)| {
+                    let wanted = moveable_at(target, c, r).map(|(d, _)| d);
+                    let have = moveable_at(grid, c, r).map(|(d, _)| d);
+                    wanted == Some(device) && have != Some(device)
+                })
+            };
+            if let Some((c, r)) = wanted.and_then(misplaced) {
+                return Some(LaserCmd::Moved(c, r, col, row));
+            }
+            if let Some((c, r)) = have.and_then(unfilled) {
+                return Some(LaserCmd::Moved(col, row, c, r));
+            }
+        }
+        // ...and then turn each one to face the right way.
+        for &(col, row) in cells.iter() {
+            if let (Some((device, dir)), Some((_, wanted))) =
+                (moveable_at(grid, col, row), moveable_at(target, col, row))
+            {
+                let facing = if device == Device::Mirror {
+                    dir.is_parallel_to(wanted)
+                } else {
+                    dir == wanted
+                };
+                if !facing {
+                    return Some(LaserCmd::Rotated(col, row));
                 }
             }
         }
-        positions
+        None
     }
 
-    pub fn all_detectors_satisfied(&self, grid: &DeviceGrid) -> bool {
-        let (num_cols, num_rows) = grid.size();
-        for row in 0..num_rows {
-            for col in 0..num_cols {
-                match grid.get(col, row) {
-                    Some((Device::Detector(color), dir)) => {
-                        let coords = Point::new(col, row);
-                        match self.lasers.get(&(coords, dir)) {
-                            Some(&(laser, _)) if laser == color => {}
-                            _ => return false,
-                        }
-                    }
-                    _ => {}
-                }
+    /// Returns the hint to give after `hints_used` earlier hints: the `tip`
+    /// first, and then the next move towards a layout that satisfies every
+    /// detector.
+    pub fn hint(
+        &self,
+        grid: &DeviceGrid,
+        hints_used: u32,
+        tip: &'static str,
+    ) -> Hint {
+        if !self.poll_search(grid) || hints_used == 0 {
+            return Hint::Tip(tip);
+        }
+        Hint::escalate(hints_used, tip, self.next_move(grid).is_some())
+    }
+
+    /// Returns true if the solution is known, and otherwise makes sure that
+    /// a search for it is running.  The search can take a while on a big
+    /// grid, so it runs on another thread.
+    fn poll_search(&self, grid: &DeviceGrid) -> bool {
+        if self.solution.borrow().is_some() {
+            return true;
+        }
+        let mut search = self.search.borrow_mut();
+        let result = match search.as_ref() {
+            Some(pending) => pending.try_result(),
+            None => Err(TryRecvError::Disconnected),
+        };
+        match result {
+            Ok(solution) => {
+                *self.solution.borrow_mut() = Some(solution);
+                *search = None;
+                return true;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                let grid = grid.clone();
+                *search = Some(BackgroundSearch::start(move |cancelled| {
+                    grid.solution_unless_cancelled(cancelled)
+                }));
+            }
+        }
+        false
+    }
+
+    /// Waits for any search in progress to finish.
+    #[cfg(test)]
+    pub fn finish_search(&self) {
+        let mut search = self.search.borrow_mut();
+        if let Some(pending) = search.take() {
+            if let Some(solution) = pending.wait() {
+                *self.solution.borrow_mut() = Some(solution);
+            }
+        }
+    }
+
+    /// Makes the next move or rotation towards a layout that satisfies every
+    /// detector, and returns it (or returns `None` if there isn't one yet).
+    pub fn apply_hint(&mut self, grid: &mut DeviceGrid) -> Option<LaserCmd> {
+        let cmd = self.next_move(grid)?;
+        match cmd {
+            LaserCmd::Moved(col1, row1, col2, row2) => {
+                grid.move_to(col1, row1, col2, row2);
             }
+            LaserCmd::Rotated(col, row) => grid.rotate(col, row),
+        }
+        self.recalculate_lasers(grid);
+        Some(cmd)
+    }
+
+    /// Records a move or rotation that was just made, or if it satisfied
+    /// every detector, calls `mark_solved` and begins the outro instead.
+    /// Returns true if the puzzle is now solved.
+    pub fn finish_cmd<F: FnOnce()>(
+        &self,
+        core: &mut PuzzleCore<LaserCmd>,
+        cmd: LaserCmd,
+        mark_solved: F,
+    ) -> bool {
+        if self.solved {
+            mark_solved();
+            core.begin_outro_scene();
+            true
+        } else {
+            core.push_undo(cmd);
+            false
         }
-        true
+    }
+
+    /// Marks the device that should move or rotate next (and where it should
+    /// move to), until the next move or reset.
+    pub fn highlight_hint(&mut self, grid: &DeviceGrid) {
+        self.hint = self.next_move(grid);
+    }
+
+    pub fn satisfied_detector_positions(
+        &self,
+        grid: &DeviceGrid,
+    ) -> HashSet<(i32, i32)> {
+        self.lasers.satisfied_detector_positions(grid)
+    }
+
+    pub fn all_detectors_satisfied(&self, grid: &DeviceGrid) -> bool {
+        self.lasers.all_detectors_satisfied(grid)
     }
 
     fn draw_device_bg(
@@ -243,124 +392,17 @@ impl LaserField {
     }
 
     pub fn clear_lasers(&mut self) {
-        self.lasers.clear();
-        self.sparks.clear();
+        self.lasers = Lasers::default();
+        self.solved = false;
         self.drag = None;
+        self.hint = None;
     }
 
     pub fn recalculate_lasers(&mut self, grid: &DeviceGrid) {
-        self.clear_lasers();
-        let (num_cols, num_rows) = grid.size();
-        let mut queue: VecDeque<(Point, Direction, MixedColor)> =
-            VecDeque::new();
-        for row in 0..num_rows {
-            for col in 0..num_cols {
-                match grid.get(col, row) {
-                    Some((Device::Emitter(color), dir)) => {
-                        if color != MixedColor::Black {
-                            let coords = Point::new(col, row);
-                            self.lasers.insert((coords, dir), (color, 10));
-                            queue.push_back((coords, dir, color));
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-        while let Some((coords, laser_dir, color)) = queue.pop_front() {
-            let next = coords + laser_dir.delta();
-            let anti_dir = laser_dir.opposite();
-            if self.lasers.contains_key(&(next, anti_dir)) {
-                if !self.sparks.contains_key(&(next, anti_dir)) {
-                    self.sparks.insert((coords, laser_dir), 0);
-                }
-                continue;
-            }
-            match grid.get(next.x(), next.y()) {
-                Some((Device::Wall, _)) | Some((Device::Emitter(_), _)) => {
-                    self.sparks.insert((coords, laser_dir), 0);
-                }
-                Some((Device::Channel, ch_dir))
-                    if !ch_dir.is_parallel_to(laser_dir) =>
-                {
-                    self.sparks.insert((coords, laser_dir), 0);
-                }
-                Some((Device::Channel, _))
-                | Some((Device::CrossChannel, _))
-                | None => {
-                    let perp_dir = laser_dir.rotated_cw();
-                    let mut dist = GRID_CELL_SIZE / 2;
-                    if self.lasers.contains_key(&(next, perp_dir)) {
-                        dist -= LASER_THICKNESS / 2;
-                    }
-                    self.lasers.insert((next, anti_dir), (color, dist));
-                    self.lasers.insert((next, laser_dir), (color, dist));
-                    queue.push_back((next, laser_dir, color));
-                }
-                Some((Device::Detector(det_color), det_dir)) => {
-                    if det_dir == anti_dir {
-                        self.lasers.insert((next, anti_dir), (color, 10));
-                        if det_color != color {
-                            self.sparks.insert((next, anti_dir), 10);
-                        }
-                    } else {
-                        self.sparks.insert((coords, laser_dir), 0);
-                    }
-                }
-                Some((Device::Mirror, mir_dir)) => {
-                    let mut reflect_dir = match anti_dir {
-                        Direction::East => Direction::South,
-                        Direction::South => Direction::East,
-                        Direction::West => Direction::North,
-                        Direction::North => Direction::West,
-                    };
-                    if mir_dir.is_vertical() {
-                        reflect_dir = reflect_dir.opposite();
-                    }
-                    self.lasers.insert((next, anti_dir), (color, 15));
-                    self.lasers.insert((next, reflect_dir), (color, 15));
-                    queue.push_back((next, reflect_dir, color));
-                }
-                Some((Device::Splitter, split_dir)) => {
-                    if split_dir == laser_dir {
-                        self.lasers.insert((next, anti_dir), (color, 6));
-                        let left_dir = laser_dir.rotated_ccw();
-                        let right_dir = laser_dir.rotated_cw();
-                        self.lasers.insert((next, left_dir), (color, 6));
-                        self.lasers.insert((next, right_dir), (color, 6));
-                        self.sparks.remove(&(next, left_dir));
-                        self.sparks.remove(&(next, right_dir));
-                        queue.push_back((next, left_dir, color));
-                        queue.push_back((next, right_dir, color));
-                    } else if split_dir == anti_dir {
-                        self.lasers.insert((next, anti_dir), (color, 3));
-                        self.sparks.insert((next, anti_dir), 3);
-                    } else {
-                        self.lasers.insert((next, anti_dir), (color, 6));
-                        self.sparks.insert((next, anti_dir), 6);
-                    }
-                }
-                Some((Device::Mixer, mixer_dir)) => {
-                    if mixer_dir == laser_dir {
-                        self.lasers.insert((next, anti_dir), (color, 1));
-                        self.sparks.insert((next, anti_dir), 1);
-                    } else if mixer_dir == anti_dir {
-                        self.lasers.insert((next, anti_dir), (color, 3));
-                        self.sparks.insert((next, anti_dir), 3);
-                    } else {
-                        self.lasers.insert((next, anti_dir), (color, 3));
-                        if let Some(&(other, _)) =
-                            self.lasers.get(&(next, laser_dir))
-                        {
-                            let output = mixer_output(color, other);
-                            self.lasers.insert((next, mixer_dir), (output, 3));
-                            self.sparks.remove(&(next, mixer_dir));
-                            queue.push_back((next, mixer_dir, output));
-                        }
-                    }
-                }
-            }
-        }
+        self.drag = None;
+        self.hint = None;
+        self.lasers = grid.lasers();
+        self.solved = self.lasers.all_detectors_satisfied(grid);
     }
 
     pub fn draw_immovables(&self, grid: &DeviceGrid, canvas: &mut Canvas) {
@@ -436,16 +478,11 @@ impl LaserField {
 
     pub fn draw_lasers(&self, canvas: &mut Canvas) {
         let mut canvas = canvas.subcanvas(self.rect);
-        for (&(coords, dir), &(laser_color, dist)) in self.lasers.iter() {
-            let fill_color = match laser_color {
-                MixedColor::Black => unreachable!(),
-                MixedColor::Red => (255, 64, 64),
-                MixedColor::Green => (64, 255, 64),
-                MixedColor::Yellow => (255, 255, 64),
-                MixedColor::Blue => (64, 64, 255),
-                MixedColor::Magenta => (255, 64, 255),
-                MixedColor::Cyan => (64, 255, 255),
-                MixedColor::White => (255, 255, 255),
+        for (&(coords, dir), &(laser_color, dist)) in self.lasers.beams() {
+            let fill_color = if self.color_blind {
+                color_blind_rgb(laser_color)
+            } else {
+                laser_rgb(laser_color)
             };
             let mut fill_rect = match dir {
                 Direction::East => Rect::new(
@@ -481,8 +518,61 @@ impl LaserField {
         }
     }
 
+    /// In color-blind mode, labels each emitter and detector with the letter
+    /// for its color.
+    fn draw_color_glyphs(&self, grid: &DeviceGrid, canvas: &mut Canvas) {
+        let (num_cols, num_rows) = grid.size();
+        let mut canvas = canvas.subcanvas(self.rect);
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                let color = match grid.get(col, row) {
+                    Some((Device::Emitter(color), _))
+                    | Some((Device::Detector(color), _)) => color,
+                    _ => continue,
+                };
+                let center = Point::new(
+                    col * GRID_CELL_SIZE + GRID_CELL_SIZE / 2,
+                    row * GRID_CELL_SIZE + GRID_CELL_SIZE / 2,
+                );
+                draw_color_glyph(
+                    &mut canvas,
+                    &self.glyph_font,
+                    center,
+                    color_glyph(color),
+                );
+            }
+        }
+    }
+
+    fn draw_hint(&self, canvas: &mut Canvas) {
+        let cells = match self.hint {
+            Some(LaserCmd::Moved(col1, row1, col2, row2)) => {
+                vec![(col1, row1), (col2, row2)]
+            }
+            Some(LaserCmd::Rotated(col, row)) => vec![(col, row)],
+            None => return,
+        };
+        let mut canvas = canvas.subcanvas(self.rect);
+        for (col, row) in cells {
+            let rect = Rect::new(
+                col * GRID_CELL_SIZE,
+                row * GRID_CELL_SIZE,
+                GRID_CELL_SIZE as u32,
+                GRID_CELL_SIZE as u32,
+            );
+            canvas.draw_rect(HINT_COLOR, rect);
+            let inner = Rect::new(
+                rect.x() + 1,
+                rect.y() + 1,
+                rect.width() - 2,
+                rect.height() - 2,
+            );
+            canvas.draw_rect(HINT_COLOR, inner);
+        }
+    }
+
     pub fn draw_sparks(&self, canvas: &mut Canvas) {
-        for (&(coords, dir), &dist) in self.sparks.iter() {
+        for (&(coords, dir), &dist) in self.lasers.sparks() {
             let center = self.rect.top_left()
                 + dir.delta() * (GRID_CELL_SIZE / 2 - dist)
                 + Point::new(
@@ -516,7 +606,11 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
         self.draw_movables_bg(grid, canvas);
         self.draw_lasers(canvas);
         self.draw_movables_fg(grid, canvas);
+        if self.color_blind {
+            self.draw_color_glyphs(grid, canvas);
+        }
         self.draw_sparks(canvas);
+        self.draw_hint(canvas);
         if let Some(ref drag) = self.drag {
             if drag.from_pt != drag.to_pt {
                 let center = self.rect.top_left() + drag.to_pt;
@@ -524,6 +618,7 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
                 self.draw_device_fg(canvas, center, drag.device, drag.dir);
             }
         }
+        self.cursor.draw(grid, canvas);
     }
 
     fn handle_event(
@@ -531,6 +626,36 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
         event: &Event,
         grid: &mut DeviceGrid,
     ) -> Action<LaserCmd> {
+        let action = self.cursor.handle_event(event, grid);
+        if action.should_stop() {
+            return match action.value() {
+                Some(&CursorCmd::Drop(from, to)) => {
+                    let success =
+                        grid.move_to(from.x(), from.y(), to.x(), to.y());
+                    self.recalculate_lasers(grid);
+                    if success {
+                        action
+                            .but_return(LaserCmd::Moved(
+                                from.x(),
+                                from.y(),
+                                to.x(),
+                                to.y(),
+                            ))
+                            .and_play_sound(Sound::device_drop())
+                    } else {
+                        action.but_no_value()
+                    }
+                }
+                Some(&CursorCmd::Rotate(coords)) => {
+                    grid.rotate(coords.x(), coords.y());
+                    self.recalculate_lasers(grid);
+                    action
+                        .but_return(LaserCmd::Rotated(coords.x(), coords.y()))
+                        .and_play_sound(Sound::device_rotate())
+                }
+                _ => action.but_no_value(),
+            };
+        }
         match event {
             &Event::ClockTick => {
                 if let Some(ref mut drag) = self.drag {
@@ -542,7 +667,7 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
                 self.anim_counter += 1;
                 self.anim_counter %= 2 * ANIM_SLOWDOWN;
                 if self.anim_counter % ANIM_SLOWDOWN == 0
-                    && !self.sparks.is_empty()
+                    && !self.lasers.sparks().is_empty()
                 {
                     return Action::redraw();
                 }
@@ -571,8 +696,7 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
             &Event::MouseDrag(pt) => {
                 if let Some(ref mut drag) = self.drag {
                     drag.to_pt = pt - self.rect.top_left();
-                    self.lasers.clear();
-                    self.sparks.clear();
+                    self.lasers = Lasers::default();
                     let mut action = Action::redraw();
                     if !drag.moved {
                         drag.moved = true;
@@ -630,6 +754,19 @@ impl Element<DeviceGrid, LaserCmd> for LaserField {
     }
 }
 
+impl CursorGrid for DeviceGrid {
+    fn grid_size(&self) -> (i32, i32) {
+        self.size()
+    }
+
+    fn can_pick_up(&self, coords: Point) -> bool {
+        match self.get(coords.x(), coords.y()) {
+            Some((device, _)) => device.is_moveable(),
+            None => false,
+        }
+    }
+}
+
 // ========================================================================= //
 
 pub struct DangerSign {
@@ -675,6 +812,14 @@ impl DangerSign {
 
 // ========================================================================= //
 
+fn moveable_at(
+    grid: &DeviceGrid,
+    col: i32,
+    row: i32,
+) -> Option<(Device, Direction)> {
+    grid.get(col, row).filter(|&(device, _)| device.is_moveable())
+}
+
 fn color_index(color: MixedColor) -> usize {
     match color {
         MixedColor::Black => 0,
@@ -688,17 +833,17 @@ fn color_index(color: MixedColor) -> usize {
     }
 }
 
-fn mixer_output(color1: MixedColor, color2: MixedColor) -> MixedColor {
-    let red = (color1.has_red() && color2.has_red())
-        || (color1.has_green() && color2.has_blue())
-        || (color1.has_blue() && color2.has_green());
-    let green = (color1.has_green() && color2.has_green())
-        || (color1.has_red() && color2.has_blue())
-        || (color1.has_blue() && color2.has_red());
-    let blue = (color1.has_blue() && color2.has_blue())
-        || (color1.has_red() && color2.has_green())
-        || (color1.has_green() && color2.has_red());
-    MixedColor::from_rgb(red, green, blue)
+fn laser_rgb(color: MixedColor) -> (u8, u8, u8) {
+    match color {
+        MixedColor::Black => unreachable!(),
+        MixedColor::Red => (255, 64, 64),
+        MixedColor::Green => (64, 255, 64),
+        MixedColor::Yellow => (255, 255, 64),
+        MixedColor::Blue => (64, 64, 255),
+        MixedColor::Magenta => (255, 64, 255),
+        MixedColor::Cyan => (64, 255, 255),
+        MixedColor::White => (255, 255, 255),
+    }
 }
 
 // ========================================================================= //
@@ -20,6 +20,7 @@
 pub mod column;
 pub mod cross;
 mod crossword;
+mod cursor;
 pub mod cutscene;
 mod dialog;
 pub mod factor;
@@ -28,21 +29,32 @@ mod hud;
 pub mod ice;
 pub mod lasers;
 pub mod memory;
+mod palette;
 mod paragraph;
 pub mod plane;
 mod progress;
 mod puzzle;
+mod search;
 pub mod shift;
 mod stars;
+mod transcript;
 
 pub use self::crossword::CrosswordView;
-pub use self::cutscene::{Ast, Scene, TalkPos, TalkStyle, Theater};
+pub use self::cursor::{
+    nearest_in_direction, CursorCmd, CursorGrid, CursorStyle, GridCursor,
+};
+pub use self::cutscene::{
+    Ast, Scene, TalkPos, TalkStyle, Theater, TranscriptLine,
+};
 pub use self::dialog::DialogBox;
 pub use self::fade::{FadeStyle, ScreenFade};
 pub use self::hud::{Hud, HudCmd, HudInput};
+pub use self::palette::{color_blind_rgb, color_glyph, draw_color_glyph};
 pub use self::paragraph::Paragraph;
 pub use self::progress::ProgressBar;
-pub use self::puzzle::{PuzzleCmd, PuzzleCore, PuzzleView};
+pub use self::puzzle::{Hint, PuzzleCmd, PuzzleCore, PuzzleView};
+pub use self::search::BackgroundSearch;
 pub use self::stars::MovingStars;
+pub use self::transcript::TranscriptView;
 
 // ========================================================================= //
@@ -0,0 +1,111 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use crate::gui::{Align, Canvas, Font, Point, Rect};
+use crate::save::MixedColor;
+
+// ========================================================================= //
+
+const GLYPH_BADGE_COLOR: (u8, u8, u8) = (255, 255, 255);
+const GLYPH_BADGE_SIZE: u32 = 7;
+
+// ========================================================================= //
+
+/// Returns the color to draw `color` with in color-blind mode.  These are
+/// based on the Okabe-Ito palette, which stays distinguishable under the
+/// common forms of color blindness.
+pub fn color_blind_rgb(color: MixedColor) -> (u8, u8, u8) {
+    match color {
+        MixedColor::Black => (0, 0, 0),
+        MixedColor::Red => (213, 94, 0),
+        MixedColor::Green => (0, 158, 115),
+        MixedColor::Yellow => (240, 228, 66),
+        MixedColor::Blue => (0, 114, 178),
+        MixedColor::Magenta => (204, 121, 167),
+        MixedColor::Cyan => (86, 180, 233),
+        MixedColor::White => (255, 255, 255),
+    }
+}
+
+/// Returns the letter that marks pieces of the given color in color-blind
+/// mode.
+pub fn color_glyph(color: MixedColor) -> char {
+    match color {
+        MixedColor::Black => 'K',
+        MixedColor::Red => 'R',
+        MixedColor::Green => 'G',
+        MixedColor::Yellow => 'Y',
+        MixedColor::Blue => 'B',
+        MixedColor::Magenta => 'M',
+        MixedColor::Cyan => 'C',
+        MixedColor::White => 'W',
+    }
+}
+
+/// Draws a color-blind-mode letter, on a small white badge so that it can be
+/// read on top of any color, centered on the given point.  The font should
+/// be the "tiny" font.
+pub fn draw_color_glyph(
+    canvas: &mut Canvas,
+    font: &Font,
+    center: Point,
+    glyph: char,
+) {
+    let half = (GLYPH_BADGE_SIZE / 2) as i32;
+    let rect = Rect::new(
+        center.x() - half,
+        center.y() - half,
+        GLYPH_BADGE_SIZE,
+        GLYPH_BADGE_SIZE,
+    );
+    canvas.fill_rect(GLYPH_BADGE_COLOR, rect);
+    canvas.draw_char(font, Align::Center, center + Point::new(0, 3), glyph);
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{color_blind_rgb, color_glyph};
+    use crate::save::MixedColor;
+    use std::collections::HashSet;
+
+    const ALL_COLORS: &[MixedColor] = &[
+        MixedColor::Black,
+        MixedColor::Red,
+        MixedColor::Green,
+        MixedColor::Yellow,
+        MixedColor::Blue,
+        MixedColor::Magenta,
+        MixedColor::Cyan,
+        MixedColor::White,
+    ];
+
+    #[test]
+    fn colors_are_all_distinct() {
+        let rgbs: HashSet<_> =
+            ALL_COLORS.iter().map(|&c| color_blind_rgb(c)).collect();
+        assert_eq!(rgbs.len(), ALL_COLORS.len());
+        let glyphs: HashSet<_> =
+            ALL_COLORS.iter().map(|&c| color_glyph(c)).collect();
+        assert_eq!(glyphs.len(), ALL_COLORS.len());
+    }
+}
+
+// ========================================================================= //
@@ -22,6 +22,7 @@ use std::collections::HashMap;
 use std::mem;
 use std::rc::Rc;
 
+use super::draw_color_glyph;
 use crate::gui::{
     Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
     Sprite,
@@ -50,6 +51,8 @@ pub struct PlaneGridView {
     changes: Vec<(Point, Point)>,
     font: Rc<Font>,
     letters: HashMap<Point, char>,
+    color_blind: bool,
+    glyph_font: Rc<Font>,
 }
 
 impl PlaneGridView {
@@ -67,6 +70,8 @@ impl PlaneGridView {
             changes: Vec::new(),
             font: resources.get_font("roman"),
             letters: HashMap::new(),
+            color_blind: resources.color_blind_mode(),
+            glyph_font: resources.get_font("tiny"),
         }
     }
 
@@ -206,6 +211,20 @@ impl Element<PlaneGrid, PlaneCmd> for PlaneGridView {
             );
             canvas.draw_char(&self.font, Align::Center, pt, letter);
         }
+        if self.color_blind {
+            for (&coords, &obj) in grid.objects() {
+                if let Some(glyph) = node_glyph(obj) {
+                    let center = coords * TILE_ISIZE
+                        + Point::new(TILE_ISIZE / 2, TILE_ISIZE / 2);
+                    draw_color_glyph(
+                        &mut canvas,
+                        &self.glyph_font,
+                        center,
+                        glyph,
+                    );
+                }
+            }
+        }
     }
 
     fn handle_event(
@@ -246,3 +265,16 @@ impl Element<PlaneGrid, PlaneCmd> for PlaneGridView {
 }
 
 // ========================================================================= //
+
+/// Returns the letter that marks a colored node in color-blind mode.
+fn node_glyph(obj: PlaneObj) -> Option<char> {
+    match obj {
+        PlaneObj::PurpleNode => Some('P'),
+        PlaneObj::RedNode => Some('R'),
+        PlaneObj::GreenNode => Some('G'),
+        PlaneObj::BlueNode => Some('B'),
+        PlaneObj::Wall | PlaneObj::Cross | PlaneObj::GrayNode => None,
+    }
+}
+
+// ========================================================================= //
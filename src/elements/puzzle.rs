@@ -21,12 +21,14 @@ use std::collections::HashMap;
 
 use crate::elements::{
     FadeStyle, Hud, HudCmd, HudInput, Scene, ScreenFade, Theater,
+    TranscriptView,
 };
 use crate::gui::{Action, Canvas, Element, Event, Rect, Resources};
 use crate::save::{Access, Game, Location, PuzzleState};
 
 // ========================================================================= //
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PuzzleCmd {
     Back,
     Info,
@@ -35,12 +37,46 @@ pub enum PuzzleCmd {
     Reset,
     Replay,
     Solve,
+    Hint,
     Next,
     Save,
 }
 
 // ========================================================================= //
 
+/// A nudge for a stuck player, as returned by `PuzzleView::hint`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Hint {
+    /// Show the given text in an info box.
+    Tip(&'static str),
+    /// Point out the next move, without making it.
+    Highlight,
+    /// Make the next move for the player.
+    Apply,
+}
+
+impl Hint {
+    /// Chooses how big a nudge to give, given how many hints the player has
+    /// already used on this puzzle: first a general tip, then the next move
+    /// highlighted, and from then on the next move applied.  If `has_move` is
+    /// false (e.g. because the puzzle has no solver), always gives the tip.
+    pub fn escalate(
+        hints_used: u32,
+        tip: &'static str,
+        has_move: bool,
+    ) -> Hint {
+        if !has_move || hints_used == 0 {
+            Hint::Tip(tip)
+        } else if hints_used == 1 {
+            Hint::Highlight
+        } else {
+            Hint::Apply
+        }
+    }
+}
+
+// ========================================================================= //
+
 pub trait PuzzleView: Element<Game, PuzzleCmd> {
     fn info_text(&self, game: &Game) -> &'static str;
 
@@ -52,6 +88,20 @@ pub trait PuzzleView: Element<Game, PuzzleCmd> {
 
     fn solve(&mut self, game: &mut Game);
 
+    /// Returns true if this puzzle gives hints beyond its info text.  Views
+    /// that return true should also call `PuzzleCore::enable_hints`.
+    fn supports_hints(&self) -> bool {
+        false
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        Hint::Tip(self.info_text(game))
+    }
+
+    fn highlight_hint(&mut self, _game: &Game) {}
+
+    fn apply_hint(&mut self, _game: &mut Game) {}
+
     fn drain_queue(&mut self);
 }
 
@@ -64,10 +114,12 @@ pub struct PuzzleCore<U> {
     outro_scene: Scene,
     extra_scenes: HashMap<i32, Scene>,
     hud: Hud,
+    transcript: TranscriptView,
     screen_fade: ScreenFade<PuzzleCmd>,
     undo_stack: Vec<U>,
     redo_stack: Vec<U>,
     previously_solved: bool,
+    hints_enabled: bool,
 }
 
 impl<U: Clone> PuzzleCore<U> {
@@ -80,6 +132,7 @@ impl<U: Clone> PuzzleCore<U> {
         mut outro_scene: Scene,
     ) -> PuzzleCore<U> {
         let mut theater = Theater::new();
+        theater.set_announcer(resources.announcer());
         if state.is_visited() {
             intro_scene.skip(&mut theater);
             if state.is_solved() {
@@ -95,13 +148,21 @@ impl<U: Clone> PuzzleCore<U> {
             outro_scene,
             extra_scenes: HashMap::new(),
             hud: Hud::new(resources, visible, S::location()),
+            transcript: TranscriptView::new(resources, visible),
             screen_fade: ScreenFade::new(resources, fade.0, fade.1),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             previously_solved: state.access() >= Access::Solved,
+            hints_enabled: false,
         }
     }
 
+    /// Enables the HUD's Hint button, for puzzles whose view supports hints
+    /// (see `PuzzleView::supports_hints`).
+    pub fn enable_hints(&mut self) {
+        self.hints_enabled = true;
+    }
+
     pub fn flash_info_button(&mut self) {
         self.hud.flash_info_button();
     }
@@ -201,6 +262,8 @@ impl<U: Clone> PuzzleCore<U> {
             can_undo: !self.undo_stack.is_empty(),
             can_redo: !self.redo_stack.is_empty(),
             can_reset,
+            can_hint: self.hints_enabled,
+            has_transcript: true,
         }
     }
 
@@ -228,6 +291,7 @@ impl<U: Clone> PuzzleCore<U> {
     ) {
         self.theater.draw_speech_bubbles(canvas);
         self.hud.draw(&self.hud_input(state), canvas);
+        self.transcript.draw(&self.theater, canvas);
         self.screen_fade.draw(&(), canvas);
     }
 
@@ -237,6 +301,15 @@ impl<U: Clone> PuzzleCore<U> {
         state: &mut S,
     ) -> Action<PuzzleCmd> {
         let mut action = self.screen_fade.handle_event(event, &mut ());
+        if !action.should_stop() && self.transcript.is_open() {
+            // While the transcript is open, the scene and puzzle are frozen.
+            action.merge(
+                self.transcript
+                    .handle_event(event, &mut self.theater)
+                    .but_no_value(),
+            );
+            return action;
+        }
         if event == &Event::ClockTick {
             if self.theater.tick_animations() {
                 action.also_redraw();
@@ -259,6 +332,11 @@ impl<U: Clone> PuzzleCore<U> {
                     subaction.but_no_value()
                 }
                 Some(&HudCmd::Solve) => subaction.but_return(PuzzleCmd::Solve),
+                Some(&HudCmd::Hint) => subaction.but_return(PuzzleCmd::Hint),
+                Some(&HudCmd::Transcript) => {
+                    self.transcript.open(&self.theater);
+                    subaction.but_no_value()
+                }
                 Some(&HudCmd::Skip) => {
                     if !self.intro_scene.is_finished() {
                         self.intro_scene.skip(&mut self.theater);
@@ -307,3 +385,20 @@ impl<U: Clone> PuzzleCore<U> {
 }
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::Hint;
+
+    #[test]
+    fn hints_escalate() {
+        let tip = "Try harder.";
+        assert_eq!(Hint::escalate(0, tip, true), Hint::Tip(tip));
+        assert_eq!(Hint::escalate(1, tip, true), Hint::Highlight);
+        assert_eq!(Hint::escalate(2, tip, true), Hint::Apply);
+        assert_eq!(Hint::escalate(7, tip, true), Hint::Apply);
+        assert_eq!(Hint::escalate(3, tip, false), Hint::Tip(tip));
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,106 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+// ========================================================================= //
+
+/// A search for a puzzle's solution, running on another thread so that the
+/// event loop keeps going while it runs.  Dropping the search before it
+/// finishes cancels it.
+pub struct BackgroundSearch<T> {
+    receiver: Receiver<T>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> BackgroundSearch<T> {
+    /// Starts running `search` on another thread.  The search is passed a
+    /// flag that gets set once its result is no longer wanted, and should
+    /// give up as soon as it sees it.
+    pub fn start<F>(search: F) -> BackgroundSearch<T>
+    where
+        F: FnOnce(&AtomicBool) -> T + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+        thread::spawn(move || {
+            let result = search(&flag);
+            if !flag.load(Ordering::Relaxed) {
+                let _ = sender.send(result);
+            }
+        });
+        BackgroundSearch { receiver, cancelled }
+    }
+
+    /// Returns the search's result if it has finished, `Empty` if it is
+    /// still running, or `Disconnected` if it died without a result.
+    pub fn try_result(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Waits for the search to finish and returns its result.
+    #[cfg(test)]
+    pub fn wait(&self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<T> Drop for BackgroundSearch<T> {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::Arc;
+
+    use super::BackgroundSearch;
+
+    #[test]
+    fn search_returns_result() {
+        let search = BackgroundSearch::start(|_| 42);
+        assert_eq!(search.wait(), Some(42));
+        assert_eq!(search.try_result(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn dropping_search_cancels_it() {
+        let seen = Arc::new(AtomicBool::new(false));
+        let seen_clone = seen.clone();
+        let search = BackgroundSearch::start(move |cancelled| {
+            while !cancelled.load(Ordering::Relaxed) {}
+            seen_clone.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(search.try_result(), Err(TryRecvError::Empty));
+        drop(search);
+        while !seen.load(Ordering::Relaxed) {}
+    }
+}
+
+// ========================================================================= //
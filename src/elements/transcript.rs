@@ -0,0 +1,388 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::cmp;
+use std::rc::Rc;
+
+use crate::elements::{Paragraph, TalkStyle, Theater, TranscriptLine};
+use crate::gui::{
+    Action, Align, Canvas, Element, Event, Font, Keycode, Point, Rect,
+    Resources,
+};
+
+// ========================================================================= //
+
+const PANEL_MARGIN_HORZ: i32 = 48;
+const PANEL_MARGIN_TOP: i32 = 24;
+const PANEL_MARGIN_BOTTOM: i32 = 40;
+const LIST_MARGIN: i32 = 8;
+const LIST_TOP: i32 = 24;
+const ENTRY_MARGIN: i32 = 6;
+const ENTRY_SPACING: i32 = 6;
+const LINE_SCROLL: i32 = 16;
+const EMPTY_TEXT: &str = "Nothing has been said here yet.";
+const SKIPPED_TEXT: &str = "(skipped)";
+const TITLE_TEXT: &str = "Transcript";
+const COMM_TEXT: &str = "comm";
+const THINKING_TEXT: &str = "thinking";
+
+// ========================================================================= //
+
+/// An overlay that shows every line of dialogue recorded in a `Theater`,
+/// with the most recent line at the bottom.  It can be scrolled with the
+/// arrow keys or by dragging, and is closed by a click or by ESC.  Each line
+/// is labelled with its speaker's name, where known.
+pub struct TranscriptView {
+    font: Rc<Font>,
+    empty: Paragraph,
+    comm_text: String,
+    thinking_text: String,
+    rect: Rect,
+    open: bool,
+    scroll: i32,
+    drag_from: Option<i32>,
+    dragged: bool,
+}
+
+impl TranscriptView {
+    pub fn new(resources: &mut Resources, visible: Rect) -> TranscriptView {
+        let rect = Rect::new(
+            visible.left() + PANEL_MARGIN_HORZ,
+            visible.top() + PANEL_MARGIN_TOP,
+            visible.width() - 2 * PANEL_MARGIN_HORZ as u32,
+            visible.height() - (PANEL_MARGIN_TOP + PANEL_MARGIN_BOTTOM) as u32,
+        );
+        TranscriptView {
+            font: resources.get_font("roman"),
+            empty: Paragraph::new(
+                resources,
+                "roman",
+                Align::Center,
+                EMPTY_TEXT,
+            ),
+            comm_text: resources.translate_common(COMM_TEXT).to_string(),
+            thinking_text: resources
+                .translate_common(THINKING_TEXT)
+                .to_string(),
+            rect,
+            open: false,
+            scroll: 0,
+            drag_from: None,
+            dragged: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the overlay, scrolled to the most recent line.
+    pub fn open(&mut self, theater: &Theater) {
+        self.open = true;
+        self.scroll = self.max_scroll(theater);
+        self.drag_from = None;
+        self.dragged = false;
+    }
+
+    fn list_rect(&self) -> Rect {
+        Rect::new(
+            LIST_MARGIN,
+            LIST_TOP,
+            self.rect.width() - 2 * LIST_MARGIN as u32,
+            self.rect.height() - (LIST_TOP + LIST_MARGIN) as u32,
+        )
+    }
+
+    fn content_height(&self, theater: &Theater) -> i32 {
+        theater
+            .transcript()
+            .iter()
+            .map(|line| self.entry_height(line) + ENTRY_SPACING)
+            .sum::<i32>()
+            - ENTRY_SPACING
+    }
+
+    fn max_scroll(&self, theater: &Theater) -> i32 {
+        let list_height = self.list_rect().height() as i32;
+        cmp::max(0, self.content_height(theater) - list_height)
+    }
+
+    fn scroll_by(&mut self, theater: &Theater, delta: i32) -> Action<()> {
+        let old_scroll = self.scroll;
+        self.scroll = cmp::max(
+            0,
+            cmp::min(self.max_scroll(theater), self.scroll + delta),
+        );
+        Action::redraw_if(self.scroll != old_scroll).and_stop()
+    }
+
+    fn close(&mut self) -> Action<()> {
+        self.open = false;
+        self.drag_from = None;
+        Action::redraw().and_stop()
+    }
+
+    /// Returns the label to show above a line, naming who said it and how.
+    fn speaker_label(&self, line: &TranscriptLine) -> Option<String> {
+        match (line.style, line.speaker) {
+            (TalkStyle::Comm, Some(name)) => {
+                Some(format!("{} ({})", name, self.comm_text))
+            }
+            (TalkStyle::Comm, None) => Some(format!("({})", self.comm_text)),
+            (TalkStyle::Thought, Some(name)) => {
+                Some(format!("{} ({})", name, self.thinking_text))
+            }
+            (_, speaker) => speaker.map(str::to_string),
+        }
+    }
+
+    fn label_height(&self, line: &TranscriptLine) -> i32 {
+        if self.speaker_label(line).is_some() {
+            self.font.height() as i32
+        } else {
+            0
+        }
+    }
+
+    fn entry_height(&self, line: &TranscriptLine) -> i32 {
+        self.label_height(line)
+            + line.paragraph.height() as i32
+            + 2 * ENTRY_MARGIN
+    }
+}
+
+impl Element<Theater, ()> for TranscriptView {
+    fn draw(&self, theater: &Theater, canvas: &mut Canvas) {
+        if !self.open {
+            return;
+        }
+        canvas.fill_rect((255, 255, 255), self.rect);
+        let mut canvas = canvas.subcanvas(self.rect);
+        let inner =
+            Rect::new(1, 1, self.rect.width() - 2, self.rect.height() - 2);
+        canvas.draw_rect((0, 0, 0), inner);
+        let center_x = self.rect.width() as i32 / 2;
+        canvas.draw_text(
+            &self.font,
+            Align::Center,
+            Point::new(center_x, LIST_TOP - 8),
+            TITLE_TEXT,
+        );
+        let list_rect = self.list_rect();
+        canvas.fill_rect((200, 200, 200), list_rect);
+        let mut canvas = canvas.subcanvas(list_rect);
+        let lines = theater.transcript();
+        if lines.is_empty() {
+            let width = self.empty.min_width();
+            let height = self.empty.height();
+            let rect = Rect::new(
+                (list_rect.width() as i32 - width) / 2,
+                (list_rect.height() - height) as i32 / 2,
+                width as u32,
+                height,
+            );
+            self.empty.draw(&mut canvas.subcanvas(rect));
+            return;
+        }
+        let mut top = -self.scroll;
+        for line in lines.iter() {
+            let height = self.entry_height(line);
+            if top + height > 0 && top < list_rect.height() as i32 {
+                let label = self.speaker_label(line);
+                let label_width = label
+                    .as_ref()
+                    .map_or(0, |label| self.font.text_width(label));
+                let width = cmp::max(line.paragraph.min_width(), label_width)
+                    + 2 * ENTRY_MARGIN;
+                let rect = Rect::new(0, top, width as u32, height as u32);
+                canvas.fill_rect(line.bg_color, rect);
+                let border =
+                    if line.skipped { (128, 128, 128) } else { (0, 0, 0) };
+                canvas.draw_rect(border, rect);
+                if let Some(ref label) = label {
+                    let baseline = top + self.font.baseline() + ENTRY_MARGIN;
+                    canvas.draw_text(
+                        &self.font,
+                        Align::Left,
+                        Point::new(ENTRY_MARGIN, baseline),
+                        label,
+                    );
+                }
+                let text_rect = Rect::new(
+                    ENTRY_MARGIN,
+                    top + ENTRY_MARGIN + self.label_height(line),
+                    line.paragraph.min_width() as u32,
+                    line.paragraph.height(),
+                );
+                line.paragraph.draw(&mut canvas.subcanvas(text_rect));
+                if line.skipped {
+                    let baseline = top + self.font.baseline() + ENTRY_MARGIN;
+                    canvas.draw_text(
+                        &self.font,
+                        Align::Left,
+                        Point::new(width + ENTRY_MARGIN, baseline),
+                        SKIPPED_TEXT,
+                    );
+                }
+            }
+            top += height + ENTRY_SPACING;
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        theater: &mut Theater,
+    ) -> Action<()> {
+        if !self.open {
+            return Action::ignore();
+        }
+        let page = self.list_rect().height() as i32 - LINE_SCROLL;
+        match event {
+            &Event::Quit => Action::ignore(),
+            &Event::KeyDown(Keycode::Escape, _)
+            | &Event::KeyDown(Keycode::Return, _)
+            | &Event::KeyDown(Keycode::F2, _) => self.close(),
+            &Event::KeyDown(Keycode::Up, _) => {
+                self.scroll_by(theater, -LINE_SCROLL)
+            }
+            &Event::KeyDown(Keycode::Down, _) => {
+                self.scroll_by(theater, LINE_SCROLL)
+            }
+            &Event::KeyDown(Keycode::PageUp, _) => {
+                self.scroll_by(theater, -page)
+            }
+            &Event::KeyDown(Keycode::PageDown, _) => {
+                self.scroll_by(theater, page)
+            }
+            &Event::KeyDown(Keycode::Home, _) => {
+                self.scroll_by(theater, -self.scroll)
+            }
+            &Event::KeyDown(Keycode::End, _) => {
+                let delta = self.max_scroll(theater) - self.scroll;
+                self.scroll_by(theater, delta)
+            }
+            &Event::MouseDown(pt) => {
+                self.drag_from = Some(pt.y());
+                self.dragged = false;
+                Action::ignore().and_stop()
+            }
+            &Event::MouseDrag(pt) => {
+                if let Some(from) = self.drag_from {
+                    self.drag_from = Some(pt.y());
+                    self.dragged = true;
+                    self.scroll_by(theater, from - pt.y())
+                } else {
+                    Action::ignore().and_stop()
+                }
+            }
+            &Event::MouseUp => {
+                if self.drag_from.take().is_some() && !self.dragged {
+                    self.close()
+                } else {
+                    Action::ignore().and_stop()
+                }
+            }
+            _ => Action::ignore().and_stop(),
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::TranscriptView;
+    use crate::elements::{Ast, TalkPos, TalkStyle, Theater};
+    use crate::gui::{Element, Event, KeyMod, Keycode, Window};
+
+    #[test]
+    fn records_shown_and_skipped_lines() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut resources = window.resources();
+        let mut scene = Ast::compile_scene(
+            &mut resources,
+            vec![
+                Ast::Place(1, "chars/mezure", 0, (100, 200)),
+                Ast::Talk(1, TalkStyle::Normal, TalkPos::NE, "Hello."),
+                Ast::Talk(1, TalkStyle::Thought, TalkPos::NE, "Hmm."),
+            ],
+        );
+        let mut theater = Theater::new();
+        scene.begin(&mut theater);
+        scene.tick(&mut theater);
+        assert!(scene.is_paused());
+        scene.skip(&mut theater);
+        let lines = theater.transcript();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "Hello.");
+        assert_eq!(lines[0].speaker, Some("Mezure"));
+        assert!(!lines[0].skipped);
+        assert_eq!(lines[1].text, "Hmm.");
+        assert!(lines[1].skipped);
+
+        let mut transcript = TranscriptView::new(&mut resources, visible);
+        assert_eq!(
+            transcript.speaker_label(&lines[0]),
+            Some("Mezure".to_string())
+        );
+        assert_eq!(
+            transcript.speaker_label(&lines[1]),
+            Some("Mezure (thinking)".to_string())
+        );
+        assert!(!transcript.is_open());
+        transcript.open(&theater);
+        assert!(transcript.is_open());
+        let tick = transcript.handle_event(&Event::ClockTick, &mut theater);
+        assert!(tick.should_stop());
+        let escape = Event::KeyDown(Keycode::Escape, KeyMod::none());
+        transcript.handle_event(&escape, &mut theater);
+        assert!(!transcript.is_open());
+    }
+
+    #[test]
+    fn replaying_a_scene_does_not_repeat_lines() {
+        let mut window = Window::headless((576, 384));
+        let mut resources = window.resources();
+        let scene = Ast::compile_scene(
+            &mut resources,
+            vec![
+                Ast::Place(1, "chars/invis", 0, (100, 200)),
+                Ast::Talk(1, TalkStyle::Comm, TalkPos::NE, "Hello?"),
+            ],
+        );
+        let mut theater = Theater::new();
+        scene.clone().skip(&mut theater);
+        assert_eq!(theater.transcript().len(), 1);
+        assert!(theater.transcript()[0].skipped);
+        for _ in 0..2 {
+            let mut replay = scene.clone();
+            replay.begin(&mut theater);
+            replay.tick(&mut theater);
+            replay.skip(&mut theater);
+        }
+        let lines = theater.transcript();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].speaker, None);
+        assert!(!lines[0].skipped);
+    }
+}
+
+// ========================================================================= //
@@ -19,7 +19,8 @@
 
 use super::background::Background;
 use super::font::Font;
-use super::sprite::Sprite;
+use super::image::RgbaImage;
+use super::sprite::{Sprite, SpriteData};
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
 use sdl2::render::Canvas as SdlCanvas;
@@ -28,19 +29,35 @@ use sdl2::video::Window as SdlWindow;
 // ========================================================================= //
 
 pub struct Canvas<'a> {
-    renderer: &'a mut SdlCanvas<SdlWindow>,
+    target: Target<'a>,
     offset_rect: Rect,
     clip_rect: Option<Rect>,
     prev_clip_rect: Option<Rect>,
 }
 
+enum Target<'a> {
+    Window(&'a mut SdlCanvas<SdlWindow>),
+    Image(&'a mut RgbaImage),
+}
+
 impl<'a> Canvas<'a> {
     pub fn new(
         renderer: &'a mut SdlCanvas<SdlWindow>,
         rect: Rect,
     ) -> Canvas<'a> {
+        Canvas::with_target(Target::Window(renderer), rect)
+    }
+
+    /// Creates a canvas that draws into an in-memory image instead of a
+    /// window.  Sprites drawn onto such a canvas must have been loaded from
+    /// headless `Resources`.
+    pub fn from_image(image: &'a mut RgbaImage, rect: Rect) -> Canvas<'a> {
+        Canvas::with_target(Target::Image(image), rect)
+    }
+
+    fn with_target(target: Target<'a>, rect: Rect) -> Canvas<'a> {
         Canvas {
-            renderer,
+            target,
             offset_rect: rect,
             clip_rect: None,
             prev_clip_rect: None,
@@ -60,7 +77,10 @@ impl<'a> Canvas<'a> {
     }
 
     fn clip_rect(&self) -> Rect {
-        self.clip_rect.unwrap_or_else(|| self.renderer.viewport())
+        self.clip_rect.unwrap_or_else(|| match self.target {
+            Target::Window(ref renderer) => renderer.viewport(),
+            Target::Image(ref image) => image.rect(),
+        })
     }
 
     fn clip_intersection(&self, rect: Rect) -> Rect {
@@ -71,59 +91,99 @@ impl<'a> Canvas<'a> {
         }
     }
 
+    fn set_clip_rect(&mut self, clip_rect: Option<Rect>) {
+        if let Target::Window(ref mut renderer) = self.target {
+            renderer.set_clip_rect(clip_rect);
+        }
+    }
+
+    fn reborrow_target(&mut self) -> Target<'_> {
+        match self.target {
+            Target::Window(ref mut renderer) => Target::Window(renderer),
+            Target::Image(ref mut image) => Target::Image(image),
+        }
+    }
+
     pub fn subcanvas(&mut self, mut rect: Rect) -> Canvas {
         rect.offset(self.offset_rect.x(), self.offset_rect.y());
         let new_clip_rect = Some(self.clip_intersection(rect));
-        self.renderer.set_clip_rect(new_clip_rect);
+        self.set_clip_rect(new_clip_rect);
+        let prev_clip_rect = self.clip_rect;
         Canvas {
-            renderer: self.renderer,
+            target: self.reborrow_target(),
             offset_rect: rect,
             clip_rect: new_clip_rect,
-            prev_clip_rect: self.clip_rect,
+            prev_clip_rect,
         }
     }
 
     pub fn clipped(&mut self, mut rect: Rect) -> Canvas {
         rect.offset(self.offset_rect.x(), self.offset_rect.y());
         let new_clip_rect = Some(self.clip_intersection(rect));
-        self.renderer.set_clip_rect(new_clip_rect);
+        self.set_clip_rect(new_clip_rect);
+        let prev_clip_rect = self.clip_rect;
+        let offset_rect = self.offset_rect;
         Canvas {
-            renderer: self.renderer,
-            offset_rect: self.offset_rect,
+            target: self.reborrow_target(),
+            offset_rect,
             clip_rect: new_clip_rect,
-            prev_clip_rect: self.clip_rect,
+            prev_clip_rect,
         }
     }
 
     pub fn clear(&mut self, color: (u8, u8, u8)) {
-        let (r, g, b) = color;
-        self.renderer.set_draw_color(Color::RGB(r, g, b));
-        if let Some(clip) = self.clip_rect {
-            self.renderer.fill_rect(clip).unwrap();
-        } else {
-            self.renderer.clear();
+        let clip = self.clip_rect();
+        match self.target {
+            Target::Window(ref mut renderer) => {
+                let (r, g, b) = color;
+                renderer.set_draw_color(Color::RGB(r, g, b));
+                if let Some(clip) = self.clip_rect {
+                    renderer.fill_rect(clip).unwrap();
+                } else {
+                    renderer.clear();
+                }
+            }
+            Target::Image(ref mut image) => {
+                image.fill_rect(color, clip, clip);
+            }
         }
     }
 
     pub fn fill_rect(&mut self, color: (u8, u8, u8), mut rect: Rect) {
-        let (r, g, b) = color;
-        self.renderer.set_draw_color(Color::RGB(r, g, b));
         rect.offset(self.offset_rect.x(), self.offset_rect.y());
-        if let Some(clip) = self.clip_rect {
-            if let Some(intersection) = clip.intersection(rect) {
-                rect = intersection;
-            } else {
-                rect.resize(0, 0);
+        let clip = self.clip_rect();
+        match self.target {
+            Target::Window(ref mut renderer) => {
+                let (r, g, b) = color;
+                renderer.set_draw_color(Color::RGB(r, g, b));
+                if let Some(clip) = self.clip_rect {
+                    if let Some(intersection) = clip.intersection(rect) {
+                        rect = intersection;
+                    } else {
+                        rect.resize(0, 0);
+                    }
+                }
+                renderer.fill_rect(rect).unwrap();
+            }
+            Target::Image(ref mut image) => {
+                image.fill_rect(color, rect, clip);
             }
         }
-        self.renderer.fill_rect(rect).unwrap();
     }
 
     pub fn draw_rect(&mut self, color: (u8, u8, u8), mut rect: Rect) {
-        let (r, g, b) = color;
-        self.renderer.set_draw_color(Color::RGB(r, g, b));
         rect.offset(self.offset_rect.x(), self.offset_rect.y());
-        self.renderer.draw_rect(rect).unwrap();
+        let clip = self.clip_rect();
+        match self.target {
+            Target::Window(ref mut renderer) => {
+                let (r, g, b) = color;
+                renderer.set_draw_color(Color::RGB(r, g, b));
+                renderer.draw_rect(rect).unwrap();
+            }
+            Target::Image(ref mut image) => {
+                image.draw_rect(color, rect, clip);
+            }
+        }
     }
 
     pub fn draw_background(&mut self, background: &Background) {
@@ -134,18 +194,13 @@ impl<'a> Canvas<'a> {
 
     pub fn draw_sprite(&mut self, sprite: &Sprite, mut top_left: Point) {
         top_left = top_left.offset(self.offset_rect.x(), self.offset_rect.y());
-        self.renderer
-            .copy(
-                &sprite.sdl2_texture(),
-                None,
-                Some(Rect::new(
-                    top_left.x(),
-                    top_left.y(),
-                    sprite.width(),
-                    sprite.height(),
-                )),
-            )
-            .unwrap();
+        let dest = Rect::new(
+            top_left.x(),
+            top_left.y(),
+            sprite.width(),
+            sprite.height(),
+        );
+        self.copy_sprite(sprite, dest, 0.0, false, false);
     }
 
     pub fn draw_sprite_transposed(
@@ -154,22 +209,13 @@ impl<'a> Canvas<'a> {
         mut top_left: Point,
     ) {
         top_left = top_left.offset(self.offset_rect.x(), self.offset_rect.y());
-        self.renderer
-            .copy_ex(
-                &sprite.sdl2_texture(),
-                None,
-                Some(Rect::new(
-                    top_left.x(),
-                    top_left.y(),
-                    sprite.width(),
-                    sprite.height(),
-                )),
-                90.0,
-                None,
-                false,
-                true,
-            )
-            .unwrap();
+        let dest = Rect::new(
+            top_left.x(),
+            top_left.y(),
+            sprite.width(),
+            sprite.height(),
+        );
+        self.copy_sprite(sprite, dest, 90.0, false, true);
     }
 
     pub fn draw_sprite_flipped(
@@ -180,22 +226,13 @@ impl<'a> Canvas<'a> {
         flip_vert: bool,
     ) {
         top_left = top_left.offset(self.offset_rect.x(), self.offset_rect.y());
-        self.renderer
-            .copy_ex(
-                &sprite.sdl2_texture(),
-                None,
-                Some(Rect::new(
-                    top_left.x(),
-                    top_left.y(),
-                    sprite.width(),
-                    sprite.height(),
-                )),
-                0.0,
-                None,
-                flip_horz,
-                flip_vert,
-            )
-            .unwrap();
+        let dest = Rect::new(
+            top_left.x(),
+            top_left.y(),
+            sprite.width(),
+            sprite.height(),
+        );
+        self.copy_sprite(sprite, dest, 0.0, flip_horz, flip_vert);
     }
 
     pub fn draw_sprite_centered(&mut self, sprite: &Sprite, center: Point) {
@@ -230,17 +267,43 @@ impl<'a> Canvas<'a> {
             sprite.width(),
             sprite.height(),
         );
-        self.renderer
-            .copy_ex(
-                &sprite.sdl2_texture(),
-                None,
-                Some(dest),
-                degrees as f64,
-                None,
-                flip_horz,
-                flip_vert,
-            )
-            .unwrap();
+        self.copy_sprite(sprite, dest, degrees as f64, flip_horz, flip_vert);
+    }
+
+    fn copy_sprite(
+        &mut self,
+        sprite: &Sprite,
+        dest: Rect,
+        degrees: f64,
+        flip_horz: bool,
+        flip_vert: bool,
+    ) {
+        let clip = self.clip_rect();
+        match (&mut self.target, sprite.data()) {
+            (Target::Window(renderer), SpriteData::Texture(texture)) => {
+                if degrees == 0.0 && !flip_horz && !flip_vert {
+                    renderer.copy(texture, None, Some(dest)).unwrap();
+                } else {
+                    renderer
+                        .copy_ex(
+                            texture,
+                            None,
+                            Some(dest),
+                            degrees,
+                            None,
+                            flip_horz,
+                            flip_vert,
+                        )
+                        .unwrap();
+                }
+            }
+            (Target::Image(image), SpriteData::Image(pixels)) => {
+                image.draw_image(
+                    pixels, dest, degrees, flip_horz, flip_vert, clip,
+                );
+            }
+            _ => panic!("sprite was loaded for a different kind of canvas"),
+        }
     }
 
     pub fn draw_char(
@@ -279,7 +342,7 @@ impl<'a> Canvas<'a> {
 
 impl<'a> Drop for Canvas<'a> {
     fn drop(&mut self) {
-        self.renderer.set_clip_rect(self.prev_clip_rect);
+        self.set_clip_rect(self.prev_clip_rect);
     }
 }
 
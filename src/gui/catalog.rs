@@ -0,0 +1,314 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+use std::path::PathBuf;
+use toml;
+
+use super::loader::ResourceLoader;
+
+// ========================================================================= //
+
+/// The language that the game's text is written in, and which therefore
+/// needs no catalogue.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The catalogue section for text that isn't specific to any one location,
+/// such as dialog button labels and location names.
+pub const COMMON_SECTION: &str = "common";
+
+/// The languages that the game ships catalogues for (including English),
+/// as pairs of language code and the language's name for itself.  A language
+/// is only listed once its catalogue covers the cutscenes and info boxes;
+/// until then (as for `fr`), its catalogue can still be tried out by setting
+/// `language` in the prefs file by hand.
+pub const LANGUAGES: &[(&str, &str)] = &[("en", "English")];
+
+// ========================================================================= //
+
+/// A set of translated strings for one language.
+///
+/// The game's text is written in English directly in the source code.  A
+/// catalogue for another language lives at `lang/<language>.toml` in the
+/// resource data, and has one table per location (keyed by the location's
+/// save-file key, e.g. `[light_syrup]`), plus a `[common]` table for text
+/// shared between locations, mapping line IDs to translations.  A line's ID
+/// is the `line_id` of its English text, so that catalogues don't need to
+/// change when lines are added or reordered.  Lines missing from the
+/// catalogue fall back to English.
+///
+/// The trade-off is that a line's ID is a hash of its English text, so any
+/// edit to the English text, even fixing a typo, drops that line's
+/// translation (showing the new English instead) until the catalogue is
+/// updated, and identical English lines within a section always share one
+/// translation.  Catalogues should therefore be regenerated with `template`
+/// after editing the English text; it lists translations whose English line
+/// no longer exists, so that they can be moved to the new ID.
+pub struct Catalog {
+    language: String,
+    sections: HashMap<String, HashMap<String, String>>,
+    recorded: Option<RefCell<Sections>>,
+}
+
+/// English lines in order of first appearance, as (ID, text) pairs, keyed by
+/// section.
+type Sections = BTreeMap<String, Vec<(String, String)>>;
+
+impl Catalog {
+    /// Returns an empty catalogue, which leaves all text in English.
+    pub fn english() -> Catalog {
+        Catalog {
+            language: DEFAULT_LANGUAGE.to_string(),
+            sections: HashMap::new(),
+            recorded: None,
+        }
+    }
+
+    pub fn load(
+        loader: &ResourceLoader,
+        language: &str,
+    ) -> io::Result<Catalog> {
+        if language == DEFAULT_LANGUAGE {
+            return Ok(Catalog::english());
+        }
+        let path = PathBuf::from("lang").join(language).with_extension("toml");
+        let mut file = loader.load(&path)?;
+        let mut string = String::new();
+        file.read_to_string(&mut string)?;
+        Catalog::parse(language, &string)
+    }
+
+    fn parse(language: &str, string: &str) -> io::Result<Catalog> {
+        let value = string.parse::<toml::Value>().map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+        })?;
+        let mut sections = HashMap::new();
+        if let toml::Value::Table(table) = value {
+            for (section, lines) in table.into_iter() {
+                let mut translations = HashMap::new();
+                if let toml::Value::Table(lines) = lines {
+                    for (id, text) in lines.into_iter() {
+                        if let toml::Value::String(text) = text {
+                            translations.insert(id, text);
+                        }
+                    }
+                }
+                sections.insert(section, translations);
+            }
+        }
+        Ok(Catalog {
+            language: language.to_string(),
+            sections,
+            recorded: None,
+        })
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Returns the translation of the given English text in the given
+    /// section, or the text itself if there isn't one.
+    pub fn translate<'a>(&'a self, section: &str, text: &'a str) -> &'a str {
+        if let Some(ref recorded) = self.recorded {
+            let id = line_id(text);
+            let mut recorded = recorded.borrow_mut();
+            let lines = recorded.entry(section.to_string()).or_default();
+            if !lines.iter().any(|(line, _)| *line == id) {
+                lines.push((id, text.to_string()));
+            }
+        }
+        if self.sections.is_empty() {
+            return text;
+        }
+        let id = line_id(text);
+        match self.sections.get(section).and_then(|lines| lines.get(&id)) {
+            Some(translation) => translation,
+            None => text,
+        }
+    }
+
+    /// Starts remembering every line passed to `translate`, for use by
+    /// `template`.
+    pub fn record_lines(&mut self) {
+        self.recorded = Some(RefCell::new(BTreeMap::new()));
+    }
+
+    /// Returns the text of a catalogue file for this language covering every
+    /// line recorded since `record_lines` was called.  Lines are preceded by
+    /// their English text in a comment; those without a translation yet are
+    /// marked TODO and given their English text.  Translations for lines
+    /// that weren't recorded (e.g. because their English text has since
+    /// changed) are kept at the end of their section, commented out.
+    pub fn template(&self) -> String {
+        let empty = BTreeMap::new();
+        let recorded = self.recorded.as_ref().map(RefCell::borrow);
+        let recorded = recorded.as_ref().map_or(&empty, |r| &**r);
+        let mut names: Vec<&String> =
+            recorded.keys().chain(self.sections.keys()).collect();
+        names.sort();
+        names.dedup();
+        let mut output =
+            format!("# Text catalogue for language \"{}\".\n", self.language);
+        for name in names {
+            let translations = self.sections.get(name);
+            let lines = recorded.get(name).map_or(&[][..], Vec::as_slice);
+            output.push_str(&format!("\n[{}]\n", name));
+            for (id, english) in lines {
+                for line in english.lines() {
+                    output.push_str(&format!("# {}\n", line));
+                }
+                let translation = translations.and_then(|t| t.get(id));
+                if translation.is_none() {
+                    output.push_str("# TODO\n");
+                }
+                output.push_str(&format!(
+                    "\"{}\" = {}\n",
+                    id,
+                    toml_string(translation.unwrap_or(english))
+                ));
+            }
+            if let Some(translations) = translations {
+                let mut stale: Vec<(&String, &String)> = translations
+                    .iter()
+                    .filter(|&(id, _)| !lines.iter().any(|(l, _)| l == id))
+                    .collect();
+                stale.sort();
+                for (id, translation) in stale {
+                    output.push_str(&format!(
+                        "# STALE: \"{}\" = {}\n",
+                        id,
+                        toml_string(translation)
+                    ));
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Quotes the given text as a single-line TOML basic string.
+fn toml_string(text: &str) -> String {
+    let mut output = String::from("\"");
+    for chr in text.chars() {
+        match chr {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            chr if chr.is_control() => {
+                output.push_str(&format!("\\u{:04X}", chr as u32))
+            }
+            chr => output.push(chr),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Returns the catalogue ID for a line of English text.
+pub fn line_id(text: &str) -> String {
+    // 64-bit FNV-1a, which is stable across platforms and Rust versions.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in text.as_bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{line_id, Catalog, DEFAULT_LANGUAGE, LANGUAGES};
+    use crate::gui::loader::ResourceLoader;
+
+    #[test]
+    fn translates_known_lines_only() {
+        let hello = "Hello, world!";
+        let string = format!(
+            "[light_syrup]\n\"{}\" = \"Bonjour, le monde!\"\n",
+            line_id(hello)
+        );
+        let catalog = Catalog::parse("fr", &string).unwrap();
+        assert_eq!(catalog.language(), "fr");
+        assert_eq!(
+            catalog.translate("light_syrup", hello),
+            "Bonjour, le monde!"
+        );
+        assert_eq!(catalog.translate("light_syrup", "Goodbye."), "Goodbye.");
+        assert_eq!(catalog.translate("prolog", hello), hello);
+        let english = Catalog::english();
+        assert_eq!(english.translate("light_syrup", hello), hello);
+    }
+
+    #[test]
+    fn template_keeps_translations_and_stale_lines() {
+        let hello = "Hello, world!";
+        let string = format!(
+            "[prolog]\n\"{}\" = \"Bonjour, le monde!\"\n\
+             \"{}\" = \"Au revoir.\"\n",
+            line_id(hello),
+            line_id("Goodbye")
+        );
+        let mut catalog = Catalog::parse("fr", &string).unwrap();
+        catalog.record_lines();
+        catalog.translate("prolog", hello);
+        catalog.translate("prolog", "Say \"hi\"\nnow.");
+        catalog.translate("prolog", hello);
+        let expected = format!(
+            "# Text catalogue for language \"fr\".\n\n[prolog]\n\
+             # Hello, world!\n\"{}\" = \"Bonjour, le monde!\"\n\
+             # Say \"hi\"\n# now.\n# TODO\n\"{}\" = \"Say \\\"hi\\\"\\nnow.\"\n\
+             # STALE: \"{}\" = \"Au revoir.\"\n",
+            line_id(hello),
+            line_id("Say \"hi\"\nnow."),
+            line_id("Goodbye")
+        );
+        assert_eq!(catalog.template(), expected);
+        let reparsed = Catalog::parse("fr", &catalog.template()).unwrap();
+        assert_eq!(reparsed.translate("prolog", hello), "Bonjour, le monde!");
+    }
+
+    #[test]
+    fn shipped_catalogues_load() {
+        let loader = ResourceLoader::new();
+        // French isn't listed yet, but its partial catalogue should load.
+        let unlisted = ["fr"];
+        let listed = LANGUAGES.iter().map(|&(language, _)| language);
+        for language in listed.chain(unlisted.iter().cloned()) {
+            let catalog = Catalog::load(&loader, language).unwrap();
+            assert_eq!(catalog.language(), language);
+            if language != DEFAULT_LANGUAGE {
+                assert!(!catalog.sections.is_empty(), "{}", language);
+            }
+        }
+    }
+
+    #[test]
+    fn line_ids_are_stable() {
+        assert_eq!(line_id(""), "cbf29ce484222325");
+        assert_ne!(line_id("Hello"), line_id("hello"));
+    }
+}
+
+// ========================================================================= //
@@ -20,6 +20,7 @@
 use sdl2;
 use sdl2::mouse::MouseButton;
 use sdl2::rect::Point;
+use std::collections::VecDeque;
 use std::ops::{BitOr, BitOrAssign};
 
 pub use sdl2::keyboard::Keycode;
@@ -118,6 +119,14 @@ impl KeyMod {
         KeyMod { bits: 0x4 }
     }
 
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub fn from_bits(bits: u8) -> KeyMod {
+        KeyMod { bits: bits & 0x7 }
+    }
+
     fn from_sdl2(kmod: sdl2::keyboard::Mod) -> KeyMod {
         let mut result = KeyMod::none();
 
@@ -162,11 +171,43 @@ impl BitOrAssign for KeyMod {
 
 // ========================================================================= //
 
+/// Something that input events can be pulled from, such as a window or a
+/// prerecorded script.
+pub trait EventSource {
+    /// Returns the next event, blocking until one is available if need be.
+    fn next_event(&mut self) -> Event;
+}
+
+/// An event source that plays back a fixed sequence of events, and then
+/// returns `Event::Quit` once they have all been used up.
+pub struct EventScript {
+    events: VecDeque<Event>,
+}
+
+impl EventScript {
+    pub fn new<I: IntoIterator<Item = Event>>(events: I) -> EventScript {
+        EventScript { events: events.into_iter().collect() }
+    }
+
+    /// Appends more events to the end of the script.
+    pub fn extend<I: IntoIterator<Item = Event>>(&mut self, events: I) {
+        self.events.extend(events);
+    }
+}
+
+impl EventSource for EventScript {
+    fn next_event(&mut self) -> Event {
+        self.events.pop_front().unwrap_or(Event::Quit)
+    }
+}
+
+// ========================================================================= //
+
 #[cfg(test)]
 mod tests {
     use sdl2;
 
-    use super::{Event, KeyMod};
+    use super::{Event, EventScript, EventSource, KeyMod};
     use crate::gui::Point;
 
     #[test]
@@ -191,6 +232,16 @@ mod tests {
         );
         assert_eq!(Event::ClockTick.translate(30, 40), Event::ClockTick);
     }
+
+    #[test]
+    fn script_ends_with_quit() {
+        let mut script = EventScript::new(vec![Event::ClockTick]);
+        script.extend(vec![Event::MouseUp]);
+        assert_eq!(script.next_event(), Event::ClockTick);
+        assert_eq!(script.next_event(), Event::MouseUp);
+        assert_eq!(script.next_event(), Event::Quit);
+        assert_eq!(script.next_event(), Event::Quit);
+    }
 }
 
 // ========================================================================= //
@@ -17,10 +17,8 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use super::sprite::Sprite;
+use super::sprite::{Renderer, Sprite};
 use ahi;
-use sdl2::render::Canvas as SdlCanvas;
-use sdl2::video::Window as SdlWindow;
 use std::collections::HashMap;
 
 // ========================================================================= //
@@ -32,7 +30,7 @@ pub struct Font {
 }
 
 impl Font {
-    pub fn new(renderer: &SdlCanvas<SdlWindow>, font: &ahi::Font) -> Font {
+    pub fn new(renderer: Renderer, font: &ahi::Font) -> Font {
         let mut glyphs = HashMap::new();
         for chr in font.chars() {
             glyphs.insert(chr, Glyph::new(renderer, &font[chr]));
@@ -75,7 +73,7 @@ pub struct Glyph {
 }
 
 impl Glyph {
-    fn new(renderer: &SdlCanvas<SdlWindow>, glyph: &ahi::Glyph) -> Glyph {
+    fn new(renderer: Renderer, glyph: &ahi::Glyph) -> Glyph {
         Glyph {
             sprite: Sprite::new(renderer, glyph.image()),
             left_edge: glyph.left_edge(),
@@ -0,0 +1,220 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use sdl2;
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::GameControllerSubsystem;
+use std::collections::HashMap;
+
+use super::event::{Event, KeyMod, Keycode};
+
+// ========================================================================= //
+
+/// How far a stick must be pushed before it counts as a direction press.
+const STICK_PRESS_THRESHOLD: i16 = 16000;
+/// How far a pushed stick must return towards center before it can be
+/// pressed again.
+const STICK_RELEASE_THRESHOLD: i16 = 8000;
+
+// ========================================================================= //
+
+/// Tracks the game controllers that are currently plugged in, and translates
+/// their input into the same keyboard events that the rest of the game
+/// already understands.
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<u32, GameController>,
+    sticks: StickState,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Gamepads {
+        Gamepads {
+            subsystem,
+            controllers: HashMap::new(),
+            sticks: StickState::new(),
+        }
+    }
+
+    /// Handles a controller-related SDL event, opening or closing
+    /// controllers as they are plugged in or removed, and returns the game
+    /// event (if any) that it corresponds to.
+    pub fn handle_sdl2(
+        &mut self,
+        event: &sdl2::event::Event,
+    ) -> Option<Event> {
+        match event {
+            &sdl2::event::Event::ControllerDeviceAdded { which, .. } => {
+                match self.subsystem.open(which) {
+                    Ok(controller) => {
+                        if cfg!(debug_assertions) {
+                            println!(
+                                "Opened controller: {}",
+                                controller.name()
+                            );
+                        }
+                        self.controllers
+                            .insert(controller.instance_id(), controller);
+                    }
+                    Err(error) => {
+                        if cfg!(debug_assertions) {
+                            println!("Failed to open controller: {}", error);
+                        }
+                    }
+                }
+                None
+            }
+            &sdl2::event::Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(&which);
+                self.sticks.forget(which);
+                None
+            }
+            &sdl2::event::Event::ControllerButtonDown { button, .. } => {
+                button_event(button)
+            }
+            &sdl2::event::Event::ControllerAxisMotion {
+                which,
+                axis,
+                value,
+                ..
+            } => self.sticks.axis_event(which, axis, value),
+            _ => None,
+        }
+    }
+}
+
+// ========================================================================= //
+
+fn button_event(button: Button) -> Option<Event> {
+    let (keycode, keymod) = match button {
+        Button::DPadUp => (Keycode::Up, KeyMod::none()),
+        Button::DPadDown => (Keycode::Down, KeyMod::none()),
+        Button::DPadLeft => (Keycode::Left, KeyMod::none()),
+        Button::DPadRight => (Keycode::Right, KeyMod::none()),
+        Button::A => (Keycode::Return, KeyMod::none()),
+        Button::B | Button::Start => (Keycode::Escape, KeyMod::none()),
+        Button::X => (Keycode::R, KeyMod::command()),
+        Button::Y | Button::Back => (Keycode::F1, KeyMod::none()),
+        Button::LeftShoulder => (Keycode::Z, KeyMod::command()),
+        Button::RightShoulder => {
+            (Keycode::Z, KeyMod::command() | KeyMod::shift())
+        }
+        _ => return None,
+    };
+    Some(Event::KeyDown(keycode, keymod))
+}
+
+// ========================================================================= //
+
+/// Turns analog stick motion into arrow key presses, one press each time the
+/// stick is pushed away from center.
+struct StickState {
+    pushed: HashMap<(u32, Axis), i16>,
+}
+
+impl StickState {
+    fn new() -> StickState {
+        StickState { pushed: HashMap::new() }
+    }
+
+    fn forget(&mut self, which: u32) {
+        self.pushed.retain(|&(id, _), _| id != which);
+    }
+
+    fn axis_event(
+        &mut self,
+        which: u32,
+        axis: Axis,
+        value: i16,
+    ) -> Option<Event> {
+        let (negative, positive) = match axis {
+            Axis::LeftX => (Keycode::Left, Keycode::Right),
+            Axis::LeftY => (Keycode::Up, Keycode::Down),
+            _ => return None,
+        };
+        let sign = if value >= STICK_PRESS_THRESHOLD {
+            1
+        } else if value <= -STICK_PRESS_THRESHOLD {
+            -1
+        } else if value > -STICK_RELEASE_THRESHOLD
+            && value < STICK_RELEASE_THRESHOLD
+        {
+            0
+        } else {
+            return None;
+        };
+        let previous = self.pushed.insert((which, axis), sign).unwrap_or(0);
+        if sign == previous || sign == 0 {
+            None
+        } else {
+            let keycode = if sign < 0 { negative } else { positive };
+            Some(Event::KeyDown(keycode, KeyMod::none()))
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{button_event, StickState};
+    use crate::gui::event::{Event, KeyMod, Keycode};
+    use sdl2::controller::{Axis, Button};
+
+    #[test]
+    fn buttons_map_to_keys() {
+        let key = |keycode| Some(Event::KeyDown(keycode, KeyMod::none()));
+        assert_eq!(button_event(Button::DPadUp), key(Keycode::Up));
+        assert_eq!(button_event(Button::A), key(Keycode::Return));
+        assert_eq!(button_event(Button::B), key(Keycode::Escape));
+        assert_eq!(
+            button_event(Button::X),
+            Some(Event::KeyDown(Keycode::R, KeyMod::command()))
+        );
+        assert_eq!(button_event(Button::Y), key(Keycode::F1));
+        assert_eq!(
+            button_event(Button::RightShoulder),
+            Some(Event::KeyDown(
+                Keycode::Z,
+                KeyMod::command() | KeyMod::shift()
+            ))
+        );
+        assert_eq!(button_event(Button::Guide), None);
+    }
+
+    #[test]
+    fn stick_presses_once_per_push() {
+        let mut sticks = StickState::new();
+        let right = Some(Event::KeyDown(Keycode::Right, KeyMod::none()));
+        let up = Some(Event::KeyDown(Keycode::Up, KeyMod::none()));
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 5000), None);
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 20000), right);
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 32767), None);
+        // Easing off a little doesn't count as releasing the stick.
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 12000), None);
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 20000), None);
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 0), None);
+        assert_eq!(sticks.axis_event(0, Axis::LeftX, 20000), right);
+        assert_eq!(sticks.axis_event(0, Axis::LeftY, -20000), up);
+        assert_eq!(sticks.axis_event(1, Axis::LeftY, -20000), up);
+        assert_eq!(sticks.axis_event(0, Axis::RightX, 20000), None);
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,312 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use ahi;
+use sdl2::rect::Rect;
+use std::io::{self, Write};
+
+// ========================================================================= //
+
+/// An in-memory RGBA pixel buffer.  This serves both as the pixel data for
+/// sprites and as a render target when drawing without a display attached.
+#[derive(Clone, Eq, PartialEq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Creates a new image filled with opaque black.
+    pub fn new(width: u32, height: u32) -> RgbaImage {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for _ in 0..(width * height) {
+            data.extend_from_slice(&[0, 0, 0, 255]);
+        }
+        RgbaImage { width, height, data }
+    }
+
+    pub fn from_ahi(image: &ahi::Image) -> RgbaImage {
+        RgbaImage {
+            width: image.width(),
+            height: image.height(),
+            data: image.rgba_data(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// Returns the image's pixels as RGBA bytes, in row-major order.
+    pub fn rgba_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8, u8) {
+        let index = self.index(x, y);
+        let pixel = &self.data[index..(index + 4)];
+        (pixel[0], pixel[1], pixel[2], pixel[3])
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        debug_assert!(x < self.width && y < self.height);
+        ((y * self.width + x) * 4) as usize
+    }
+
+    /// Returns the bounds of `clip` within this image, as `(left, top,
+    /// right, bottom)`, with right and bottom exclusive.
+    fn clip_bounds(&self, clip: Rect) -> (i32, i32, i32, i32) {
+        (
+            clip.left().max(0),
+            clip.top().max(0),
+            clip.right().min(self.width as i32),
+            clip.bottom().min(self.height as i32),
+        )
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, src: (u8, u8, u8, u8)) {
+        let index = self.index(x as u32, y as u32);
+        let (r, g, b, a) = src;
+        blend(&mut self.data[index..(index + 4)], &[r, g, b, a]);
+    }
+
+    /// Fills the part of `rect` that lies within `clip` with an opaque color.
+    pub fn fill_rect(&mut self, color: (u8, u8, u8), rect: Rect, clip: Rect) {
+        let (left, top, right, bottom) = self.clip_bounds(clip);
+        let (left, right) = (rect.left().max(left), rect.right().min(right));
+        if left >= right {
+            return;
+        }
+        let (r, g, b) = color;
+        for y in rect.top().max(top)..rect.bottom().min(bottom) {
+            let start = self.index(left as u32, y as u32);
+            let end = start + 4 * (right - left) as usize;
+            for pixel in self.data[start..end].chunks_mut(4) {
+                pixel.copy_from_slice(&[r, g, b, 255]);
+            }
+        }
+    }
+
+    /// Draws the one-pixel outline of `rect`, clipped to `clip`.
+    pub fn draw_rect(&mut self, color: (u8, u8, u8), rect: Rect, clip: Rect) {
+        let (x0, y0) = (rect.left(), rect.top());
+        let (x1, y1) = (rect.right() - 1, rect.bottom() - 1);
+        let edges = [
+            Rect::new(x0, y0, rect.width(), 1),
+            Rect::new(x0, y1, rect.width(), 1),
+            Rect::new(x0, y0, 1, rect.height()),
+            Rect::new(x1, y0, 1, rect.height()),
+        ];
+        for &edge in edges.iter() {
+            self.fill_rect(color, edge, clip);
+        }
+    }
+
+    /// Alpha-blends `src` onto this image, scaled to fill `dest` and then
+    /// rotated clockwise by `degrees` about the center of `dest`, clipped to
+    /// `clip`.  Flips are applied before rotation, just as SDL does for
+    /// `copy_ex`.
+    pub fn draw_image(
+        &mut self,
+        src: &RgbaImage,
+        dest: Rect,
+        degrees: f64,
+        flip_horz: bool,
+        flip_vert: bool,
+        clip: Rect,
+    ) {
+        let unscaled =
+            dest.width() == src.width && dest.height() == src.height;
+        if degrees == 0.0 && unscaled {
+            self.draw_image_unrotated(src, dest, flip_horz, flip_vert, clip);
+            return;
+        }
+        let half_width = dest.width() as f64 / 2.0;
+        let half_height = dest.height() as f64 / 2.0;
+        let center_x = dest.x() as f64 + half_width;
+        let center_y = dest.y() as f64 + half_height;
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        // Find the bounding box of the rotated destination rectangle.
+        let extent_x = (half_width * cos).abs() + (half_height * sin).abs();
+        let extent_y = (half_width * sin).abs() + (half_height * cos).abs();
+        let (left, top, right, bottom) = self.clip_bounds(clip);
+        let left = left.max((center_x - extent_x).floor() as i32);
+        let top = top.max((center_y - extent_y).floor() as i32);
+        let right = right.min((center_x + extent_x).ceil() as i32);
+        let bottom = bottom.min((center_y + extent_y).ceil() as i32);
+        let scale_x = src.width as f64 / dest.width() as f64;
+        let scale_y = src.height as f64 / dest.height() as f64;
+        for y in top..bottom {
+            for x in left..right {
+                // Map the center of this pixel back into the source image.
+                let dx = x as f64 + 0.5 - center_x;
+                let dy = y as f64 + 0.5 - center_y;
+                let u = dx * cos + dy * sin + half_width;
+                let v = dy * cos - dx * sin + half_height;
+                if u < 0.0 || v < 0.0 {
+                    continue;
+                }
+                let mut sx = (u * scale_x).floor() as u32;
+                let mut sy = (v * scale_y).floor() as u32;
+                if sx >= src.width || sy >= src.height {
+                    continue;
+                }
+                if flip_horz {
+                    sx = src.width - 1 - sx;
+                }
+                if flip_vert {
+                    sy = src.height - 1 - sy;
+                }
+                self.blend_pixel(x, y, src.pixel(sx, sy));
+            }
+        }
+    }
+
+    fn draw_image_unrotated(
+        &mut self,
+        src: &RgbaImage,
+        dest: Rect,
+        flip_horz: bool,
+        flip_vert: bool,
+        clip: Rect,
+    ) {
+        let (left, top, right, bottom) = self.clip_bounds(clip);
+        let (left, right) = (dest.left().max(left), dest.right().min(right));
+        if left >= right {
+            return;
+        }
+        for y in dest.top().max(top)..dest.bottom().min(bottom) {
+            let mut sy = (y - dest.top()) as u32;
+            if flip_vert {
+                sy = src.height - 1 - sy;
+            }
+            let start = self.index(left as u32, y as u32);
+            let end = start + 4 * (right - left) as usize;
+            let row = &mut self.data[start..end];
+            for (dx, dst) in row.chunks_mut(4).enumerate() {
+                let mut sx = (left - dest.left()) as u32 + dx as u32;
+                if flip_horz {
+                    sx = src.width - 1 - sx;
+                }
+                let index = src.index(sx, sy);
+                blend(dst, &src.data[index..(index + 4)]);
+            }
+        }
+    }
+
+    /// Writes the image as a binary PPM file, discarding alpha.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for pixel in self.data.chunks(4) {
+            writer.write_all(&pixel[0..3])?;
+        }
+        Ok(())
+    }
+}
+
+/// Alpha-blends one RGBA pixel over another.
+fn blend(dst: &mut [u8], src: &[u8]) {
+    let alpha = src[3] as u32;
+    if alpha == 255 {
+        dst.copy_from_slice(src);
+    } else if alpha != 0 {
+        let mix = |s: u8, d: u8| {
+            ((s as u32 * alpha + d as u32 * (255 - alpha) + 127) / 255) as u8
+        };
+        dst[0] = mix(src[0], dst[0]);
+        dst[1] = mix(src[1], dst[1]);
+        dst[2] = mix(src[2], dst[2]);
+        dst[3] = mix(255, dst[3]);
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::RgbaImage;
+    use sdl2::rect::Rect;
+
+    const RED: (u8, u8, u8, u8) = (255, 0, 0, 255);
+    const BLACK: (u8, u8, u8, u8) = (0, 0, 0, 255);
+    const WHITE: (u8, u8, u8, u8) = (255, 255, 255, 255);
+
+    fn sprite() -> RgbaImage {
+        // A 3x2 image that is red on the left column and clear elsewhere.
+        let mut image = RgbaImage::new(3, 2);
+        image.data.iter_mut().for_each(|byte| *byte = 0);
+        image.fill_rect((255, 0, 0), Rect::new(0, 0, 1, 2), image.rect());
+        image
+    }
+
+    #[test]
+    fn fill_rect_is_clipped() {
+        let mut image = RgbaImage::new(4, 4);
+        image.fill_rect((255, 255, 255), Rect::new(1, 1, 5, 5), image.rect());
+        assert_eq!(image.pixel(0, 0), BLACK);
+        assert_eq!(image.pixel(3, 3), WHITE);
+        let mut image = RgbaImage::new(4, 4);
+        let clip = Rect::new(0, 0, 2, 2);
+        image.fill_rect((255, 255, 255), Rect::new(1, 1, 3, 3), clip);
+        assert_eq!(image.pixel(1, 1), WHITE);
+        assert_eq!(image.pixel(2, 2), BLACK);
+    }
+
+    #[test]
+    fn draw_image_flipped_and_rotated() {
+        let src = sprite();
+        let mut image = RgbaImage::new(5, 5);
+        let dest = Rect::new(1, 1, 3, 2);
+        image.draw_image(&src, dest, 0.0, false, false, image.rect());
+        assert_eq!(image.pixel(1, 1), RED);
+        assert_eq!(image.pixel(1, 2), RED);
+        assert_eq!(image.pixel(2, 1), BLACK);
+
+        let mut image = RgbaImage::new(5, 5);
+        image.draw_image(&src, dest, 0.0, true, false, image.rect());
+        assert_eq!(image.pixel(1, 1), BLACK);
+        assert_eq!(image.pixel(3, 1), RED);
+
+        // Rotating clockwise moves the left column to the top row.
+        let mut image = RgbaImage::new(4, 4);
+        let dest = Rect::new(0, 0, 3, 3);
+        let src = {
+            let mut src = RgbaImage::new(3, 3);
+            src.data.iter_mut().for_each(|byte| *byte = 0);
+            src.fill_rect((255, 0, 0), Rect::new(0, 0, 1, 3), src.rect());
+            src
+        };
+        image.draw_image(&src, dest, 90.0, false, false, image.rect());
+        assert_eq!(image.pixel(0, 0), RED);
+        assert_eq!(image.pixel(2, 0), RED);
+        assert_eq!(image.pixel(0, 1), BLACK);
+        assert_eq!(image.pixel(0, 2), BLACK);
+    }
+}
+
+// ========================================================================= //
@@ -20,26 +20,36 @@
 mod action;
 mod background;
 mod canvas;
+mod catalog;
 mod element;
 mod event;
 mod font;
+mod gamepad;
+mod image;
 mod itersynth;
 mod loader;
 mod resources;
 mod sound;
+mod speech;
 mod sprite;
 mod window;
 
 pub use self::action::Action;
 pub use self::background::Background;
 pub use self::canvas::{Align, Canvas};
+pub use self::catalog::{DEFAULT_LANGUAGE, LANGUAGES};
 pub use self::element::Element;
 pub use self::event::{Event, KeyMod, Keycode};
+#[cfg(test)]
+pub use self::event::{EventScript, EventSource};
 pub use self::font::Font;
-pub use self::resources::Resources;
+#[cfg(test)]
+pub use self::image::RgbaImage;
+pub use self::resources::{Resources, ScenePacing};
 pub use self::sound::Sound;
+pub use self::speech::{Announcer, SpeechOutput};
 pub use self::sprite::Sprite;
-pub use self::window::Window;
+pub use self::window::{ScaleMode, Window};
 pub use sdl2::rect::{Point, Rect};
 
 pub const FRAME_DELAY_MILLIS: u32 = 40;
@@ -18,31 +18,65 @@
 // +--------------------------------------------------------------------------+
 
 use ahi;
-use sdl2::render::Canvas as SdlCanvas;
-use sdl2::video::Window as SdlWindow;
 use std::collections::HashMap;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use super::background::Background;
+use super::catalog::{Catalog, COMMON_SECTION};
 use super::font::Font;
 use super::loader::ResourceLoader;
-use super::sprite::Sprite;
+use super::speech::Announcer;
+use super::sprite::{Renderer, Sprite};
+
+// ========================================================================= //
+
+/// How quickly cutscenes should play.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScenePacing {
+    /// The playback speed, as a percentage of normal speed.
+    pub speed_percent: u32,
+    /// How long to show a speech bubble before continuing the scene without
+    /// waiting for a click, or zero to always wait.
+    pub auto_advance_millis: u32,
+}
+
+impl ScenePacing {
+    pub fn normal() -> ScenePacing {
+        ScenePacing { speed_percent: 100, auto_advance_millis: 0 }
+    }
+}
 
 // ========================================================================= //
 
 pub struct Resources<'a> {
-    renderer: &'a SdlCanvas<SdlWindow>,
+    renderer: Renderer<'a>,
     cache: &'a mut ResourceCache,
+    color_blind: bool,
+    pacing: ScenePacing,
 }
 
 impl<'a> Resources<'a> {
     pub fn new(
-        renderer: &'a SdlCanvas<SdlWindow>,
+        renderer: Renderer<'a>,
         cache: &'a mut ResourceCache,
+        color_blind: bool,
+        pacing: ScenePacing,
     ) -> Resources<'a> {
-        Resources { renderer, cache }
+        Resources { renderer, cache, color_blind, pacing }
+    }
+
+    /// Returns true if elements created with these resources should use a
+    /// color-blind-friendly palette and mark colored pieces with letters.
+    pub fn color_blind_mode(&self) -> bool {
+        self.color_blind
+    }
+
+    /// Returns how quickly cutscenes compiled with these resources should
+    /// play.
+    pub fn scene_pacing(&self) -> ScenePacing {
+        self.pacing
     }
 
     pub fn get_background(&mut self, name: &str) -> Rc<Background> {
@@ -56,6 +90,24 @@ impl<'a> Resources<'a> {
     pub fn get_sprites(&mut self, name: &str) -> Vec<Sprite> {
         self.cache.get_sprites(self.renderer, name)
     }
+
+    /// Returns the translation of the given English text into the current
+    /// language (see `ResourceCache::set_language`).
+    pub fn translate<'b>(&'b self, text: &'b str) -> &'b str {
+        self.cache.translate(text)
+    }
+
+    /// Like `translate`, but for text that isn't specific to the current
+    /// location, such as button labels.
+    pub fn translate_common<'b>(&'b self, text: &'b str) -> &'b str {
+        self.cache.translate_common(text)
+    }
+
+    /// Returns a handle for sending displayed text to the accessibility
+    /// output channel.
+    pub fn announcer(&self) -> Announcer {
+        self.cache.announcer().clone()
+    }
 }
 
 // ========================================================================= //
@@ -65,6 +117,9 @@ pub struct ResourceCache {
     fonts: HashMap<String, Rc<Font>>,
     sprites: HashMap<String, Vec<Sprite>>,
     loader: ResourceLoader,
+    catalog: Catalog,
+    section: String,
+    announcer: Announcer,
 }
 
 impl ResourceCache {
@@ -74,12 +129,63 @@ impl ResourceCache {
             fonts: HashMap::new(),
             sprites: HashMap::new(),
             loader: ResourceLoader::new(),
+            catalog: Catalog::english(),
+            section: String::new(),
+            announcer: Announcer::silent(),
+        }
+    }
+
+    /// Loads the text catalogue for the given language, falling back to
+    /// English if there isn't one.
+    pub fn set_language(&mut self, language: &str) {
+        if language == self.catalog.language() {
+            return;
         }
+        self.catalog = match Catalog::load(&self.loader, language) {
+            Ok(catalog) => catalog,
+            Err(error) => {
+                println!("Failed to load {} catalogue: {}", language, error);
+                Catalog::english()
+            }
+        };
+    }
+
+    /// Sets which section of the catalogue (usually the current location's
+    /// key) text is translated from.
+    pub fn set_text_section(&mut self, section: &str) {
+        self.section = section.to_string();
+    }
+
+    pub fn translate<'a>(&'a self, text: &'a str) -> &'a str {
+        self.catalog.translate(&self.section, text)
+    }
+
+    pub fn translate_common<'a>(&'a self, text: &'a str) -> &'a str {
+        self.catalog.translate(COMMON_SECTION, text)
+    }
+
+    /// Starts recording all translated text, for `text_template`.
+    pub fn record_text(&mut self) {
+        self.catalog.record_lines();
+    }
+
+    /// Returns a catalogue template for the current language covering all
+    /// text translated since `record_text` was called.
+    pub fn text_template(&self) -> String {
+        self.catalog.template()
+    }
+
+    pub fn set_announcer(&mut self, announcer: Announcer) {
+        self.announcer = announcer;
+    }
+
+    pub fn announcer(&self) -> &Announcer {
+        &self.announcer
     }
 
     fn get_background(
         &mut self,
-        renderer: &SdlCanvas<SdlWindow>,
+        renderer: Renderer,
         name: &str,
     ) -> Rc<Background> {
         if let Some(background) = self.backgrounds.get(name) {
@@ -101,11 +207,7 @@ impl ResourceCache {
         background
     }
 
-    fn get_font(
-        &mut self,
-        renderer: &SdlCanvas<SdlWindow>,
-        name: &str,
-    ) -> Rc<Font> {
+    fn get_font(&mut self, renderer: Renderer, name: &str) -> Rc<Font> {
         if let Some(font) = self.fonts.get(name) {
             return font.clone();
         }
@@ -119,11 +221,7 @@ impl ResourceCache {
         font
     }
 
-    fn get_sprites(
-        &mut self,
-        renderer: &SdlCanvas<SdlWindow>,
-        name: &str,
-    ) -> Vec<Sprite> {
+    fn get_sprites(&mut self, renderer: Renderer, name: &str) -> Vec<Sprite> {
         if let Some(vec) = self.sprites.get(name) {
             return vec.clone();
         }
@@ -257,11 +257,22 @@ impl Sound {
 
 pub struct SoundQueue {
     queue: Mutex<Vec<Sound>>,
+    gain: Mutex<f32>,
 }
 
 impl SoundQueue {
     pub fn new() -> SoundQueue {
-        SoundQueue { queue: Mutex::new(Vec::new()) }
+        SoundQueue { queue: Mutex::new(Vec::new()), gain: Mutex::new(1.0) }
+    }
+
+    /// Sets the factor that the mixed output is scaled by, from 0.0 (silent)
+    /// to 1.0 (full volume).
+    pub fn set_gain(&self, gain: f32) {
+        *self.gain.lock().unwrap() = gain.clamp(0.0, 1.0);
+    }
+
+    pub fn gain(&self) -> f32 {
+        *self.gain.lock().unwrap()
     }
 
     pub fn enqueue(&self, mut sounds: Vec<Sound>) {
@@ -333,6 +344,12 @@ impl AudioCallback for SoundMixer {
         }
         debug_assert!(self.active_sounds.is_empty());
         self.active_sounds = new_sounds;
+        let gain = self.sound_queue.gain();
+        if gain < 1.0 {
+            for sample in out.iter_mut() {
+                *sample *= gain;
+            }
+        }
     }
 }
 
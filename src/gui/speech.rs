@@ -0,0 +1,389 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::Chars;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// ========================================================================= //
+
+/// Where to send text for a screen reader or speech synthesizer to voice.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SpeechOutput {
+    /// Don't send text anywhere.
+    Off,
+    /// Print each line of text to stdout.
+    Stdout,
+    /// Write each line of text to a file, usually a named pipe that some
+    /// other program is reading from.
+    Pipe(PathBuf),
+    /// Send each line of text to a speech-dispatcher-compatible (SSIP) Unix
+    /// socket.  With no path, the socket is found the same way that
+    /// speech-dispatcher's own clients find it.
+    SpeechDispatcher(Option<PathBuf>),
+}
+
+impl SpeechOutput {
+    pub fn key(&self) -> String {
+        match self {
+            SpeechOutput::Off => "off".to_string(),
+            SpeechOutput::Stdout => "stdout".to_string(),
+            SpeechOutput::Pipe(path) => format!("pipe:{}", path.display()),
+            SpeechOutput::SpeechDispatcher(None) => "ssip".to_string(),
+            SpeechOutput::SpeechDispatcher(Some(path)) => {
+                format!("ssip:{}", path.display())
+            }
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<SpeechOutput> {
+        match key {
+            "off" => Some(SpeechOutput::Off),
+            "stdout" => Some(SpeechOutput::Stdout),
+            "ssip" => Some(SpeechOutput::SpeechDispatcher(None)),
+            _ => {
+                if let Some(path) = key.strip_prefix("pipe:") {
+                    Some(SpeechOutput::Pipe(PathBuf::from(path)))
+                } else if let Some(path) = key.strip_prefix("ssip:") {
+                    let path = PathBuf::from(path);
+                    Some(SpeechOutput::SpeechDispatcher(Some(path)))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// A handle for sending displayed text to the accessibility output channel.
+/// Cloning an `Announcer` is cheap, and all clones share the same channel.
+/// Text is written on a background thread, so announcing never blocks the
+/// game, even if e.g. nothing is reading from the pipe yet.
+#[derive(Clone)]
+pub struct Announcer {
+    sender: Option<Sender<String>>,
+}
+
+impl Announcer {
+    /// Returns an announcer that discards all text.
+    pub fn silent() -> Announcer {
+        Announcer { sender: None }
+    }
+
+    pub fn new(output: &SpeechOutput) -> Announcer {
+        if *output == SpeechOutput::Off {
+            return Announcer::silent();
+        }
+        let (sender, receiver) = mpsc::channel();
+        let output = output.clone();
+        thread::spawn(move || {
+            if let Err(error) = write_speech(&output, receiver) {
+                println!("Speech output {} failed: {}", output.key(), error);
+            }
+        });
+        Announcer { sender: Some(sender) }
+    }
+
+    /// Returns an announcer along with a receiver for the plain text it is
+    /// sent, for tests to check what would be read aloud.
+    #[cfg(test)]
+    pub fn capture() -> (Announcer, Receiver<String>) {
+        let (sender, receiver) = mpsc::channel();
+        (Announcer { sender: Some(sender) }, receiver)
+    }
+
+    /// Sends the given text, which may contain `Paragraph` formatting
+    /// codes, to be read aloud as plain text.
+    pub fn announce(&self, text: &str) {
+        if let Some(ref sender) = self.sender {
+            let text = plain_text(text);
+            if !text.is_empty() {
+                let _ = sender.send(text);
+            }
+        }
+    }
+}
+
+fn write_speech(
+    output: &SpeechOutput,
+    receiver: Receiver<String>,
+) -> io::Result<()> {
+    match output {
+        SpeechOutput::Off => Ok(()),
+        SpeechOutput::Stdout => {
+            for text in receiver.iter() {
+                println!("{}", text);
+            }
+            Ok(())
+        }
+        SpeechOutput::Pipe(path) => {
+            // Opening a named pipe blocks until there's a reader, which is
+            // fine here on the writer thread.
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            for text in receiver.iter() {
+                writeln!(file, "{}", text)?;
+                file.flush()?;
+            }
+            Ok(())
+        }
+        SpeechOutput::SpeechDispatcher(path) => {
+            write_ssip(path.clone(), receiver)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn write_ssip(
+    path: Option<PathBuf>,
+    receiver: Receiver<String>,
+) -> io::Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    let path = match path.or_else(default_ssip_socket) {
+        Some(path) => path,
+        None => {
+            let msg = "could not find speech-dispatcher socket";
+            return Err(io::Error::new(io::ErrorKind::NotFound, msg));
+        }
+    };
+    let stream = UnixStream::connect(path)?;
+    let mut reader = io::BufReader::new(stream.try_clone()?);
+    run_ssip_session(&mut reader, &stream, receiver)
+}
+
+/// Speaks each line of text received over an SSIP connection, waiting for
+/// the server to accept each command before sending the next.  Returns an
+/// error as soon as the server rejects a command.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn run_ssip_session<R: BufRead, W: Write>(
+    reader: &mut R,
+    mut writer: W,
+    receiver: Receiver<String>,
+) -> io::Result<()> {
+    writer.write_all(b"SET SELF CLIENT_NAME user:syzygy:main\r\n")?;
+    read_ssip_reply(reader, 208)?;
+    for text in receiver.iter() {
+        writer.write_all(b"SPEAK\r\n")?;
+        read_ssip_reply(reader, 230)?;
+        writer.write_all(ssip_data(&text).as_bytes())?;
+        read_ssip_reply(reader, 225)?;
+    }
+    writer.write_all(b"QUIT\r\n")
+}
+
+/// Reads one (possibly multi-line) reply from an SSIP server, and returns an
+/// error unless it has the expected status code.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn read_ssip_reply<R: BufRead>(
+    reader: &mut R,
+    expected: u32,
+) -> io::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            let msg = "speech-dispatcher closed the connection";
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg));
+        }
+        // Every line of a reply starts with its status code, followed by a
+        // dash on all but the last line.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+    let line = line.trim_end();
+    if line.get(..3).and_then(|code| code.parse().ok()) == Some(expected) {
+        Ok(())
+    } else {
+        let msg = format!("speech-dispatcher replied {:?}", line);
+        Err(io::Error::new(io::ErrorKind::Other, msg))
+    }
+}
+
+#[cfg(not(unix))]
+fn write_ssip(_: Option<PathBuf>, _: Receiver<String>) -> io::Result<()> {
+    let msg = "speech-dispatcher sockets are not supported on this platform";
+    Err(io::Error::new(io::ErrorKind::Other, msg))
+}
+
+#[cfg(unix)]
+fn default_ssip_socket() -> Option<PathBuf> {
+    use std::env;
+
+    if let Ok(address) = env::var("SPEECHD_ADDRESS") {
+        if let Some(path) = address.strip_prefix("unix_socket:") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    env::var("XDG_RUNTIME_DIR").ok().map(|dir| {
+        PathBuf::from(dir).join("speech-dispatcher").join("speechd.sock")
+    })
+}
+
+/// Returns the SSIP message data for speaking the given text, to be sent
+/// once the server has accepted a SPEAK command.  In SSIP, message data ends
+/// with a line containing just a period, so any line of the text that starts
+/// with a period has it doubled.
+fn ssip_data(text: &str) -> String {
+    let mut message = String::new();
+    for line in text.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push_str(".\r\n");
+    message
+}
+
+/// Strips `Paragraph` formatting codes from the given text, and joins its
+/// lines with spaces.
+fn plain_text(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(chr) = chars.next() {
+        if chr == '$' {
+            match chars.next() {
+                Some('$') => plain.push('$'),
+                Some('f') => {
+                    parse_arg(&mut chars);
+                }
+                Some('M') => {
+                    let mobile = parse_arg(&mut chars);
+                    let desktop = parse_arg(&mut chars);
+                    if cfg!(any(target_os = "android", target_os = "ios")) {
+                        plain.push_str(&mobile);
+                    } else {
+                        plain.push_str(&desktop);
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            plain.push(chr);
+        }
+    }
+    plain.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn parse_arg(chars: &mut Chars) -> String {
+    let mut arg = String::new();
+    if chars.next() == Some('{') {
+        for chr in chars {
+            if chr == '}' {
+                break;
+            }
+            arg.push(chr);
+        }
+    }
+    arg
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use std::io::{self, Cursor};
+    use std::sync::mpsc;
+
+    use super::{plain_text, run_ssip_session, ssip_data, SpeechOutput};
+
+    #[test]
+    fn speech_output_keys_round_trip() {
+        let outputs = vec![
+            SpeechOutput::Off,
+            SpeechOutput::Stdout,
+            SpeechOutput::Pipe(PathBuf::from("/tmp/syzygy.fifo")),
+            SpeechOutput::SpeechDispatcher(None),
+            SpeechOutput::SpeechDispatcher(Some(PathBuf::from("/run/s.sock"))),
+        ];
+        for output in outputs {
+            assert_eq!(SpeechOutput::from_key(&output.key()), Some(output));
+        }
+        assert_eq!(SpeechOutput::from_key("bogus"), None);
+    }
+
+    #[test]
+    fn formatting_codes_are_stripped() {
+        assert_eq!(
+            plain_text("$CHello,\n$ithere$r.  It costs $$5."),
+            "Hello, there. It costs $5."
+        );
+        assert_eq!(plain_text("$M{Tap}{Click} $f{system}here"), "Click here");
+    }
+
+    #[test]
+    fn ssip_escapes_leading_periods() {
+        assert_eq!(
+            ssip_data("Wait...\n...what?"),
+            "Wait...\r\n....what?\r\n.\r\n"
+        );
+    }
+
+    fn ssip_session(
+        replies: &str,
+        texts: &[&str],
+    ) -> (io::Result<()>, String) {
+        let (sender, receiver) = mpsc::channel();
+        for &text in texts {
+            sender.send(text.to_string()).unwrap();
+        }
+        drop(sender);
+        let mut reader = Cursor::new(replies.as_bytes().to_vec());
+        let mut written = Vec::new();
+        let result = run_ssip_session(&mut reader, &mut written, receiver);
+        (result, String::from_utf8(written).unwrap())
+    }
+
+    #[test]
+    fn ssip_waits_for_server_to_accept_text() {
+        let replies = "208 OK CLIENT NAME SET\r\n\
+                       230 OK RECEIVING DATA\r\n\
+                       225-21\r\n225 OK MESSAGE QUEUED\r\n";
+        let (result, written) = ssip_session(replies, &["Hello."]);
+        assert!(result.is_ok());
+        assert_eq!(
+            written,
+            "SET SELF CLIENT_NAME user:syzygy:main\r\n\
+             SPEAK\r\nHello.\r\n.\r\nQUIT\r\n"
+        );
+    }
+
+    #[test]
+    fn ssip_stops_when_server_refuses() {
+        let replies = "208 OK CLIENT NAME SET\r\n\
+                       401 ERR NOT ALLOWED\r\n";
+        let (result, written) = ssip_session(replies, &["Hello."]);
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("401 ERR NOT ALLOWED"));
+        assert!(!written.contains("Hello."));
+        let (result, _) = ssip_session("", &["Hello."]);
+        assert!(result.is_err());
+    }
+}
+
+// ========================================================================= //
@@ -26,41 +26,46 @@ use sdl2::surface::Surface;
 use sdl2::video::Window as SdlWindow;
 use std::rc::Rc;
 
+use super::image::RgbaImage;
+
+// ========================================================================= //
+
+/// Determines how sprites store their pixels: as textures on an SDL window's
+/// renderer, or as in-memory images for drawing with no display attached.
+#[derive(Clone, Copy)]
+pub enum Renderer<'a> {
+    Window(&'a SdlCanvas<SdlWindow>),
+    Headless,
+}
+
 // ========================================================================= //
 
 #[derive(Clone)]
 pub struct Sprite {
     width: u32,
     height: u32,
-    texture: Rc<Texture>,
+    data: SpriteData,
+}
+
+#[derive(Clone)]
+pub enum SpriteData {
+    Texture(Rc<Texture>),
+    Image(Rc<RgbaImage>),
 }
 
 impl Sprite {
-    pub fn new(renderer: &SdlCanvas<SdlWindow>, image: &ahi::Image) -> Sprite {
+    pub fn new(renderer: Renderer, image: &ahi::Image) -> Sprite {
         let width = image.width();
         let height = image.height();
-        let mut data = image.rgba_data();
-        let bytes_per_pixel = 4;
-        let format = if cfg!(target_endian = "big") {
-            PixelFormatEnum::RGBA8888
-        } else {
-            PixelFormatEnum::ABGR8888
+        let data = match renderer {
+            Renderer::Window(renderer) => {
+                SpriteData::Texture(Rc::new(create_texture(renderer, image)))
+            }
+            Renderer::Headless => {
+                SpriteData::Image(Rc::new(RgbaImage::from_ahi(image)))
+            }
         };
-        let surface = Surface::from_data(
-            &mut data,
-            width,
-            height,
-            width * bytes_per_pixel,
-            format,
-        )
-        .unwrap();
-        Sprite {
-            width,
-            height,
-            texture: Rc::new(
-                renderer.create_texture_from_surface(&surface).unwrap(),
-            ),
-        }
+        Sprite { width, height, data }
     }
 
     pub fn width(&self) -> u32 {
@@ -75,9 +80,33 @@ impl Sprite {
         Rect::new(0, 0, self.width, self.height)
     }
 
-    pub fn sdl2_texture(&self) -> &Texture {
-        &self.texture
+    pub fn data(&self) -> &SpriteData {
+        &self.data
     }
 }
 
+fn create_texture(
+    renderer: &SdlCanvas<SdlWindow>,
+    image: &ahi::Image,
+) -> Texture {
+    let width = image.width();
+    let height = image.height();
+    let mut data = image.rgba_data();
+    let bytes_per_pixel = 4;
+    let format = if cfg!(target_endian = "big") {
+        PixelFormatEnum::RGBA8888
+    } else {
+        PixelFormatEnum::ABGR8888
+    };
+    let surface = Surface::from_data(
+        &mut data,
+        width,
+        height,
+        width * bytes_per_pixel,
+        format,
+    )
+    .unwrap();
+    renderer.create_texture_from_surface(&surface).unwrap()
+}
+
 // ========================================================================= //
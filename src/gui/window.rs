@@ -18,34 +18,138 @@
 // +--------------------------------------------------------------------------+
 
 use sdl2::audio::AudioDevice;
+use sdl2::event::WindowEvent;
 use sdl2::rect::{Point, Rect};
 use sdl2::render::Canvas as SdlCanvas;
-use sdl2::video::FullscreenType;
 use sdl2::video::Window as SdlWindow;
+use sdl2::video::{FullscreenType, WindowPos};
 use sdl2::{AudioSubsystem, EventPump, Sdl, VideoSubsystem};
+use std::collections::VecDeque;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use super::canvas::{Align, Canvas};
 use super::element::Element;
-use super::event::Event;
+use super::event::{Event, EventScript, EventSource};
 use super::font::Font;
-use super::resources::{ResourceCache, Resources};
+use super::gamepad::Gamepads;
+use super::image::RgbaImage;
+use super::resources::{ResourceCache, Resources, ScenePacing};
 use super::sound::{Sound, SoundMixer, SoundQueue};
+use super::speech::{Announcer, SpeechOutput};
+use super::sprite::Renderer;
+use super::FRAME_DELAY_MILLIS;
+
+// ========================================================================= //
+
+pub type EventObserver = Box<dyn FnMut(&Event)>;
+
+/// How the game's pixels are mapped onto the window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScaleMode {
+    /// Scales the game up by the largest whole number that fits, so that
+    /// every game pixel is the same size, showing more of the background
+    /// to fill the rest of the window.
+    IntegerScale,
+    /// Scales the game smoothly to fill the whole window without distorting
+    /// it, showing more of the background if the window's shape differs from
+    /// the game's.
+    Fill,
+    /// Scales the game smoothly to fit inside the window, with black bars
+    /// along the edges if the window's shape differs from the game's.
+    Letterbox,
+}
+
+impl ScaleMode {
+    pub fn all() -> &'static [ScaleMode] {
+        &[ScaleMode::IntegerScale, ScaleMode::Fill, ScaleMode::Letterbox]
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            ScaleMode::IntegerScale => "integer",
+            ScaleMode::Fill => "fill",
+            ScaleMode::Letterbox => "letterbox",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScaleMode> {
+        ScaleMode::all().iter().cloned().find(|mode| mode.key() == key)
+    }
+
+    /// Returns the logical size to render at for a window of the given size,
+    /// given the size that the game is designed to be seen at.
+    fn logical_size(
+        self,
+        window_size: (u32, u32),
+        ideal_size: (u32, u32),
+    ) -> (u32, u32) {
+        let (window_width, window_height) = window_size;
+        let (ideal_width, ideal_height) = ideal_size;
+        match self {
+            ScaleMode::IntegerScale => {
+                let scale = (window_width / ideal_width)
+                    .min(window_height / ideal_height)
+                    .max(1);
+                (
+                    (window_width / scale).max(ideal_width),
+                    (window_height / scale).max(ideal_height),
+                )
+            }
+            ScaleMode::Fill => {
+                let aspect_ratio =
+                    (window_width as f64) / (window_height as f64);
+                let ideal_ratio = (ideal_width as f64) / (ideal_height as f64);
+                if aspect_ratio > ideal_ratio {
+                    let width =
+                        (aspect_ratio * (ideal_height as f64)).round() as u32;
+                    (width, ideal_height)
+                } else {
+                    let height =
+                        ((ideal_width as f64) / aspect_ratio).round() as u32;
+                    (ideal_width, height)
+                }
+            }
+            ScaleMode::Letterbox => ideal_size,
+        }
+    }
+}
 
 // ========================================================================= //
 
 pub struct Window {
-    _audio_subsystem: AudioSubsystem,
-    _audio_device: AudioDevice<SoundMixer>,
-    sound_queue: Arc<SoundQueue>,
-    _video_subsystem: VideoSubsystem,
-    renderer: SdlCanvas<SdlWindow>,
+    output: Output,
     full_rect: Rect,
-    event_pump: EventPump,
+    ideal_size: (u32, u32),
+    force_ideal: bool,
+    scale_mode: ScaleMode,
+    color_blind: bool,
+    scene_pacing: ScenePacing,
     resource_cache: ResourceCache,
     debug_font: Option<Rc<Font>>,
     debug_counter: i32,
+    replay: VecDeque<Event>,
+    observer: Option<EventObserver>,
+}
+
+enum Output {
+    Sdl {
+        _audio_subsystem: AudioSubsystem,
+        _audio_device: AudioDevice<SoundMixer>,
+        sound_queue: Arc<SoundQueue>,
+        _video_subsystem: VideoSubsystem,
+        renderer: SdlCanvas<SdlWindow>,
+        event_pump: EventPump,
+        gamepads: Option<Gamepads>,
+    },
+    Headless {
+        image: Option<RgbaImage>,
+        events: EventScript,
+        renders: usize,
+        capture: Option<usize>,
+    },
 }
 
 impl Window {
@@ -56,6 +160,7 @@ impl Window {
         ideal_size: (u32, u32),
         force_ideal: bool,
         fullscreen: bool,
+        scale_mode: ScaleMode,
     ) -> Window {
         // Init video:
         let (full_width, full_height) = full_size;
@@ -65,6 +170,7 @@ impl Window {
             video_subsystem
                 .window(title, ideal_width, ideal_height)
                 .position_centered()
+                .resizable()
                 .fullscreen_desktop()
                 .build()
                 .unwrap()
@@ -72,33 +178,20 @@ impl Window {
             video_subsystem
                 .window(title, ideal_width, ideal_height)
                 .position_centered()
+                .resizable()
                 .build()
                 .unwrap()
         };
-        let (actual_width, actual_height) = if force_ideal {
-            ideal_size
-        } else {
-            let (native_width, native_height) = sdl_window.size();
-            let aspect_ratio = (native_width as f64) / (native_height as f64);
-            let ideal_ratio = (ideal_width as f64) / (ideal_height as f64);
-            if aspect_ratio > ideal_ratio {
-                let actual_width =
-                    (aspect_ratio * (ideal_height as f64)).round() as u32;
-                (actual_width, ideal_height)
-            } else {
-                let actual_height =
-                    ((ideal_width as f64) / aspect_ratio).round() as u32;
-                (ideal_width, actual_height)
-            }
-        };
-        let mut renderer =
+        let renderer =
             sdl_window.into_canvas().present_vsync().build().unwrap();
-        renderer.set_logical_size(actual_width, actual_height).unwrap();
-        let offset_x = (actual_width as i32 - full_width as i32) / 2;
-        let offset_y = (actual_height as i32 - full_height as i32) / 2;
         let mut resource_cache = ResourceCache::new();
         let debug_font = if cfg!(debug_assertions) {
-            let mut resources = Resources::new(&renderer, &mut resource_cache);
+            let mut resources = Resources::new(
+                Renderer::Window(&renderer),
+                &mut resource_cache,
+                false,
+                ScenePacing::normal(),
+            );
             Some(resources.get_font("debug"))
         } else {
             None
@@ -111,31 +204,137 @@ impl Window {
             SoundMixer::audio_device(&audio_subsystem, sound_queue.clone());
         audio_device.resume();
 
-        Window {
-            _audio_subsystem: audio_subsystem,
-            _audio_device: audio_device,
-            sound_queue,
-            _video_subsystem: video_subsystem,
-            renderer,
-            full_rect: Rect::new(offset_x, offset_y, full_width, full_height),
-            event_pump: sdl_context.event_pump().unwrap(),
+        // Init game controllers (if any are ever plugged in, SDL will send us
+        // a ControllerDeviceAdded event for each one):
+        let gamepads = match sdl_context.game_controller() {
+            Ok(subsystem) => Some(Gamepads::new(subsystem)),
+            Err(error) => {
+                if cfg!(debug_assertions) {
+                    println!("Game controllers unavailable: {}", error);
+                }
+                None
+            }
+        };
+
+        let mut window = Window {
+            output: Output::Sdl {
+                _audio_subsystem: audio_subsystem,
+                _audio_device: audio_device,
+                sound_queue,
+                _video_subsystem: video_subsystem,
+                renderer,
+                event_pump: sdl_context.event_pump().unwrap(),
+                gamepads,
+            },
+            full_rect: Rect::new(0, 0, full_width, full_height),
+            ideal_size,
+            force_ideal,
+            scale_mode,
+            color_blind: false,
+            scene_pacing: ScenePacing::normal(),
             resource_cache,
             debug_font,
             debug_counter: 0,
+            replay: VecDeque::new(),
+            observer: None,
+        };
+        window.update_layout();
+        window
+    }
+
+    /// Creates a window that renders into an in-memory image of the given
+    /// size instead of onto a display, without initializing SDL at all.
+    /// Sounds played into a headless window are discarded, and its events
+    /// come only from `queue_events`; once those run out, `next_event`
+    /// returns `Event::Quit`.  Drawing in software is slow, so nothing is
+    /// drawn except a frame asked for with `capture_frame`.
+    pub fn headless(full_size: (u32, u32)) -> Window {
+        let (full_width, full_height) = full_size;
+        Window {
+            output: Output::Headless {
+                image: None,
+                events: EventScript::new(vec![]),
+                renders: 0,
+                capture: None,
+            },
+            full_rect: Rect::new(0, 0, full_width, full_height),
+            ideal_size: full_size,
+            force_ideal: true,
+            scale_mode: ScaleMode::Letterbox,
+            color_blind: false,
+            scene_pacing: ScenePacing::normal(),
+            resource_cache: ResourceCache::new(),
+            debug_font: None,
+            debug_counter: 0,
+            replay: VecDeque::new(),
+            observer: None,
         }
     }
 
     pub fn visible_rect(&self) -> Rect {
-        let (width, height) = self.renderer.logical_size();
+        let (width, height) = match self.output {
+            Output::Sdl { ref renderer, .. } => renderer.logical_size(),
+            Output::Headless { .. } => self.ideal_size,
+        };
         Rect::new(-self.full_rect.x(), -self.full_rect.y(), width, height)
     }
 
-    #[allow(dead_code)]
+    /// Makes a headless window draw the frame for the given call to
+    /// `render` from now on, counting from zero, or no frame at all if
+    /// `None`.  Also resets the count of calls and discards any frame
+    /// captured so far.  Has no effect on a window with a display.
+    #[cfg(test)]
+    pub fn capture_frame(&mut self, index: Option<usize>) {
+        if let Output::Headless {
+            ref mut image,
+            ref mut renders,
+            ref mut capture,
+            ..
+        } = self.output
+        {
+            *image = None;
+            *renders = 0;
+            *capture = index;
+        }
+    }
+
+    /// Returns how many times `render` has been called on a headless window
+    /// since `capture_frame` was last called.
+    #[cfg(test)]
+    pub fn render_count(&self) -> usize {
+        match self.output {
+            Output::Sdl { .. } => 0,
+            Output::Headless { renders, .. } => renders,
+        }
+    }
+
+    /// Returns the frame captured by a headless window, if it has been
+    /// rendered yet.
+    #[cfg(test)]
+    pub fn headless_image(&self) -> Option<&RgbaImage> {
+        match self.output {
+            Output::Sdl { .. } => None,
+            Output::Headless { ref image, .. } => image.as_ref(),
+        }
+    }
+
+    /// Adds events for a headless window to return from `next_event`.  Has
+    /// no effect on a window with a display.
+    pub fn queue_events<I: IntoIterator<Item = Event>>(&mut self, new: I) {
+        if let Output::Headless { ref mut events, .. } = self.output {
+            events.extend(new);
+        }
+    }
+
     pub fn is_fullscreen(&self) -> bool {
-        self.renderer.window().fullscreen_state() != FullscreenType::Off
+        match self.output {
+            Output::Sdl { ref renderer, .. } => {
+                renderer.window().fullscreen_state() != FullscreenType::Off
+            }
+            Output::Headless { .. } => false,
+        }
     }
 
-    #[allow(dead_code)]
     pub fn set_fullscreen(&mut self, fullscreen: bool) {
         if fullscreen != self.is_fullscreen() {
             let state = if fullscreen {
@@ -143,21 +342,85 @@ impl Window {
             } else {
                 FullscreenType::Off
             };
-            if cfg!(debug_assertions) {
-                println!("Setting fullscreen to {:?}.", state);
+            if let Output::Sdl { ref mut renderer, .. } = self.output {
+                if cfg!(debug_assertions) {
+                    println!("Setting fullscreen to {:?}.", state);
+                }
+                renderer.window_mut().set_fullscreen(state).unwrap();
             }
-            self.renderer.window_mut().set_fullscreen(state).unwrap();
         }
     }
 
+    /// Resizes the window to a whole multiple of its logical size.  Has no
+    /// effect while the window is fullscreen.
+    pub fn set_window_scale(&mut self, scale: u32) {
+        if self.is_fullscreen() {
+            return;
+        }
+        let (width, height) = self.ideal_size;
+        if let Output::Sdl { ref mut renderer, .. } = self.output {
+            let window = renderer.window_mut();
+            if window.set_size(width * scale, height * scale).is_ok() {
+                window.set_position(WindowPos::Centered, WindowPos::Centered);
+            }
+        }
+    }
+
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        if mode != self.scale_mode {
+            self.scale_mode = mode;
+            self.update_layout();
+        }
+    }
+
+    /// Recomputes the logical size and the position of the game within it,
+    /// e.g. after the window has been resized.
+    fn update_layout(&mut self) {
+        let renderer = match self.output {
+            Output::Sdl { ref mut renderer, .. } => renderer,
+            Output::Headless { .. } => return,
+        };
+        let (width, height) = if self.force_ideal {
+            self.ideal_size
+        } else {
+            let window_size = renderer.window().size();
+            self.scale_mode.logical_size(window_size, self.ideal_size)
+        };
+        renderer.set_logical_size(width, height).unwrap();
+        let integer = self.scale_mode == ScaleMode::IntegerScale;
+        renderer.set_integer_scale(integer).unwrap();
+        let offset_x = (width as i32 - self.full_rect.width() as i32) / 2;
+        let offset_y = (height as i32 - self.full_rect.height() as i32) / 2;
+        self.full_rect.set_x(offset_x);
+        self.full_rect.set_y(offset_y);
+    }
+
     pub fn render<S, A, E: Element<S, A>>(&mut self, state: &S, view: &E) {
+        let visible = self.visible_rect();
+        let renderer = match self.output {
+            Output::Sdl { ref mut renderer, .. } => renderer,
+            Output::Headless {
+                ref mut image,
+                ref mut renders,
+                capture,
+                ..
+            } => {
+                if capture == Some(*renders) {
+                    let (width, height) = self.ideal_size;
+                    let image = image.insert(RgbaImage::new(width, height));
+                    let mut canvas = Canvas::from_image(image, self.full_rect);
+                    view.draw(state, &mut canvas);
+                }
+                *renders += 1;
+                return;
+            }
+        };
         {
-            let mut canvas = Canvas::new(&mut self.renderer, self.full_rect);
+            let mut canvas = Canvas::new(renderer, self.full_rect);
             view.draw(state, &mut canvas);
         }
         if cfg!(debug_assertions) {
-            let visible = self.visible_rect();
-            let mut canvas = Canvas::new(&mut self.renderer, self.full_rect);
+            let mut canvas = Canvas::new(renderer, self.full_rect);
             if let Some(ref font) = self.debug_font {
                 canvas.fill_rect(
                     (0, 0, 0),
@@ -172,13 +435,81 @@ impl Window {
                 self.debug_counter = (self.debug_counter + 1) % 1000;
             }
         }
-        self.renderer.present();
+        renderer.present();
+    }
+
+    /// Makes `next_event` return the given events before any live input,
+    /// as when replaying a recorded session.  Each replayed clock tick is
+    /// paced to the usual frame rate, and live input other than quitting is
+    /// discarded until the replayed events run out.
+    pub fn replay_events<I: IntoIterator<Item = Event>>(&mut self, events: I) {
+        self.replay.extend(events);
+    }
+
+    /// Sets a function to be called with every event that `next_event`
+    /// returns, e.g. to record the session.
+    pub fn observe_events(&mut self, observer: EventObserver) {
+        self.observer = Some(observer);
     }
 
     /// Blocks until the next event is available.
     pub fn next_event(&mut self) -> Event {
+        let event = self.pop_event();
+        if let Some(ref mut observer) = self.observer {
+            observer(&event);
+        }
+        event
+    }
+
+    fn pop_event(&mut self) -> Event {
+        let event_pump = match self.output {
+            Output::Sdl { ref mut event_pump, .. } => event_pump,
+            Output::Headless { ref mut events, .. } => {
+                return self
+                    .replay
+                    .pop_front()
+                    .unwrap_or_else(|| events.next_event());
+            }
+        };
+        if let Some(event) = self.replay.pop_front() {
+            let quit = event_pump
+                .poll_iter()
+                .any(|event| Event::from_sdl2(&event) == Some(Event::Quit));
+            if quit {
+                self.replay.clear();
+                return Event::Quit;
+            }
+            if event == Event::ClockTick {
+                thread::sleep(Duration::from_millis(
+                    FRAME_DELAY_MILLIS as u64,
+                ));
+            }
+            return event;
+        }
         loop {
-            match Event::from_sdl2(&self.event_pump.wait_event()) {
+            let (sdl_event, gamepad_event) = match self.output {
+                Output::Sdl {
+                    ref mut event_pump, ref mut gamepads, ..
+                } => {
+                    let sdl_event = event_pump.wait_event();
+                    let gamepad_event = gamepads
+                        .as_mut()
+                        .and_then(|gamepads| gamepads.handle_sdl2(&sdl_event));
+                    (sdl_event, gamepad_event)
+                }
+                Output::Headless { .. } => unreachable!(),
+            };
+            if let Some(event) = gamepad_event {
+                return event;
+            }
+            if let sdl2::event::Event::Window {
+                win_event: WindowEvent::SizeChanged(..),
+                ..
+            } = sdl_event
+            {
+                self.update_layout();
+            }
+            match Event::from_sdl2(&sdl_event) {
                 Some(event) => {
                     return event
                         .translate(-self.full_rect.x(), -self.full_rect.y())
@@ -189,11 +520,121 @@ impl Window {
     }
 
     pub fn resources(&mut self) -> Resources {
-        Resources::new(&self.renderer, &mut self.resource_cache)
+        let renderer = match self.output {
+            Output::Sdl { ref renderer, .. } => Renderer::Window(renderer),
+            Output::Headless { .. } => Renderer::Headless,
+        };
+        Resources::new(
+            renderer,
+            &mut self.resource_cache,
+            self.color_blind,
+            self.scene_pacing,
+        )
+    }
+
+    /// Switches the game's text to the given language, if there is a
+    /// catalogue for it.
+    pub fn set_language(&mut self, language: &str) {
+        self.resource_cache.set_language(language);
+    }
+
+    /// Starts recording all text translated from now on; see
+    /// `text_template`.
+    pub fn record_text(&mut self) {
+        self.resource_cache.record_text();
+    }
+
+    /// Returns a text catalogue template for the current language, covering
+    /// every line translated since `record_text` was called.
+    pub fn text_template(&self) -> String {
+        self.resource_cache.text_template()
+    }
+
+    /// Sets which section of the text catalogue views created from now on
+    /// are translated from.
+    pub fn set_text_section(&mut self, section: &str) {
+        self.resource_cache.set_text_section(section);
+    }
+
+    /// Sets whether views created from now on should use a
+    /// color-blind-friendly palette.
+    pub fn set_color_blind_mode(&mut self, color_blind: bool) {
+        self.color_blind = color_blind;
+    }
+
+    /// Sets where displayed text is sent for a screen reader to voice.
+    pub fn set_speech_output(&mut self, output: &SpeechOutput) {
+        self.resource_cache.set_announcer(Announcer::new(output));
+    }
+
+    #[cfg(test)]
+    pub fn set_announcer(&mut self, announcer: Announcer) {
+        self.resource_cache.set_announcer(announcer);
+    }
+
+    /// Sets how quickly cutscenes created from now on should play.
+    pub fn set_scene_pacing(&mut self, pacing: ScenePacing) {
+        self.scene_pacing = pacing;
+    }
+
+    /// Sets how loud sounds are played, from 0.0 (silent) to 1.0 (full
+    /// volume).
+    pub fn set_sound_gain(&mut self, gain: f32) {
+        if let Output::Sdl { ref sound_queue, .. } = self.output {
+            sound_queue.set_gain(gain);
+        }
     }
 
     pub fn play_sounds(&mut self, sounds: Vec<Sound>) {
-        self.sound_queue.enqueue(sounds);
+        if let Output::Sdl { ref sound_queue, .. } = self.output {
+            sound_queue.enqueue(sounds);
+        }
+    }
+}
+
+impl EventSource for Window {
+    fn next_event(&mut self) -> Event {
+        Window::next_event(self)
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::ScaleMode;
+
+    const IDEAL: (u32, u32) = (480, 320);
+
+    #[test]
+    fn scale_mode_key_round_trip() {
+        for &mode in ScaleMode::all() {
+            assert_eq!(ScaleMode::from_key(mode.key()), Some(mode));
+        }
+        assert_eq!(ScaleMode::from_key("zoom"), None);
+    }
+
+    #[test]
+    fn integer_scale_uses_whole_multiples() {
+        let mode = ScaleMode::IntegerScale;
+        assert_eq!(mode.logical_size((1920, 1080), IDEAL), (640, 360));
+        assert_eq!(mode.logical_size((1440, 960), IDEAL), (480, 320));
+        assert_eq!(mode.logical_size((1000, 700), IDEAL), (500, 350));
+        assert_eq!(mode.logical_size((400, 300), IDEAL), (480, 320));
+    }
+
+    #[test]
+    fn fill_matches_window_aspect_ratio() {
+        let mode = ScaleMode::Fill;
+        assert_eq!(mode.logical_size((1920, 1080), IDEAL), (569, 320));
+        assert_eq!(mode.logical_size((960, 960), IDEAL), (480, 480));
+    }
+
+    #[test]
+    fn letterbox_keeps_ideal_size() {
+        let mode = ScaleMode::Letterbox;
+        assert_eq!(mode.logical_size((1920, 1080), IDEAL), IDEAL);
+        assert_eq!(mode.logical_size((960, 960), IDEAL), IDEAL);
     }
 }
 
@@ -17,23 +17,31 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+mod cli;
 mod elements;
 mod gui;
 mod modes;
+mod recording;
 mod save;
 
 use std::path::PathBuf;
 
 use self::gui::{Event, Window, FRAME_DELAY_MILLIS};
 use self::modes::Mode;
-use self::save::{Location, SaveData};
+use self::recording::{Recorder, Recording};
+use self::save::{random, Access, Location, SaveData};
 
 // ========================================================================= //
 
 struct Flags {
+    command: Vec<String>,
     fullscreen: Option<bool>,
+    location: Option<Location>,
+    access: Option<Access>,
     save_file: Option<PathBuf>,
     window_size: Option<(u32, u32)>,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
 }
 
 impl Flags {
@@ -47,22 +55,68 @@ impl Flags {
             "override fullscreen setting",
             "BOOL",
         );
+        opts.optopt("", "location", "start the game at a location", "KEY");
+        opts.optopt(
+            "",
+            "access",
+            "with --location, force it to unvisited, unsolved or solved",
+            "STATE",
+        );
         opts.optopt("", "save_file", "override save file path", "FILE");
         opts.optopt("", "window_size", "override window size", "WxH");
+        opts.optopt("", "record", "record this session to a file", "FILE");
+        opts.optopt(
+            "",
+            "replay",
+            "replay a session recorded with --record",
+            "FILE",
+        );
         let matches = opts.parse(&args[1..]).unwrap_or_else(|failure| {
             println!("Error: {:?}", failure);
             println!("Run with --help to see available flags.");
             std::process::exit(1);
         });
         if matches.opt_present("help") {
-            let brief = format!("Usage: {} [options]", &args[0]);
+            let brief = format!("Usage: {} [options] [command]", &args[0]);
             print!("{}", opts.usage(&brief));
+            print!("\n{}", cli::COMMAND_USAGE);
             std::process::exit(0);
         }
         let fullscreen = matches
             .opt_default("fullscreen", "true")
             .and_then(|value| value.parse().ok());
+        let location = matches.opt_str("location").map(|key| {
+            if key == Location::Map.key() {
+                Location::Map
+            } else {
+                cli::parse_location(&key).unwrap_or_else(exit_with_error)
+            }
+        });
+        let access = matches.opt_str("access").map(|key| {
+            match cli::parse_access(&key).unwrap_or_else(exit_with_error) {
+                access @ Access::Unvisited
+                | access @ Access::Unsolved
+                | access @ Access::Solved => access,
+                _ => exit_with_error(format!(
+                    "--access must be unvisited, unsolved or solved, not {}",
+                    key
+                )),
+            }
+        });
+        match location {
+            Some(Location::Map) | None if access.is_some() => {
+                exit_with_error("--access requires a puzzle --location")
+            }
+            _ => {}
+        }
         let save_file = matches.opt_str("save_file").map(PathBuf::from);
+        let record = matches.opt_str("record").map(PathBuf::from);
+        let replay = matches.opt_str("replay").map(PathBuf::from);
+        if replay.is_some() && (record.is_some() || location.is_some()) {
+            exit_with_error::<(), _>(
+                "--replay can't be combined with --record or --location",
+            );
+        }
         let window_size = matches.opt_str("window_size").and_then(|value| {
             match &value as &str {
                 "full" => Some((576, 384)),
@@ -82,7 +136,36 @@ impl Flags {
                 }
             }
         });
-        Flags { fullscreen, save_file, window_size }
+        let command = matches.free;
+        Flags {
+            command,
+            fullscreen,
+            location,
+            access,
+            save_file,
+            window_size,
+            record,
+            replay,
+        }
+    }
+
+    fn start_mode(&self, save_data: &mut SaveData) -> Mode {
+        match self.location {
+            Some(location) => {
+                if let Some(access) = self.access {
+                    cli::set_access(save_data.game_mut(), location, access);
+                }
+                Mode::Location(location)
+            }
+            None => Mode::Title,
+        }
+    }
+
+    fn start_location(mode: &Mode) -> Option<Location> {
+        match *mode {
+            Mode::Location(location) => Some(location),
+            _ => None,
+        }
     }
 
     fn ideal_size(&self) -> (u32, u32) {
@@ -105,11 +188,47 @@ impl Flags {
     }
 }
 
+fn exit_with_error<T, S: std::fmt::Display>(message: S) -> T {
+    println!("Error: {}", message);
+    println!("Run with --help to see available flags.");
+    std::process::exit(1);
+}
+
 // ========================================================================= //
 
 fn main() {
     let flags = Flags::parse_or_exit();
-    let mut save_data = SaveData::load_or_create(flags.save_file()).unwrap();
+    let loaded = if cli::ignores_save_file(&flags.command) {
+        Ok(SaveData::new(flags.save_file()))
+    } else if cli::is_read_only(&flags.command) {
+        SaveData::load_existing(flags.save_file())
+    } else {
+        SaveData::load_or_create(flags.save_file())
+    };
+    let mut save_data = match loaded {
+        Ok(save_data) => save_data,
+        Err(error) => {
+            println!("Failed to load or create save file: {}", error);
+            std::process::exit(1);
+        }
+    };
+    if !flags.command.is_empty() {
+        match cli::run_command(&flags.command, &mut save_data) {
+            Ok(false) => {}
+            Ok(true) => {
+                if let Err(error) = save_data.save_to_disk() {
+                    println!("Failed to save game: {}", error);
+                    std::process::exit(1);
+                }
+            }
+            Err(message) => {
+                println!("Error: {}", message);
+                println!("Run with --help to see available commands.");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     let sdl_context = sdl2::init().unwrap();
     let event_subsystem = sdl_context.event().unwrap();
     let timer_subsystem = sdl_context.timer().unwrap();
@@ -120,6 +239,7 @@ fn main() {
         flags.ideal_size(),
         flags.force_ideal(),
         flags.fullscreen(save_data.prefs()),
+        save_data.prefs().scale_mode(),
     );
     let _timer = {
         Event::register_clock_ticks(&event_subsystem);
@@ -129,156 +249,60 @@ fn main() {
         });
         timer_subsystem.add_timer(FRAME_DELAY_MILLIS, callback)
     };
-    let mut mode = Mode::Title;
+    let mut mode = if let Some(ref path) = flags.replay {
+        let recording = Recording::load(path).unwrap_or_else(|error| {
+            exit_with_error(format!("Failed to load recording: {}", error))
+        });
+        // Don't let the replay clobber the real save file.
+        let path = std::env::temp_dir().join("syzygy-replay/save.toml");
+        save_data = SaveData::from_snapshot(path, recording.save);
+        random::set_seed(recording.seed);
+        window.replay_events(recording.events);
+        recording.start.map(Mode::Location).unwrap_or(Mode::Title)
+    } else {
+        flags.start_mode(&mut save_data)
+    };
+    window.set_scale_mode(save_data.prefs().scale_mode());
+    if !flags.force_ideal() {
+        window.set_window_scale(save_data.prefs().window_scale());
+    }
+    window.set_sound_gain(save_data.prefs().sound_gain());
+    window.set_color_blind_mode(save_data.prefs().color_blind());
+    window.set_scene_pacing(save_data.prefs().scene_pacing());
+    window.set_speech_output(save_data.prefs().speech_output());
+    window.set_language(save_data.prefs().language());
+    if let Some(ref path) = flags.record {
+        let seed = random::new_seed();
+        let start = Flags::start_location(&mode);
+        let recorder =
+            Recorder::create(path, seed, start, save_data.snapshot())
+                .unwrap_or_else(|error| {
+                    exit_with_error(format!(
+                        "Failed to create recording: {}",
+                        error
+                    ))
+                });
+        random::set_seed(seed);
+        let mut recorder = Some(recorder);
+        window.observe_events(Box::new(move |event| {
+            if let Some(Err(error)) =
+                recorder.as_mut().map(|recorder| recorder.record(event))
+            {
+                println!("Failed to record event: {}", error);
+                recorder = None;
+            }
+        }));
+    }
     loop {
         mode = match mode {
             Mode::Title => {
                 modes::run_title_screen(&mut window, &mut save_data)
             }
             Mode::Location(loc) => {
-                save_data.game_mut().location = loc;
-                match loc {
-                    Location::Map => {
-                        modes::run_map_screen(&mut window, &mut save_data)
-                    }
-                    Location::Prolog => {
-                        modes::run_prolog(&mut window, &mut save_data)
-                    }
-                    Location::ALightInTheAttic => {
-                        modes::run_a_light_in_the_attic(
-                            &mut window,
-                            &mut save_data,
-                        )
-                    }
-                    Location::AutofacTour => {
-                        modes::run_autofac_tour(&mut window, &mut save_data)
-                    }
-                    Location::BlackAndBlue => {
-                        modes::run_black_and_blue(&mut window, &mut save_data)
-                    }
-                    Location::ColumnAsIcyEm => modes::run_column_as_icy_em(
-                        &mut window,
-                        &mut save_data,
-                    ),
-                    Location::ConnectTheDots => modes::run_connect_the_dots(
-                        &mut window,
-                        &mut save_data,
-                    ),
-                    Location::CrossSauce => {
-                        modes::run_cross_sauce(&mut window, &mut save_data)
-                    }
-                    Location::CrossTheLine => {
-                        modes::run_cross_the_line(&mut window, &mut save_data)
-                    }
-                    Location::CubeTangle => {
-                        modes::run_cube_tangle(&mut window, &mut save_data)
-                    }
-                    Location::Disconnected => {
-                        modes::run_disconnected(&mut window, &mut save_data)
-                    }
-                    Location::DoubleCross => {
-                        modes::run_double_cross(&mut window, &mut save_data)
-                    }
-                    Location::FactOrFiction => {
-                        modes::run_fact_or_fiction(&mut window, &mut save_data)
-                    }
-                    Location::HexSpangled => {
-                        modes::run_hex_spangled(&mut window, &mut save_data)
-                    }
-                    Location::IceToMeetYou => {
-                        modes::run_ice_to_meet_you(&mut window, &mut save_data)
-                    }
-                    Location::IfMemoryServes => modes::run_if_memory_serves(
-                        &mut window,
-                        &mut save_data,
-                    ),
-                    Location::JogYourMemory => {
-                        modes::run_jog_your_memory(&mut window, &mut save_data)
-                    }
-                    Location::LevelHeaded => {
-                        modes::run_level_headed(&mut window, &mut save_data)
-                    }
-                    Location::LevelUp => {
-                        modes::run_level_up(&mut window, &mut save_data)
-                    }
-                    Location::LightSyrup => {
-                        modes::run_light_syrup(&mut window, &mut save_data)
-                    }
-                    Location::LogLevel => {
-                        modes::run_log_level(&mut window, &mut save_data)
-                    }
-                    Location::MemoryLane => {
-                        modes::run_memory_lane(&mut window, &mut save_data)
-                    }
-                    Location::MissedConnections => {
-                        modes::run_missed_connections(
-                            &mut window,
-                            &mut save_data,
-                        )
-                    }
-                    Location::PasswordFile => {
-                        modes::run_password_file(&mut window, &mut save_data)
-                    }
-                    Location::PlaneAndSimple => modes::run_plane_and_simple(
-                        &mut window,
-                        &mut save_data,
-                    ),
-                    Location::PlaneAsDay => {
-                        modes::run_plane_as_day(&mut window, &mut save_data)
-                    }
-                    Location::PointOfNoReturn => {
-                        modes::run_point_of_no_return(
-                            &mut window,
-                            &mut save_data,
-                        )
-                    }
-                    Location::PointOfOrder => {
-                        modes::run_point_of_order(&mut window, &mut save_data)
-                    }
-                    Location::PointOfView => {
-                        modes::run_point_of_view(&mut window, &mut save_data)
-                    }
-                    Location::ShiftGears => {
-                        modes::run_shift_gears(&mut window, &mut save_data)
-                    }
-                    Location::ShiftTheBlame => {
-                        modes::run_shift_the_blame(&mut window, &mut save_data)
-                    }
-                    Location::ShiftingGround => {
-                        modes::run_shifting_ground(&mut window, &mut save_data)
-                    }
-                    Location::StarCrossed => {
-                        modes::run_star_crossed(&mut window, &mut save_data)
-                    }
-                    Location::SystemFailure => {
-                        modes::run_system_failure(&mut window, &mut save_data)
-                    }
-                    Location::SystemSyzygy => {
-                        modes::run_system_syzygy(&mut window, &mut save_data)
-                    }
-                    Location::TheIceIsRight => modes::run_the_ice_is_right(
-                        &mut window,
-                        &mut save_data,
-                    ),
-                    Location::TheYFactor => {
-                        modes::run_the_y_factor(&mut window, &mut save_data)
-                    }
-                    Location::ThreeBlindIce => {
-                        modes::run_three_blind_ice(&mut window, &mut save_data)
-                    }
-                    Location::TreadLightly => {
-                        modes::run_tread_lightly(&mut window, &mut save_data)
-                    }
-                    Location::WhatchaColumn => {
-                        modes::run_whatcha_column(&mut window, &mut save_data)
-                    }
-                    Location::WreckedAngle => {
-                        modes::run_wrecked_angle(&mut window, &mut save_data)
-                    }
-                    Location::Finale => {
-                        modes::run_finale(&mut window, &mut save_data)
-                    }
-                }
+                modes::run_location(&mut window, &mut save_data, loc)
+            }
+            Mode::Settings(back) => {
+                modes::run_settings(&mut window, &mut save_data, back)
             }
             Mode::Quit => break,
         };
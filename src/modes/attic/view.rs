@@ -20,18 +20,19 @@
 use std::cmp;
 
 use super::scenes;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
 use crate::gui::{
     Action, Canvas, Element, Event, Point, Rect, Resources, Sound, Sprite,
 };
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{AtticState, Game, PuzzleState};
+use crate::save::{AtticState, Game, Location, PuzzleState};
 
 // ========================================================================= //
 
 pub struct View {
     core: PuzzleCore<(i32, i32)>,
     grid: AtticGrid,
+    hinted: Option<(i32, i32)>,
 }
 
 impl View {
@@ -46,9 +47,26 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_argony_midscene(resources));
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
-        View { core, grid: AtticGrid::new(resources, state) }
+        View { core, grid: AtticGrid::new(resources, state), hinted: None }
+    }
+
+    fn toggle(&mut self, state: &mut AtticState, position: (i32, i32)) {
+        self.clear_hint();
+        state.toggle(position);
+        if state.is_solved() {
+            self.core.begin_outro_scene();
+        } else {
+            self.core.push_undo(position);
+        }
+    }
+
+    fn clear_hint(&mut self) {
+        if let Some(position) = self.hinted.take() {
+            self.grid.set_hilight(position, false);
+        }
     }
 }
 
@@ -72,12 +90,9 @@ impl Element<Game, PuzzleCmd> for View {
             let subaction = self.grid.handle_event(event, state);
             if let Some(&position) = subaction.value() {
                 action.also_play_sound(Sound::device_rotate());
-                state.toggle(position);
+                self.toggle(state, position);
                 if state.is_solved() {
-                    self.core.begin_outro_scene();
                     action = action.and_return(PuzzleCmd::Save);
-                } else {
-                    self.core.push_undo(position);
                 }
             }
             action.merge(subaction.but_no_value());
@@ -99,27 +114,58 @@ impl PuzzleView for View {
     }
 
     fn undo(&mut self, game: &mut Game) {
+        self.clear_hint();
         if let Some(position) = self.core.pop_undo() {
             game.a_light_in_the_attic.toggle(position);
         }
     }
 
     fn redo(&mut self, game: &mut Game) {
+        self.clear_hint();
         if let Some(position) = self.core.pop_redo() {
             game.a_light_in_the_attic.toggle(position);
         }
     }
 
     fn reset(&mut self, game: &mut Game) {
+        self.clear_hint();
         self.core.clear_undo_redo();
         game.a_light_in_the_attic.reset();
     }
 
     fn solve(&mut self, game: &mut Game) {
+        self.clear_hint();
         game.a_light_in_the_attic.solve();
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        Hint::escalate(
+            game.hints_used(Location::ALightInTheAttic),
+            HINT_TEXT,
+            game.a_light_in_the_attic.next_toggle().is_some(),
+        )
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.clear_hint();
+        if let Some(position) = game.a_light_in_the_attic.next_toggle() {
+            self.grid.set_hilight(position, true);
+            self.hinted = Some(position);
+        }
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.a_light_in_the_attic;
+        if let Some(position) = state.next_toggle() {
+            self.toggle(state, position);
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (index, enable) in self.core.drain_queue() {
             self.grid.toggles[index as usize].set_hilight(enable != 0);
@@ -180,6 +226,11 @@ impl AtticGrid {
         }
     }
 
+    fn set_hilight(&mut self, (col, row): (i32, i32), hilight: bool) {
+        let index = (row - 1) * 4 + (col - 1);
+        self.toggles[index as usize].set_hilight(hilight);
+    }
+
     pub fn do_not_show_corner_lights(&mut self) {
         for toggle in self.toggles.iter_mut() {
             toggle.frame_on = toggle.frame_off.clone();
@@ -416,4 +467,86 @@ lights will be toggled by $M{tapp}{click}ing on that light.
 $M{Tap}{Click} on a character in the scene to hear their
 words of wisdom.";
 
+const HINT_TEXT: &str = "\
+The order in which you toggle the lights doesn't
+matter, and toggling the same light twice is the
+same as not toggling it at all.
+
+So each lettered light is either part of the
+solution or it isn't.  Try working out one
+light at a time from the edges inward.";
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{View, LIGHTS_LEFT, LIGHTS_TOP};
+    use crate::gui::{Event, EventScript, KeyMod, Keycode, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Access, Game, Location, PuzzleState};
+
+    fn click(col: i32, row: i32) -> Vec<Event> {
+        let x = LIGHTS_LEFT + 32 * col + 16;
+        let y = LIGHTS_TOP + 32 * row + 16;
+        vec![Event::MouseDown(Point::new(x, y)), Event::MouseUp]
+    }
+
+    #[test]
+    fn clicking_solution_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::ALightInTheAttic;
+        game.a_light_in_the_attic.visit();
+        let mut view = View::new(
+            &mut window.resources(),
+            visible,
+            &game.a_light_in_the_attic,
+        );
+        // Let the screen fade in before clicking anything.
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        for &(col, row) in &[(1, 1), (4, 1), (1, 2), (2, 3), (3, 3), (2, 4)] {
+            events.extend(click(col, row));
+        }
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.a_light_in_the_attic.access(), Access::Unsolved);
+
+        events.extend(click(4, 4));
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.a_light_in_the_attic.access(), Access::Solved);
+    }
+
+    #[test]
+    fn hints_escalate_to_solution() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::ALightInTheAttic;
+        game.a_light_in_the_attic.visit();
+        let mut view = View::new(
+            &mut window.resources(),
+            visible,
+            &game.a_light_in_the_attic,
+        );
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        events.extend(click(2, 1));
+        // The first hint is just a tip, and the second only highlights the
+        // next toggle.
+        let hint = Event::KeyDown(Keycode::F3, KeyMod::none());
+        events.extend(vec![hint.clone(), hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.hints_used(Location::ALightInTheAttic), 2);
+        assert_eq!(view.hinted, Some((1, 1)));
+        assert!(!game.a_light_in_the_attic.is_toggled((1, 1)));
+
+        // Later hints make moves, until the puzzle is solved.
+        for _ in 0..8 {
+            events.extend(vec![hint.clone(), Event::ClockTick]);
+        }
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.a_light_in_the_attic.access(), Access::Solved);
+        assert_eq!(view.hinted, None);
+    }
+}
+
 // ========================================================================= //
@@ -562,3 +562,49 @@ $M{Tap}{Click} on a character in the scene to hear their
 words of wisdom.";
 
 // ========================================================================= //
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    /// A shortest sequence of `(row, delta)` arrow presses that gets Mezure
+    /// up to the ledge, found by breadth-first search over `shift_platform`.
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const SOLUTION: &[(i32, i32)] = &[
+        (0, -3), (0, 3), (0, 3), (0, -3), (0, -3), (0, -3), (3, -7), (4, 5),
+        (5, -3), (5, 3), (5, 3), (5, -3), (4, -5), (4, 5), (2, -3), (1, 5),
+        (0, 3), (0, 3),
+    ];
+
+    fn click_arrow((row, delta): (i32, i32)) -> Vec<Event> {
+        let x = if delta < 0 { 64 + 12 } else { 64 + 36 };
+        let pt = Point::new(x, 112 + 32 * row + 8);
+        let mut events = vec![Event::MouseDown(pt), Event::MouseUp];
+        // Let Mezure finish jumping around before the next press.
+        events.extend(vec![Event::ClockTick; 150]);
+        events
+    }
+
+    #[test]
+    fn clicking_arrows_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::ShiftTheBlame;
+        game.shift_the_blame.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.shift_the_blame);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        for &press in SOLUTION.iter() {
+            assert!(!game.shift_the_blame.is_solved());
+            events.extend(click_arrow(press));
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        }
+        assert!(game.shift_the_blame.is_solved());
+    }
+}
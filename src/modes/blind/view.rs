@@ -19,11 +19,13 @@
 
 use super::scenes;
 use crate::elements::ice::GridView;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
-use crate::gui::{Action, Canvas, Element, Event, Rect, Resources, Sound};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::gui::{
+    Action, Canvas, Element, Event, Point, Rect, Resources, Sound,
+};
 use crate::modes::SOLVED_INFO_TEXT;
 use crate::save::ice::BlockSlide;
-use crate::save::{BlindState, Game, PuzzleState};
+use crate::save::{BlindState, Direction, Game, Location, PuzzleState};
 
 // ========================================================================= //
 
@@ -44,11 +46,31 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_elinsa_midscene(resources));
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
         core.add_extra_scene(scenes::compile_ugrent_midscene(resources));
         View { core, grid: GridView::new(resources, 80, 64, state.grid()) }
     }
+
+    fn slide_ice_block(
+        &mut self,
+        state: &mut BlindState,
+        coords: Point,
+        dir: Direction,
+    ) -> bool {
+        if let Some(slide) = state.slide_ice_block(coords, dir) {
+            self.grid.animate_slide(&slide);
+            if state.is_solved() {
+                self.core.begin_outro_scene();
+            } else {
+                self.core.push_undo(slide);
+            }
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl Element<Game, PuzzleCmd> for View {
@@ -72,14 +94,10 @@ impl Element<Game, PuzzleCmd> for View {
         {
             let subaction = self.grid.handle_event(event, state.grid_mut());
             if let Some(&(coords, dir)) = subaction.value() {
-                if let Some(slide) = state.slide_ice_block(coords, dir) {
+                if self.slide_ice_block(state, coords, dir) {
                     action.also_play_sound(Sound::device_slide());
-                    self.grid.animate_slide(&slide);
                     if state.is_solved() {
-                        self.core.begin_outro_scene();
                         action = action.and_return(PuzzleCmd::Save);
-                    } else {
-                        self.core.push_undo(slide);
                     }
                 }
             }
@@ -127,6 +145,26 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        let hints_used = game.hints_used(Location::ThreeBlindIce);
+        self.grid.hint(game.three_blind_ice.grid(), hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.grid.highlight_hint(game.three_blind_ice.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.three_blind_ice;
+        if let Some((coords, dir)) = self.grid.next_move(state.grid()) {
+            self.slide_ice_block(state, coords, dir);
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 1 {
@@ -175,4 +213,10 @@ $M{your finger}{the mouse} to slide it in that direction.
 $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
+const HINT_TEXT: &str = "\
+The rotator turns a block and the reflector flips
+it, so plan the route each block takes as well as
+where it ends up.  Decide where the push-pops need
+to be before moving blocks past them.";
+
 // ========================================================================= //
@@ -19,10 +19,10 @@
 
 use super::scenes;
 use crate::elements::lasers::{DangerSign, LaserCmd, LaserField};
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
 use crate::gui::{Action, Canvas, Element, Event, Rect, Resources};
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{DisconState, Game, PuzzleState};
+use crate::save::{DisconState, Game, Location, PuzzleState};
 
 // ========================================================================= //
 
@@ -45,6 +45,7 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
         View {
             core,
@@ -91,12 +92,11 @@ impl Element<Game, PuzzleCmd> for View {
             let subaction =
                 self.laser_field.handle_event(event, state.grid_mut());
             if let Some(&cmd) = subaction.value() {
-                if self.laser_field.all_detectors_satisfied(state.grid()) {
-                    state.mark_solved();
-                    self.core.begin_outro_scene();
+                if self
+                    .laser_field
+                    .finish_cmd(&mut self.core, cmd, || state.mark_solved())
+                {
                     action = action.and_return(PuzzleCmd::Save);
-                } else {
-                    self.core.push_undo(cmd);
                 }
             }
             action.merge(subaction.but_no_value());
@@ -161,6 +161,31 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        if !self.box_open {
+            return Hint::Tip(HINT_TEXT);
+        }
+        let hints_used = game.hints_used(Location::Disconnected);
+        let grid = game.disconnected.grid();
+        self.laser_field.hint(grid, hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.laser_field.highlight_hint(game.disconnected.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.disconnected;
+        if let Some(cmd) = self.laser_field.apply_hint(state.grid_mut()) {
+            self.laser_field
+                .finish_cmd(&mut self.core, cmd, || state.mark_solved());
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 0 {
@@ -202,4 +227,70 @@ the grid.  $M{Tap}{Click} mirrors to rotate them.
 $M{Tap}{Click} on a character in the scene to hear their words of
 wisdom.";
 
+const HINT_TEXT: &str = "\
+Each mirror turns a laser by a right angle, and a laser
+passes straight through an empty cell.  Start from each
+detector and work backwards towards a laser of the
+same color.";
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::elements::lasers::LaserCmd;
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    fn cell_center(col: i32, row: i32) -> Point {
+        Point::new(120 + 32 * col + 16, 72 + 32 * row + 16)
+    }
+
+    #[test]
+    fn dragging_and_clicking_mirrors_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::Disconnected;
+        game.disconnected.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.disconnected);
+        view.box_open = true;
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        // Asking for a move starts the search for a solution.
+        let grid = game.disconnected.grid();
+        assert!(view.laser_field.next_move(grid).is_none());
+        view.laser_field.finish_search();
+
+        // Make each move towards the solution with the mouse, as a player
+        // would, rather than with hints.
+        for _ in 0..30 {
+            if game.disconnected.is_solved() {
+                break;
+            }
+            let grid = game.disconnected.grid();
+            match view.laser_field.next_move(grid).unwrap() {
+                LaserCmd::Moved(col1, row1, col2, row2) => {
+                    events.extend(vec![
+                        Event::MouseDown(cell_center(col1, row1)),
+                        Event::MouseDrag(cell_center(col2, row2)),
+                        Event::MouseUp,
+                    ]);
+                }
+                LaserCmd::Rotated(col, row) => {
+                    let center = cell_center(col, row);
+                    events.extend(vec![
+                        Event::MouseDown(center),
+                        Event::MouseUp,
+                    ]);
+                }
+            }
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        }
+        assert!(game.disconnected.is_solved());
+    }
+}
+
 // ========================================================================= //
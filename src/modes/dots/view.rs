@@ -19,10 +19,10 @@
 
 use super::scenes;
 use crate::elements::lasers::{DangerSign, LaserCmd, LaserField};
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
 use crate::gui::{Action, Canvas, Element, Event, Rect, Resources};
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{DotsState, Game, PuzzleState};
+use crate::save::{DotsState, Game, Location, PuzzleState};
 
 // ========================================================================= //
 
@@ -45,6 +45,7 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
         View {
             core,
@@ -87,12 +88,11 @@ impl Element<Game, PuzzleCmd> for View {
             let subaction =
                 self.laser_field.handle_event(event, state.grid_mut());
             if let Some(&cmd) = subaction.value() {
-                if self.laser_field.all_detectors_satisfied(state.grid()) {
-                    state.mark_solved();
-                    self.core.begin_outro_scene();
+                if self
+                    .laser_field
+                    .finish_cmd(&mut self.core, cmd, || state.mark_solved())
+                {
                     action = action.and_return(PuzzleCmd::Save);
-                } else {
-                    self.core.push_undo(cmd);
                 }
             }
             action.merge(subaction.but_no_value());
@@ -157,6 +157,31 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        if !self.box_open {
+            return Hint::Tip(HINT_TEXT);
+        }
+        let hints_used = game.hints_used(Location::ConnectTheDots);
+        let grid = game.connect_the_dots.grid();
+        self.laser_field.hint(grid, hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.laser_field.highlight_hint(game.connect_the_dots.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.connect_the_dots;
+        if let Some(cmd) = self.laser_field.apply_hint(state.grid_mut()) {
+            self.laser_field
+                .finish_cmd(&mut self.core, cmd, || state.mark_solved());
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 0 {
@@ -195,4 +220,62 @@ them.
 $M{Tap}{Click} on a character in the scene to hear their words of
 wisdom.";
 
+const HINT_TEXT: &str = "\
+A splitter sends a laser both ways, and a mixer joins
+two colors into one.  Work out which detectors need a
+mixed color, and which lasers have to be split to
+reach more than one detector.";
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::elements::lasers::LaserCmd;
+    use crate::gui::{Event, EventScript, KeyMod, Keycode, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    #[test]
+    fn hints_wait_for_the_search_then_make_a_move() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::ConnectTheDots;
+        game.connect_the_dots.visit();
+        let mut view = View::new(
+            &mut window.resources(),
+            visible,
+            &game.connect_the_dots,
+        );
+        view.box_open = true;
+        let initial = game.connect_the_dots.grid().clone();
+
+        // The first hint is just a tip, and starts the search in the
+        // background; until that finishes, hints stay as tips.
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let hint = Event::KeyDown(Keycode::F3, KeyMod::none());
+        events.extend(vec![hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        view.laser_field.finish_search();
+        let cmd = view.laser_field.next_move(&initial).unwrap();
+
+        // The second hint only highlights the move, and the third makes it.
+        events.extend(vec![hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.hints_used(Location::ConnectTheDots), 2);
+        let grid = game.connect_the_dots.grid();
+        assert_eq!(grid.to_toml(), initial.to_toml());
+        events.extend(vec![hint, Event::ClockTick]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        let mut expected = initial;
+        match cmd {
+            LaserCmd::Moved(col1, row1, col2, row2) => {
+                assert!(expected.move_to(col1, row1, col2, row2));
+            }
+            LaserCmd::Rotated(col, row) => expected.rotate(col, row),
+        }
+        let grid = game.connect_the_dots.grid();
+        assert_eq!(grid.to_toml(), expected.to_toml());
+    }
+}
+
 // ========================================================================= //
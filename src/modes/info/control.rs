@@ -29,19 +29,39 @@ pub fn run_info_box<S, A, E: Element<S, A>>(
     original_input: &mut S,
     text: &str,
 ) -> bool {
+    let buttons = vec![("OK".to_string(), ())];
+    run_choice_box(window, original_view, original_input, text, buttons)
+        .is_some()
+}
+
+/// Like `run_info_box`, but with a choice of buttons.  Returns the value of
+/// the button that was clicked, or `None` if the user quit the game instead.
+pub fn run_choice_box<S, A, E: Element<S, A>, T: 'static + Clone>(
+    window: &mut Window,
+    original_view: &E,
+    original_input: &mut S,
+    text: &str,
+    buttons: Vec<(String, T)>,
+) -> Option<T> {
     let mut view = {
         let visible = window.visible_rect();
-        View::new(&mut window.resources(), visible, original_view, text)
+        View::new(
+            &mut window.resources(),
+            visible,
+            original_view,
+            text,
+            buttons,
+        )
     };
     window.render(original_input, &view);
     loop {
         let mut action = match window.next_event() {
-            Event::Quit => return false,
+            Event::Quit => return None,
             event => view.handle_event(&event, original_input),
         };
         window.play_sounds(action.drain_sounds());
-        if action.value().is_some() {
-            return true;
+        if let Some(value) = action.value() {
+            return Some(value.clone());
         } else if action.should_redraw() {
             window.render(original_input, &view);
         }
@@ -20,7 +20,7 @@
 mod control;
 mod view;
 
-pub use self::control::run_info_box;
+pub use self::control::{run_choice_box, run_info_box};
 pub use self::view::SOLVED_INFO_TEXT;
 
 // ========================================================================= //
@@ -24,32 +24,36 @@ use crate::gui::{Action, Canvas, Element, Event, Rect, Resources};
 
 // ========================================================================= //
 
-pub struct View<'a, A, E: 'a> {
+pub struct View<'a, A, E: 'a, T> {
     original_view: &'a E,
-    dialog: DialogBox<()>,
+    dialog: DialogBox<T>,
     phantom: PhantomData<A>,
 }
 
-impl<'a, A, E> View<'a, A, E> {
+impl<'a, A, E, T: 'static + Clone> View<'a, A, E, T> {
     pub fn new(
         resources: &mut Resources,
         visible: Rect,
         original_view: &'a E,
         text: &str,
-    ) -> View<'a, A, E> {
-        let buttons = vec![("OK".to_string(), ())];
-        let dialog = DialogBox::new(resources, visible, text, buttons);
+        buttons: Vec<(String, T)>,
+    ) -> View<'a, A, E, T> {
+        let text = resources.translate(text).to_string();
+        resources.announcer().announce(&text);
+        let dialog = DialogBox::new(resources, visible, &text, buttons);
         View { original_view, dialog, phantom: PhantomData }
     }
 }
 
-impl<'a, S, A, E: Element<S, A>> Element<S, ()> for View<'a, A, E> {
+impl<'a, S, A, E: Element<S, A>, T: 'static + Clone> Element<S, T>
+    for View<'a, A, E, T>
+{
     fn draw(&self, state: &S, canvas: &mut Canvas) {
         self.original_view.draw(state, canvas);
         self.dialog.draw(&(), canvas);
     }
 
-    fn handle_event(&mut self, event: &Event, _: &mut S) -> Action<()> {
+    fn handle_event(&mut self, event: &Event, _: &mut S) -> Action<T> {
         self.dialog.handle_event(event, &mut ())
     }
 }
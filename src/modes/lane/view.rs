@@ -312,3 +312,100 @@ symbol,  you will have to start over.
 $M{Tap}{Click} on a character in the scene to hear their words of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::util::Tomlable;
+    use crate::save::{Game, LaneState, Location, PuzzleState};
+
+    /// Enough clock ticks for tiles to finish flipping, or for a removal to
+    /// finish.
+    const SETTLE_TICKS: usize = 100;
+
+    /// Returns where to put the top-left corner of each remaining shape (or
+    /// `None` for stages that remove a symbol) so that every shape fits when
+    /// its turn comes, found by a depth-first search.
+    fn plan(state: &LaneState) -> Option<Vec<Option<(i32, i32)>>> {
+        if state.is_solved() {
+            return Some(Vec::new());
+        }
+        let copy = || LaneState::from_toml(state.to_toml());
+        let mut choices = Vec::new();
+        if let Some(symbol) = state.next_remove() {
+            let mut next = copy();
+            next.decay_symbol_all(symbol);
+            next.remove_symbol(symbol);
+            choices.push((None, next));
+        } else {
+            let grid = state.grid();
+            for row in -2..grid.num_rows() {
+                for col in -2..grid.num_cols() {
+                    let mut next = copy();
+                    if next.try_place_shape(col, row).is_some() {
+                        choices.push((Some((col, row)), next));
+                    }
+                }
+            }
+        }
+        for (choice, next) in choices {
+            if let Some(mut rest) = plan(&next) {
+                rest.insert(0, choice);
+                return Some(rest);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn placing_and_removing_tiles_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::MemoryLane;
+        game.memory_lane.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.memory_lane);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let steps = plan(&game.memory_lane).unwrap();
+        assert_eq!(steps.len(), game.memory_lane.total_num_stages());
+        for (stage, step) in steps.into_iter().enumerate() {
+            let state = &game.memory_lane;
+            assert_eq!(state.current_stage(), stage);
+            if let Some((col, row)) = step {
+                // Drag the shape by one of its tiles from the box on the left
+                // onto the grid.
+                let shape = state.next_shape().unwrap();
+                let ((dx, dy), _) = shape.tiles().next().unwrap();
+                let from = Point::new(96 + 32 * dx + 16, 64 + 32 * dy + 16);
+                let to = from.offset(112 + 32 * col, 32 * row);
+                events.extend(vec![
+                    Event::MouseDown(from),
+                    Event::MouseDrag(to),
+                    Event::MouseUp,
+                ]);
+            } else {
+                // Click any tile with the symbol to be removed.
+                let symbol = state.next_remove().unwrap();
+                let grid = state.grid();
+                let (col, row) = (0..grid.num_rows())
+                    .flat_map(|row| {
+                        (0..grid.num_cols()).map(move |col| (col, row))
+                    })
+                    .find(|&(col, row)| {
+                        grid.symbol_at(col, row) == Some(symbol)
+                    })
+                    .unwrap();
+                let pt = Point::new(208 + 32 * col + 16, 64 + 32 * row + 16);
+                events.extend(vec![Event::MouseDown(pt), Event::MouseUp]);
+            }
+            events.extend(vec![Event::ClockTick; SETTLE_TICKS]);
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        }
+        assert!(game.memory_lane.is_solved());
+    }
+}
+
+// ========================================================================= //
@@ -18,10 +18,20 @@
 // +--------------------------------------------------------------------------+
 
 use crate::gui::{Element, Event, Window};
-use crate::modes::{run_info_box, Mode};
-use crate::save::SaveData;
+use crate::modes::{run_choice_box, Mode};
+use crate::save::random::{new_seed, Difficulty, Scramble};
+use crate::save::{Game, Location, SaveData};
 
-use super::view::{Cmd, View, INFO_BOX_TEXT};
+use super::view::{Cmd, View, INFO_BOX_TEXT, PRACTICE_BOX_TEXT};
+
+// ========================================================================= //
+
+#[derive(Clone, Copy)]
+enum InfoChoice {
+    Settings,
+    Practice,
+    Ok,
+}
 
 // ========================================================================= //
 
@@ -43,8 +53,52 @@ pub fn run_map_screen(window: &mut Window, save_data: &mut SaveData) -> Mode {
                 return Mode::Title;
             }
             Some(&Cmd::ShowInfoBox) => {
-                if !run_info_box(window, &view, game, INFO_BOX_TEXT) {
-                    return Mode::Quit;
+                let mut buttons =
+                    vec![("Settings".to_string(), InfoChoice::Settings)];
+                if game.has_been_solved(Location::Finale) {
+                    buttons
+                        .push(("Practice".to_string(), InfoChoice::Practice));
+                }
+                buttons.push(("OK".to_string(), InfoChoice::Ok));
+                match run_choice_box(
+                    window,
+                    &view,
+                    game,
+                    INFO_BOX_TEXT,
+                    buttons,
+                ) {
+                    Some(InfoChoice::Settings) => {
+                        return Mode::Settings(Some(Location::Map))
+                    }
+                    Some(InfoChoice::Practice) => {
+                        let mut buttons: Vec<(String, Option<Difficulty>)> =
+                            Difficulty::all()
+                                .iter()
+                                .map(|&diff| {
+                                    (diff.name().to_string(), Some(diff))
+                                })
+                                .collect();
+                        // Cancel goes last, as the default button that the
+                        // Escape key chooses.
+                        buttons.push(("Cancel".to_string(), None));
+                        match run_choice_box(
+                            window,
+                            &view,
+                            game,
+                            PRACTICE_BOX_TEXT,
+                            buttons,
+                        ) {
+                            Some(Some(difficulty)) => {
+                                return Mode::Location(deal_practice(
+                                    game, difficulty,
+                                ));
+                            }
+                            Some(None) => {}
+                            None => return Mode::Quit,
+                        }
+                    }
+                    Some(InfoChoice::Ok) => {}
+                    None => return Mode::Quit,
                 }
             }
             Some(&Cmd::GoToPuzzle(loc)) => {
@@ -58,4 +112,69 @@ pub fn run_map_screen(window: &mut Window, save_data: &mut SaveData) -> Mode {
     }
 }
 
+/// Deals a fresh instance of one of the puzzles that can be scrambled,
+/// preferring one that the player isn't partway through replaying, and
+/// returns its location.
+fn deal_practice(game: &mut Game, difficulty: Difficulty) -> Location {
+    let seed = new_seed();
+    let mut candidates: Vec<Location> = Game::scramble_locations()
+        .iter()
+        .copied()
+        .filter(|&loc| !game.is_in_progress(loc))
+        .collect();
+    if candidates.is_empty() {
+        candidates = Game::scramble_locations().to_vec();
+    }
+    let location = candidates[seed as usize % candidates.len()];
+    game.deal_scramble(location, Scramble::new(seed, difficulty));
+    location
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::run_map_screen;
+    use crate::gui::{Event, KeyMod, Keycode, Window};
+    use crate::modes::Mode;
+    use crate::save::{Access, Game, Location, SaveData};
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown(keycode, KeyMod::none())
+    }
+
+    #[test]
+    fn practice_deals_a_scrambled_puzzle_once_finished() {
+        let mut window = Window::headless((576, 384));
+        let mut data = SaveData::new(PathBuf::new());
+        let game = data.game_mut();
+        for &location in Location::all() {
+            if location != Location::Map {
+                *game.puzzle_state_mut(location).access_mut() = Access::Solved;
+            }
+        }
+        *game = Game::from_toml(game.to_toml());
+        // Open the info box, choose Practice, and then choose Hard.
+        window.queue_events(vec![Event::ClockTick; 100]);
+        window.queue_events(vec![
+            key(Keycode::F1),
+            key(Keycode::Left),
+            key(Keycode::Return),
+            key(Keycode::Left),
+            key(Keycode::Return),
+        ]);
+        window.queue_events(vec![Event::ClockTick; 100]);
+        let location = match run_map_screen(&mut window, &mut data) {
+            Mode::Location(location) => location,
+            _ => panic!("practice didn't go to a puzzle"),
+        };
+        assert!(Game::scramble_locations().contains(&location));
+        let state = data.game().unwrap().puzzle_state(location);
+        assert!(state.is_scrambled());
+        assert_eq!(state.access(), Access::BeginReplay);
+    }
+}
+
 // ========================================================================= //
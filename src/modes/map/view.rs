@@ -21,16 +21,20 @@ use std::cmp::min;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::elements::{FadeStyle, Hud, HudCmd, HudInput, ScreenFade};
+use crate::elements::{
+    nearest_in_direction, FadeStyle, Hud, HudCmd, HudInput, ScreenFade,
+};
 use crate::gui::{
-    Action, Background, Canvas, Element, Event, Point, Rect, Resources, Sprite,
+    Action, Background, Canvas, Element, Event, Keycode, Point, Rect,
+    Resources, Sprite,
 };
-use crate::save::{Access, Game, Location};
+use crate::save::{Access, Direction, Game, Location};
 
 // ========================================================================= //
 
 const NODE_WIDTH: u32 = 28;
 const NODE_HEIGHT: u32 = 28;
+const FOCUS_RING_COLOR: (u8, u8, u8) = (255, 255, 128);
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
 const NODES: &[(Location, (i32, i32), bool)] = &[
@@ -97,6 +101,7 @@ pub struct View {
     paths_outer: Vec<Rect>,
     paths_inner: Vec<Rect>,
     selected: Option<Location>,
+    show_focus: bool,
 }
 
 impl View {
@@ -371,7 +376,30 @@ impl View {
             paths_outer,
             paths_inner,
             selected: None,
+            show_focus: false,
+        }
+    }
+
+    /// Moves the selection to the nearest unlocked node in the given
+    /// direction, or to the first node if none is selected yet.
+    fn move_focus(&mut self, dir: Direction) -> Action<Cmd> {
+        let centers: Vec<(Location, Point)> = self
+            .nodes
+            .iter()
+            .map(|node| (node.loc, node.rect.center()))
+            .collect();
+        let from = self.selected.and_then(|selected| {
+            centers.iter().find(|&&(loc, _)| loc == selected).cloned()
+        });
+        let next = match from {
+            Some((_, center)) => nearest_in_direction(&centers, center, dir),
+            None => centers.first().map(|&(loc, _)| loc),
+        };
+        if let Some(loc) = next {
+            self.selected = Some(loc);
         }
+        self.show_focus = true;
+        Action::redraw().and_stop()
     }
 
     fn hud_input(&self) -> HudInput {
@@ -384,6 +412,8 @@ impl View {
             can_undo: false,
             can_redo: false,
             can_reset: false,
+            can_hint: false,
+            has_transcript: false,
         }
     }
 }
@@ -402,6 +432,31 @@ impl Element<Game, Cmd> for View {
             canvas.fill_rect((192, 128, 0), rect);
         }
         self.nodes.draw(&self.selected, canvas);
+        if self.show_focus {
+            if let Some(node) =
+                self.nodes.iter().find(|node| self.selected == Some(node.loc))
+            {
+                let rect = node.rect;
+                canvas.draw_rect(
+                    FOCUS_RING_COLOR,
+                    Rect::new(
+                        rect.x() - 2,
+                        rect.y() - 2,
+                        rect.width() + 4,
+                        rect.height() + 4,
+                    ),
+                );
+                canvas.draw_rect(
+                    FOCUS_RING_COLOR,
+                    Rect::new(
+                        rect.x() - 3,
+                        rect.y() - 3,
+                        rect.width() + 6,
+                        rect.height() + 6,
+                    ),
+                );
+            }
+        }
         self.hud.draw(&self.hud_input(), canvas);
         self.screen_fade.draw(&(), canvas);
     }
@@ -420,6 +475,36 @@ impl Element<Game, Cmd> for View {
                 _ => subaction.but_no_value(),
             });
         }
+        if !action.should_stop() {
+            let subaction = match event {
+                &Event::KeyDown(Keycode::Up, _) => {
+                    self.move_focus(Direction::North)
+                }
+                &Event::KeyDown(Keycode::Down, _) => {
+                    self.move_focus(Direction::South)
+                }
+                &Event::KeyDown(Keycode::Left, _) => {
+                    self.move_focus(Direction::West)
+                }
+                &Event::KeyDown(Keycode::Right, _) => {
+                    self.move_focus(Direction::East)
+                }
+                &Event::KeyDown(Keycode::Return, _)
+                | &Event::KeyDown(Keycode::Space, _)
+                    if self.selected.is_some() =>
+                {
+                    let loc = self.selected.unwrap();
+                    self.screen_fade.fade_out_and_return(Cmd::GoToPuzzle(loc));
+                    Action::redraw().and_stop()
+                }
+                &Event::MouseDown(_) => {
+                    self.show_focus = false;
+                    Action::ignore()
+                }
+                _ => Action::ignore(),
+            };
+            action.merge(subaction);
+        }
         if !action.should_stop() {
             let subaction = self.nodes.handle_event(event, &mut self.selected);
             if let Some(&loc) = subaction.value() {
@@ -503,11 +588,16 @@ impl Element<Option<Location>, Location> for PuzzleNode {
 
 pub const INFO_BOX_TEXT: &str = "\
 $M{Tap}{Click} on a system node to select it; $M{tap}{click} on it again to
-travel there.
+travel there.  You can also select nodes with the arrow keys, and travel
+with Enter.
 
 Nodes that still need to be repaired are marked in red.
 Repaired nodes are marked in green.";
 
+pub const PRACTICE_BOX_TEXT: &str = "\
+Deal a freshly scrambled copy of one of the system's
+sliding and rotating puzzles.  How scrambled should it be?";
+
 // ========================================================================= //
 
 #[cfg(test)]
@@ -515,8 +605,92 @@ mod tests {
     use std::collections::{HashMap, HashSet};
 
     use super::{NODES, NODE_HEIGHT, NODE_WIDTH};
-    use crate::gui::Rect;
-    use crate::save::Location;
+    use crate::elements::nearest_in_direction;
+    use crate::gui::{Point, Rect};
+    use crate::save::{Access, Direction, Game, Location};
+
+    fn node_centers() -> Vec<(Location, Point)> {
+        NODES.iter().map(|&(loc, (x, y), _)| (loc, Point::new(x, y))).collect()
+    }
+
+    #[test]
+    fn arrow_keys_move_to_nearest_node() {
+        let centers = node_centers();
+        let prolog = Point::new(112, 144);
+        assert_eq!(
+            nearest_in_direction(&centers, prolog, Direction::East),
+            Some(Location::CrossTheLine)
+        );
+        assert_eq!(
+            nearest_in_direction(&centers, prolog, Direction::West),
+            None
+        );
+    }
+
+    /// Returns the centers of the nodes that the map shows for `game`.
+    fn unlocked_centers(game: &Game) -> Vec<(Location, Point)> {
+        node_centers()
+            .into_iter()
+            .filter(|&(loc, _)| game.is_unlocked(loc))
+            .collect()
+    }
+
+    /// Returns the number of nodes that arrow keys can reach, starting from
+    /// the first one, as `View::move_focus` does.
+    fn num_reachable(centers: &[(Location, Point)]) -> usize {
+        let mut reached: HashSet<Location> = HashSet::new();
+        let mut queue =
+            centers.first().cloned().into_iter().collect::<Vec<_>>();
+        while let Some((loc, center)) = queue.pop() {
+            if !reached.insert(loc) {
+                continue;
+            }
+            for dir in Direction::all() {
+                if let Some(next) = nearest_in_direction(centers, center, dir)
+                {
+                    let &(_, pt) =
+                        centers.iter().find(|&&(l, _)| l == next).unwrap();
+                    queue.push((next, pt));
+                }
+            }
+        }
+        reached.len()
+    }
+
+    #[test]
+    fn arrow_keys_can_reach_every_node() {
+        assert_eq!(num_reachable(&node_centers()), NODES.len());
+    }
+
+    #[test]
+    fn arrow_keys_can_reach_every_unlocked_node() {
+        // Solve puzzles one at a time, taking either the first or the last
+        // unsolved node on the map each time, and check the map at each step
+        // along the way.
+        for &take_last in &[false, true] {
+            let mut game = Game::new();
+            loop {
+                let centers = unlocked_centers(&game);
+                assert_eq!(num_reachable(&centers), centers.len());
+                let mut unsolved = centers
+                    .iter()
+                    .map(|&(loc, _)| loc)
+                    .filter(|&loc| !game.has_been_solved(loc));
+                let next = if take_last {
+                    unsolved.next_back()
+                } else {
+                    unsolved.next()
+                };
+                match next {
+                    Some(loc) => {
+                        let state = game.puzzle_state_mut(loc);
+                        *state.access_mut() = Access::Solved;
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
 
     #[test]
     fn all_locations_represented_on_map() {
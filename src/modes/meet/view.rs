@@ -19,11 +19,13 @@
 
 use super::scenes;
 use crate::elements::ice::GridView;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
-use crate::gui::{Action, Canvas, Element, Event, Rect, Resources, Sound};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::gui::{
+    Action, Canvas, Element, Event, Point, Rect, Resources, Sound,
+};
 use crate::modes::SOLVED_INFO_TEXT;
 use crate::save::ice::BlockSlide;
-use crate::save::{Game, MeetState, PuzzleState};
+use crate::save::{Direction, Game, Location, MeetState, PuzzleState};
 
 // ========================================================================= //
 
@@ -45,6 +47,7 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_elinsa_midscene(resources));
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
         View {
@@ -53,6 +56,25 @@ impl View {
             grid_visible: true,
         }
     }
+
+    fn slide_ice_block(
+        &mut self,
+        state: &mut MeetState,
+        coords: Point,
+        dir: Direction,
+    ) -> bool {
+        if let Some(slide) = state.slide_ice_block(coords, dir) {
+            self.grid.animate_slide(&slide);
+            if state.is_solved() {
+                self.core.begin_outro_scene();
+            } else {
+                self.core.push_undo(slide);
+            }
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl Element<Game, PuzzleCmd> for View {
@@ -79,14 +101,10 @@ impl Element<Game, PuzzleCmd> for View {
         {
             let subaction = self.grid.handle_event(event, state.grid_mut());
             if let Some(&(coords, dir)) = subaction.value() {
-                if let Some(slide) = state.slide_ice_block(coords, dir) {
+                if self.slide_ice_block(state, coords, dir) {
                     action.also_play_sound(Sound::device_slide());
-                    self.grid.animate_slide(&slide);
                     if state.is_solved() {
-                        self.core.begin_outro_scene();
                         action = action.and_return(PuzzleCmd::Save);
-                    } else {
-                        self.core.push_undo(slide);
                     }
                 }
             }
@@ -134,6 +152,26 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        let hints_used = game.hints_used(Location::IceToMeetYou);
+        self.grid.hint(game.ice_to_meet_you.grid(), hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.grid.highlight_hint(game.ice_to_meet_you.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.ice_to_meet_you;
+        if let Some((coords, dir)) = self.grid.next_move(state.grid()) {
+            self.slide_ice_block(state, coords, dir);
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 0 {
@@ -174,4 +212,97 @@ $M{your finger}{the mouse} to slide it in that direction.
 $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
+const HINT_TEXT: &str = "\
+A block of ice only stops when something gets in its
+way: a gap, the edge of the grid, a push-pop, or
+another block.  Look for a move that would leave a
+block right on its symbol, and work backwards.";
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, KeyMod, Keycode, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    #[test]
+    fn hints_escalate_to_a_slide() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::IceToMeetYou;
+        game.ice_to_meet_you.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.ice_to_meet_you);
+        let initial = game.ice_to_meet_you.grid().clone();
+        let moves = initial.solution().unwrap();
+        let (coords, dir) = moves[0];
+
+        // The first hint is just a tip, and starts the search for a
+        // solution.  Once that finishes, the second hint only highlights the
+        // next slide.
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let hint = Event::KeyDown(Keycode::F3, KeyMod::none());
+        events.extend(vec![hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        view.grid.finish_search();
+        events.extend(vec![hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(view.grid.next_move(&initial), Some((coords, dir)));
+        assert_eq!(game.hints_used(Location::IceToMeetYou), 2);
+        let grid = game.ice_to_meet_you.grid();
+        assert_eq!(grid.ice_blocks(), initial.ice_blocks());
+
+        // The third hint makes that slide.
+        events.extend(vec![hint, Event::ClockTick]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        let mut expected = initial;
+        assert!(expected.slide_ice_block(coords, dir).is_some());
+        let grid = game.ice_to_meet_you.grid();
+        assert_eq!(grid.ice_blocks(), expected.ice_blocks());
+
+        // The next slide comes from the same solution, without searching
+        // again.
+        assert_eq!(view.grid.next_move(grid), Some(moves[1]));
+    }
+
+    #[test]
+    fn undo_and_redo_a_slide() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::IceToMeetYou;
+        game.ice_to_meet_you.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.ice_to_meet_you);
+        let initial = game.ice_to_meet_you.grid().clone();
+        let (coords, dir) = initial.solution().unwrap()[0];
+        let mut expected = initial.clone();
+        assert!(expected.slide_ice_block(coords, dir).is_some());
+
+        // Let the third hint make the first slide of the solution.
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let hint = Event::KeyDown(Keycode::F3, KeyMod::none());
+        events.extend(vec![hint.clone()]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        view.grid.finish_search();
+        events.extend(vec![hint.clone(), hint]);
+        events.extend(vec![Event::ClockTick; 20]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        let grid = game.ice_to_meet_you.grid();
+        assert_eq!(grid.ice_blocks(), expected.ice_blocks());
+
+        events.extend(vec![Event::KeyDown(Keycode::Z, KeyMod::command())]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        let grid = game.ice_to_meet_you.grid();
+        assert_eq!(grid.ice_blocks(), initial.ice_blocks());
+
+        let redo = KeyMod::command() | KeyMod::shift();
+        events.extend(vec![Event::KeyDown(Keycode::Z, redo)]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        let grid = game.ice_to_meet_you.grid();
+        assert_eq!(grid.ice_blocks(), expected.ice_blocks());
+    }
+}
+
 // ========================================================================= //
@@ -19,12 +19,12 @@
 
 use super::scenes;
 use crate::elements::lasers::{DangerSign, LaserCmd, LaserField};
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
 use crate::gui::{
     Action, Canvas, Element, Event, Point, Rect, Resources, Sprite,
 };
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{Game, MissedState, PuzzleState};
+use crate::save::{Game, Location, MissedState, PuzzleState};
 
 // ========================================================================= //
 
@@ -48,6 +48,7 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_elinsa_midscene(resources));
         core.add_extra_scene(scenes::compile_mezure_midscene(resources));
         core.add_extra_scene(scenes::compile_ugrent_midscene(resources));
@@ -103,12 +104,11 @@ impl Element<Game, PuzzleCmd> for View {
             let subaction =
                 self.laser_field.handle_event(event, state.grid_mut());
             if let Some(&cmd) = subaction.value() {
-                if self.laser_field.all_detectors_satisfied(state.grid()) {
-                    state.mark_solved();
-                    self.core.begin_outro_scene();
+                if self
+                    .laser_field
+                    .finish_cmd(&mut self.core, cmd, || state.mark_solved())
+                {
                     action = action.and_return(PuzzleCmd::Save);
-                } else {
-                    self.core.push_undo(cmd);
                 }
             }
             action.merge(subaction.but_no_value());
@@ -176,6 +176,31 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        if !self.box_open {
+            return Hint::Tip(HINT_TEXT);
+        }
+        let hints_used = game.hints_used(Location::MissedConnections);
+        let grid = game.missed_connections.grid();
+        self.laser_field.hint(grid, hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.laser_field.highlight_hint(game.missed_connections.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.missed_connections;
+        if let Some(cmd) = self.laser_field.apply_hint(state.grid_mut()) {
+            self.laser_field
+                .finish_cmd(&mut self.core, cmd, || state.mark_solved());
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 0 {
@@ -270,4 +295,10 @@ them.
 $M{Tap}{Click} on a character in the scene to hear their words of
 wisdom.";
 
+const HINT_TEXT: &str = "\
+Compare the colors of the detectors with the colors of
+the lasers you start with.  A mixer needs one laser
+coming into each side, so plan where those two lasers
+will meet before placing the splitters.";
+
 // ========================================================================= //
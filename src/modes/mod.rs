@@ -54,6 +54,7 @@ mod puzzle;
 mod right;
 mod sauce;
 mod serves;
+mod settings;
 mod simple;
 mod star;
 mod syrup;
@@ -82,7 +83,7 @@ pub use self::ground::run_shifting_ground;
 pub use self::headed::run_level_headed;
 pub use self::hex::run_hex_spangled;
 pub use self::icyem::run_column_as_icy_em;
-pub use self::info::{run_info_box, SOLVED_INFO_TEXT};
+pub use self::info::{run_choice_box, run_info_box, SOLVED_INFO_TEXT};
 pub use self::jog::run_jog_your_memory;
 pub use self::lane::run_memory_lane;
 pub use self::levelup::run_level_up;
@@ -97,10 +98,13 @@ pub use self::order::run_point_of_order;
 pub use self::password::run_password_file;
 pub use self::pov::run_point_of_view;
 pub use self::prolog::run_prolog;
+#[cfg(test)]
+pub use self::puzzle::play_puzzle;
 pub use self::puzzle::run_puzzle;
 pub use self::right::run_the_ice_is_right;
 pub use self::sauce::run_cross_sauce;
 pub use self::serves::run_if_memory_serves;
+pub use self::settings::run_settings;
 pub use self::simple::run_plane_and_simple;
 pub use self::star::run_star_crossed;
 pub use self::syrup::run_light_syrup;
@@ -111,4 +115,244 @@ pub use self::tread::run_tread_lightly;
 pub use self::whatcha::run_whatcha_column;
 pub use self::wrecked::run_wrecked_angle;
 
+use crate::gui::Window;
+use crate::save::{Location, SaveData};
+
+// ========================================================================= //
+
+pub fn run_location(
+    window: &mut Window,
+    save_data: &mut SaveData,
+    loc: Location,
+) -> Mode {
+    save_data.game_mut().location = loc;
+    window.set_text_section(loc.key());
+    match loc {
+        Location::Map => run_map_screen(window, save_data),
+        Location::Prolog => run_prolog(window, save_data),
+        Location::ALightInTheAttic => {
+            run_a_light_in_the_attic(window, save_data)
+        }
+        Location::AutofacTour => run_autofac_tour(window, save_data),
+        Location::BlackAndBlue => run_black_and_blue(window, save_data),
+        Location::ColumnAsIcyEm => run_column_as_icy_em(window, save_data),
+        Location::ConnectTheDots => run_connect_the_dots(window, save_data),
+        Location::CrossSauce => run_cross_sauce(window, save_data),
+        Location::CrossTheLine => run_cross_the_line(window, save_data),
+        Location::CubeTangle => run_cube_tangle(window, save_data),
+        Location::Disconnected => run_disconnected(window, save_data),
+        Location::DoubleCross => run_double_cross(window, save_data),
+        Location::FactOrFiction => run_fact_or_fiction(window, save_data),
+        Location::HexSpangled => run_hex_spangled(window, save_data),
+        Location::IceToMeetYou => run_ice_to_meet_you(window, save_data),
+        Location::IfMemoryServes => run_if_memory_serves(window, save_data),
+        Location::JogYourMemory => run_jog_your_memory(window, save_data),
+        Location::LevelHeaded => run_level_headed(window, save_data),
+        Location::LevelUp => run_level_up(window, save_data),
+        Location::LightSyrup => run_light_syrup(window, save_data),
+        Location::LogLevel => run_log_level(window, save_data),
+        Location::MemoryLane => run_memory_lane(window, save_data),
+        Location::MissedConnections => {
+            run_missed_connections(window, save_data)
+        }
+        Location::PasswordFile => run_password_file(window, save_data),
+        Location::PlaneAndSimple => run_plane_and_simple(window, save_data),
+        Location::PlaneAsDay => run_plane_as_day(window, save_data),
+        Location::PointOfNoReturn => run_point_of_no_return(window, save_data),
+        Location::PointOfOrder => run_point_of_order(window, save_data),
+        Location::PointOfView => run_point_of_view(window, save_data),
+        Location::ShiftGears => run_shift_gears(window, save_data),
+        Location::ShiftTheBlame => run_shift_the_blame(window, save_data),
+        Location::ShiftingGround => run_shifting_ground(window, save_data),
+        Location::StarCrossed => run_star_crossed(window, save_data),
+        Location::SystemFailure => run_system_failure(window, save_data),
+        Location::SystemSyzygy => run_system_syzygy(window, save_data),
+        Location::TheIceIsRight => run_the_ice_is_right(window, save_data),
+        Location::TheYFactor => run_the_y_factor(window, save_data),
+        Location::ThreeBlindIce => run_three_blind_ice(window, save_data),
+        Location::TreadLightly => run_tread_lightly(window, save_data),
+        Location::WhatchaColumn => run_whatcha_column(window, save_data),
+        Location::WreckedAngle => run_wrecked_angle(window, save_data),
+        Location::Finale => run_finale(window, save_data),
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::BufWriter;
+    use std::path::Path;
+
+    use super::run_location;
+    use crate::gui::{Event, KeyMod, Keycode, Point, RgbaImage, Window};
+    use crate::save::{random, Access, Game, Location, SaveData};
+
+    /// Checksums of the frames drawn at each location, keyed by location and
+    /// state: its intro, partway through the puzzle, and once it's solved.
+    /// Run the tests with `SYZYGY_UPDATE_GOLDENS=1` set to regenerate this
+    /// file after an intentional change to what the game draws.
+    const GOLDEN_FILE: &str = "src/modes/testdata/golden.txt";
+
+    const GOLDEN_STATES: &[(&str, Access)] = &[
+        ("intro", Access::Unvisited),
+        ("midway", Access::Unsolved),
+        ("solved", Access::Solved),
+    ];
+
+    /// How many clock ticks to run each location for before and after any
+    /// input; long enough for the screen to finish fading in.
+    const GOLDEN_TICKS: usize = 20;
+
+    /// Input that moves an unsolved puzzle along from its initial state:
+    /// some key presses, then a click and a short drag at points all over
+    /// the screen above the HUD, each followed by a clock tick.
+    fn midway_script() -> Vec<Event> {
+        let mut events = Vec::new();
+        for &key in &[Keycode::Right, Keycode::Down, Keycode::Space] {
+            events.push(Event::KeyDown(key, KeyMod::none()));
+            events.push(Event::ClockTick);
+        }
+        for row in 1..6 {
+            for col in 1..9 {
+                let start = Point::new(col * 64 - 8, row * 56);
+                events.push(Event::MouseDown(start));
+                events.push(Event::MouseDrag(start.offset(20, 12)));
+                events.push(Event::MouseUp);
+                events.push(Event::ClockTick);
+            }
+        }
+        events
+    }
+
+    fn run_golden_location(
+        window: &mut Window,
+        location: Location,
+        access: Access,
+    ) {
+        // Some puzzles are randomized when created, so fix the seed.
+        random::set_seed(0);
+        let path = std::env::temp_dir().join("syzygy-golden/save.toml");
+        let mut save_data = SaveData::new(path);
+        if location != Location::Map {
+            let game = save_data.game_mut();
+            *game.puzzle_state_mut(location).access_mut() = access;
+            // Round-trip through TOML so that each puzzle state is made
+            // consistent with its new access, just as when loading a save.
+            *game = Game::from_toml(game.to_toml());
+        }
+        window.queue_events(vec![Event::ClockTick; GOLDEN_TICKS]);
+        if access == Access::Unsolved {
+            window.queue_events(midway_script());
+            window.queue_events(vec![Event::ClockTick; GOLDEN_TICKS]);
+        }
+        run_location(window, &mut save_data, location);
+        // The location may have been left before all the events were used,
+        // so discard the rest.
+        while window.next_event() != Event::Quit {}
+    }
+
+    fn render_location(
+        window: &mut Window,
+        location: Location,
+        access: Access,
+    ) -> RgbaImage {
+        // Drawing every frame would be slow, so run the location once just
+        // to count its frames, and then again to draw only the last one.
+        window.capture_frame(None);
+        run_golden_location(window, location, access);
+        let last = window.render_count() - 1;
+        window.capture_frame(Some(last));
+        run_golden_location(window, location, access);
+        window.headless_image().unwrap().clone()
+    }
+
+    fn checksum(image: &RgbaImage) -> String {
+        // 64-bit FNV-1a, which is stable across platforms and Rust versions.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for &byte in image.rgba_data() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    fn golden_name(location: Location, state: &str) -> String {
+        if location == Location::Map {
+            location.key().to_string()
+        } else {
+            format!("{}/{}", location.key(), state)
+        }
+    }
+
+    fn read_goldens(path: &Path) -> BTreeMap<String, String> {
+        let contents = fs::read_to_string(path).unwrap_or_default();
+        contents
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                match (words.next(), words.next()) {
+                    (Some(name), Some(sum)) => {
+                        Some((name.to_string(), sum.to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn locations_match_golden_images() {
+        // Share one window, and thus one resource cache, across locations.
+        let mut window = Window::headless((576, 384));
+        let mut actual = BTreeMap::new();
+        let mut images = BTreeMap::new();
+        for &location in Location::all() {
+            let states = if location == Location::Map {
+                &GOLDEN_STATES[..1]
+            } else {
+                GOLDEN_STATES
+            };
+            for &(state, access) in states {
+                let name = golden_name(location, state);
+                let image = render_location(&mut window, location, access);
+                actual.insert(name.clone(), checksum(&image));
+                images.insert(name, image);
+            }
+        }
+
+        let path = Path::new(GOLDEN_FILE);
+        if std::env::var_os("SYZYGY_UPDATE_GOLDENS").is_some() {
+            let mut contents = String::from("# location/state checksum\n");
+            for (name, sum) in actual.iter() {
+                contents.push_str(&format!("{} {}\n", name, sum));
+            }
+            fs::write(path, contents).unwrap();
+            return;
+        }
+
+        let expected = read_goldens(path);
+        let out_dir = std::env::temp_dir().join("syzygy-golden");
+        let mut mismatches = Vec::new();
+        for (name, sum) in actual.iter() {
+            if expected.get(name) != Some(sum) {
+                let file = out_dir.join(format!("{}.ppm", name));
+                fs::create_dir_all(file.parent().unwrap()).unwrap();
+                let writer = BufWriter::new(fs::File::create(&file).unwrap());
+                images[name].write_ppm(writer).unwrap();
+                mismatches.push(format!("{} (see {:?})", name, file));
+            }
+        }
+        assert!(
+            mismatches.is_empty(),
+            "Rendered frames differ from {}:\n  {}",
+            GOLDEN_FILE,
+            mismatches.join("\n  ")
+        );
+    }
+}
+
 // ========================================================================= //
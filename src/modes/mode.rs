@@ -24,6 +24,9 @@ use crate::save::Location;
 pub enum Mode {
     Title,
     Location(Location),
+    /// The settings screen, which returns to the given location when closed,
+    /// or to the title screen if there isn't one.
+    Settings(Option<Location>),
     Quit,
 }
 
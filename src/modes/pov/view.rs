@@ -21,13 +21,16 @@ use std::cmp;
 use std::rc::Rc;
 
 use super::scenes;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{
+    color_glyph, draw_color_glyph, FadeStyle, PuzzleCmd, PuzzleCore,
+    PuzzleView,
+};
 use crate::gui::{
     Action, Canvas, Element, Event, Font, Point, Rect, Resources, Sound,
     Sprite, FRAME_DELAY_MILLIS,
 };
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{Game, PovState, PuzzleState};
+use crate::save::{Game, MixedColor, PovState, PuzzleState};
 
 // ========================================================================= //
 
@@ -197,6 +200,17 @@ fn get_color(color: u8) -> (u8, u8, u8) {
     }
 }
 
+fn mixed_color(color: u8) -> Option<MixedColor> {
+    match color {
+        0 => Some(MixedColor::Red),
+        1 => Some(MixedColor::Yellow),
+        2 => Some(MixedColor::Green),
+        3 => Some(MixedColor::Blue),
+        4 => Some(MixedColor::Magenta),
+        _ => None,
+    }
+}
+
 struct GridDrag {
     tile: [u8; 4],
     from_coords: (i32, i32),
@@ -212,6 +226,8 @@ pub struct PovGridView {
     drag: Option<GridDrag>,
     font: Rc<Font>,
     num_letters: usize,
+    color_blind: bool,
+    glyph_font: Rc<Font>,
 }
 
 impl PovGridView {
@@ -227,6 +243,31 @@ impl PovGridView {
             drag: None,
             font: resources.get_font("block"),
             num_letters: 0,
+            color_blind: resources.color_blind_mode(),
+            glyph_font: resources.get_font("tiny"),
+        }
+    }
+
+    fn draw_indicator(
+        &self,
+        canvas: &mut Canvas,
+        color: u8,
+        rect: Rect,
+        glyph: bool,
+    ) {
+        canvas.fill_rect(get_color(color), rect);
+        // The tile sprites can't change color, so rather than switching
+        // palettes (and no longer matching the tiles), color-blind mode just
+        // labels the goal indicators.
+        if self.color_blind && glyph {
+            if let Some(mixed) = mixed_color(color) {
+                draw_color_glyph(
+                    canvas,
+                    &self.glyph_font,
+                    rect.center(),
+                    color_glyph(mixed),
+                );
+            }
         }
     }
 
@@ -235,6 +276,22 @@ impl PovGridView {
             let sprite_index = 4 * (color as usize) + index;
             canvas.draw_sprite(&self.tile_sprites[sprite_index], top_left);
         }
+        if self.color_blind {
+            let center =
+                top_left + Point::new(GRID_CELL_SIZE / 2, GRID_CELL_SIZE / 2);
+            // The tile's sides are in the order east, south, west, north.
+            let offsets = [(9, 0), (0, 9), (-9, 0), (0, -9)];
+            for (&color, &(dx, dy)) in tile.iter().zip(offsets.iter()) {
+                if let Some(mixed) = mixed_color(color) {
+                    draw_color_glyph(
+                        canvas,
+                        &self.glyph_font,
+                        center + Point::new(dx, dy),
+                        color_glyph(mixed),
+                    );
+                }
+            }
+        }
     }
 }
 
@@ -256,17 +313,20 @@ impl Element<PovState, PovCmd> for PovGridView {
             let color = state.row_left_color(row);
             let left = self.rect.left() - INDICATOR_TOTAL_THICKNESS;
             let top = self.rect.top() + GRID_CELL_SIZE * row;
-            canvas.fill_rect(
-                get_color(goal),
+            self.draw_indicator(
+                canvas,
+                goal,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN,
                     INDICATOR_GOAL_THICKNESS as u32,
                     INDICATOR_LENGTH,
                 ),
+                true,
             );
-            canvas.fill_rect(
-                get_color(color),
+            self.draw_indicator(
+                canvas,
+                color,
                 Rect::new(
                     left + INDICATOR_MARGIN
                         + INDICATOR_GOAL_THICKNESS
@@ -275,22 +335,26 @@ impl Element<PovState, PovCmd> for PovGridView {
                     INDICATOR_COLOR_THICKNESS as u32,
                     INDICATOR_LENGTH,
                 ),
+                false,
             );
             // Right:
             let goal = state.row_right_goal(row);
             let color = state.row_right_color(row);
             let left = self.rect.right();
-            canvas.fill_rect(
-                get_color(color),
+            self.draw_indicator(
+                canvas,
+                color,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN,
                     INDICATOR_COLOR_THICKNESS as u32,
                     INDICATOR_LENGTH,
                 ),
+                false,
             );
-            canvas.fill_rect(
-                get_color(goal),
+            self.draw_indicator(
+                canvas,
+                goal,
                 Rect::new(
                     left + INDICATOR_MARGIN
                         + INDICATOR_COLOR_THICKNESS
@@ -299,6 +363,7 @@ impl Element<PovState, PovCmd> for PovGridView {
                     INDICATOR_GOAL_THICKNESS as u32,
                     INDICATOR_LENGTH,
                 ),
+                true,
             );
         }
         for col in 0..5 {
@@ -307,17 +372,20 @@ impl Element<PovState, PovCmd> for PovGridView {
             let color = state.col_top_color(col);
             let left = self.rect.left() + GRID_CELL_SIZE * col;
             let top = self.rect.top() - INDICATOR_TOTAL_THICKNESS;
-            canvas.fill_rect(
-                get_color(goal),
+            self.draw_indicator(
+                canvas,
+                goal,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN,
                     INDICATOR_LENGTH,
                     INDICATOR_GOAL_THICKNESS as u32,
                 ),
+                true,
             );
-            canvas.fill_rect(
-                get_color(color),
+            self.draw_indicator(
+                canvas,
+                color,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN
@@ -326,22 +394,26 @@ impl Element<PovState, PovCmd> for PovGridView {
                     INDICATOR_LENGTH,
                     INDICATOR_COLOR_THICKNESS as u32,
                 ),
+                false,
             );
             // Bottom:
             let goal = state.col_bottom_goal(col);
             let color = state.col_bottom_color(col);
             let top = self.rect.bottom();
-            canvas.fill_rect(
-                get_color(color),
+            self.draw_indicator(
+                canvas,
+                color,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN,
                     INDICATOR_LENGTH,
                     INDICATOR_COLOR_THICKNESS as u32,
                 ),
+                false,
             );
-            canvas.fill_rect(
-                get_color(goal),
+            self.draw_indicator(
+                canvas,
+                goal,
                 Rect::new(
                     left + INDICATOR_MARGIN,
                     top + INDICATOR_MARGIN
@@ -350,6 +422,7 @@ impl Element<PovState, PovCmd> for PovGridView {
                     INDICATOR_LENGTH,
                     INDICATOR_GOAL_THICKNESS as u32,
                 ),
+                true,
             );
         }
         for (&coords, &tile) in state.tiles() {
@@ -17,10 +17,12 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use crate::elements::{PuzzleCmd, PuzzleView};
-use crate::gui::{Event, Window};
+use crate::elements::{Hint, PuzzleCmd, PuzzleView};
+#[cfg(test)]
+use crate::gui::EventSource;
+use crate::gui::{Action, Event, Window};
 use crate::modes::{run_info_box, Mode};
-use crate::save::{Location, SaveData};
+use crate::save::{Game, Location, SaveData};
 
 // ========================================================================= //
 
@@ -38,48 +40,31 @@ pub fn run_puzzle<V: PuzzleView>(
         location
     };
     loop {
-        let mut action = match window.next_event() {
+        let (mut action, step) = match window.next_event() {
             Event::Quit => return Mode::Quit,
-            event => {
-                let action = view.handle_event(&event, save_data.game_mut());
-                view.drain_queue();
-                action
-            }
+            event => handle_event(&mut view, save_data.game_mut(), &event),
         };
         window.play_sounds(action.drain_sounds());
-        match action.value() {
-            Some(&PuzzleCmd::Back) => return Mode::Location(Location::Map),
-            Some(&PuzzleCmd::Info) => {
-                let game = save_data.game_mut();
-                game.ever_clicked_info = true;
-                let text = view.info_text(game);
-                if !run_info_box(window, &view, game, text) {
-                    return Mode::Quit;
-                }
+        if let Some(text) = step.info_text {
+            if !run_info_box(window, &view, save_data.game_mut(), text) {
+                return Mode::Quit;
             }
-            Some(&PuzzleCmd::Undo) => view.undo(save_data.game_mut()),
-            Some(&PuzzleCmd::Redo) => view.redo(save_data.game_mut()),
-            Some(&PuzzleCmd::Reset) => view.reset(save_data.game_mut()),
-            Some(&PuzzleCmd::Replay) => {
-                save_data.game_mut().puzzle_state_mut(location).replay();
-                return Mode::Location(location);
-            }
-            Some(&PuzzleCmd::Solve) => {
-                view.solve(save_data.game_mut());
-                view.drain_queue();
+        }
+        if step.save {
+            if let Err(error) = save_data.save_to_disk() {
+                println!("Failed to auto-save game: {}", error);
             }
-            Some(&PuzzleCmd::Next) => {
+        }
+        match step.leave {
+            Some(PuzzleCmd::Next) => {
                 let mut next = location.next();
                 if !save_data.game_mut().is_unlocked(next) {
                     next = Location::Map;
                 }
                 return Mode::Location(next);
             }
-            Some(&PuzzleCmd::Save) => {
-                if let Err(error) = save_data.save_to_disk() {
-                    println!("Failed to auto-save game: {}", error);
-                }
-            }
+            Some(PuzzleCmd::Replay) => return Mode::Location(location),
+            Some(_) => return Mode::Location(Location::Map),
             None => {}
         }
         if action.should_redraw() {
@@ -88,4 +73,115 @@ pub fn run_puzzle<V: PuzzleView>(
     }
 }
 
+/// Feeds events from `events` to a puzzle view, as `run_puzzle` would, but
+/// without a window: nothing is drawn, sounds are discarded, `Info` and
+/// `Save` commands are ignored, and hints that would show a tip are just
+/// counted.  Stops and returns the command once the view asks to leave the
+/// puzzle (`Back`, `Replay` or `Next`), or returns `None` once the source
+/// reports `Event::Quit`.
+#[cfg(test)]
+pub fn play_puzzle<V: PuzzleView, E: EventSource>(
+    events: &mut E,
+    view: &mut V,
+    game: &mut Game,
+) -> Option<PuzzleCmd> {
+    view.drain_queue();
+    let location = game.location;
+    game.puzzle_state_mut(location).revisit();
+    loop {
+        let (_, step) = match events.next_event() {
+            Event::Quit => return None,
+            event => handle_event(view, game, &event),
+        };
+        if step.leave.is_some() {
+            return step.leave;
+        }
+    }
+}
+
+/// What the puzzle loop should do after the view has handled an event, beyond
+/// redrawing and playing sounds.
+#[derive(Default)]
+struct PuzzleStep {
+    /// Text to show in an info box.
+    info_text: Option<&'static str>,
+    /// Whether the game should be saved to disk.
+    save: bool,
+    /// The command (`Back`, `Replay` or `Next`) to leave the puzzle with.
+    leave: Option<PuzzleCmd>,
+}
+
+/// Passes an event to the puzzle view and carries out any command that it
+/// returns, as far as that can be done without a window.
+fn handle_event<V: PuzzleView>(
+    view: &mut V,
+    game: &mut Game,
+    event: &Event,
+) -> (Action<()>, PuzzleStep) {
+    let mut action = view.handle_event(event, game);
+    view.drain_queue();
+    let mut step = PuzzleStep::default();
+    match action.take_value() {
+        Some(PuzzleCmd::Info) => {
+            game.ever_clicked_info = true;
+            step.info_text = Some(view.info_text(game));
+        }
+        Some(PuzzleCmd::Hint) if view.supports_hints() => {
+            step.info_text = give_hint(view, game);
+            step.save = true;
+        }
+        Some(PuzzleCmd::Save) => step.save = true,
+        Some(PuzzleCmd::Replay) => {
+            let location = game.location;
+            game.puzzle_state_mut(location).replay();
+            step.leave = Some(PuzzleCmd::Replay);
+        }
+        Some(cmd @ PuzzleCmd::Back) | Some(cmd @ PuzzleCmd::Next) => {
+            step.leave = Some(cmd);
+        }
+        Some(cmd) => apply_puzzle_cmd(view, game, cmd),
+        None => {}
+    }
+    (action.but_no_value(), step)
+}
+
+/// Records that the player used a hint on the current puzzle, and carries out
+/// the hint.  Returns the tip text if the hint should be shown in an info
+/// box.
+fn give_hint<V: PuzzleView>(
+    view: &mut V,
+    game: &mut Game,
+) -> Option<&'static str> {
+    let hint = view.hint(game);
+    let location = game.location;
+    game.use_hint(location);
+    match hint {
+        Hint::Tip(text) => return Some(text),
+        Hint::Highlight => view.highlight_hint(game),
+        Hint::Apply => {
+            view.apply_hint(game);
+            view.drain_queue();
+        }
+    }
+    None
+}
+
+/// Carries out a command that only affects the puzzle itself.
+fn apply_puzzle_cmd<V: PuzzleView>(
+    view: &mut V,
+    game: &mut Game,
+    cmd: PuzzleCmd,
+) {
+    match cmd {
+        PuzzleCmd::Undo => view.undo(game),
+        PuzzleCmd::Redo => view.redo(game),
+        PuzzleCmd::Reset => view.reset(game),
+        PuzzleCmd::Solve => {
+            view.solve(game);
+            view.drain_queue();
+        }
+        _ => {}
+    }
+}
+
 // ========================================================================= //
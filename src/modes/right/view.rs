@@ -19,11 +19,13 @@
 
 use super::scenes;
 use crate::elements::ice::GridView;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
-use crate::gui::{Action, Canvas, Element, Event, Rect, Resources, Sound};
+use crate::elements::{FadeStyle, Hint, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::gui::{
+    Action, Canvas, Element, Event, Point, Rect, Resources, Sound,
+};
 use crate::modes::SOLVED_INFO_TEXT;
 use crate::save::ice::BlockSlide;
-use crate::save::{Game, PuzzleState, RightState};
+use crate::save::{Direction, Game, Location, PuzzleState, RightState};
 
 // ========================================================================= //
 
@@ -44,10 +46,30 @@ impl View {
             let outro = scenes::compile_outro_scene(resources);
             PuzzleCore::new(resources, visible, state, fade, intro, outro)
         };
+        core.enable_hints();
         core.add_extra_scene(scenes::compile_elinsa_midscene(resources));
         core.add_extra_scene(scenes::compile_yttris_midscene(resources));
         View { core, grid: GridView::new(resources, 144, 48, state.grid()) }
     }
+
+    fn slide_ice_block(
+        &mut self,
+        state: &mut RightState,
+        coords: Point,
+        dir: Direction,
+    ) -> bool {
+        if let Some(slide) = state.slide_ice_block(coords, dir) {
+            self.grid.animate_slide(&slide);
+            if state.is_solved() {
+                self.core.begin_outro_scene();
+            } else {
+                self.core.push_undo(slide);
+            }
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl Element<Game, PuzzleCmd> for View {
@@ -71,14 +93,10 @@ impl Element<Game, PuzzleCmd> for View {
         {
             let subaction = self.grid.handle_event(event, state.grid_mut());
             if let Some(&(coords, dir)) = subaction.value() {
-                if let Some(slide) = state.slide_ice_block(coords, dir) {
+                if self.slide_ice_block(state, coords, dir) {
                     action.also_play_sound(Sound::device_slide());
-                    self.grid.animate_slide(&slide);
                     if state.is_solved() {
-                        self.core.begin_outro_scene();
                         action = action.and_return(PuzzleCmd::Save);
-                    } else {
-                        self.core.push_undo(slide);
                     }
                 }
             }
@@ -126,6 +144,26 @@ impl PuzzleView for View {
         self.core.begin_outro_scene();
     }
 
+    fn supports_hints(&self) -> bool {
+        true
+    }
+
+    fn hint(&self, game: &Game) -> Hint {
+        let hints_used = game.hints_used(Location::TheIceIsRight);
+        self.grid.hint(game.the_ice_is_right.grid(), hints_used, HINT_TEXT)
+    }
+
+    fn highlight_hint(&mut self, game: &Game) {
+        self.grid.highlight_hint(game.the_ice_is_right.grid());
+    }
+
+    fn apply_hint(&mut self, game: &mut Game) {
+        let state = &mut game.the_ice_is_right;
+        if let Some((coords, dir)) = self.grid.next_move(state.grid()) {
+            self.slide_ice_block(state, coords, dir);
+        }
+    }
+
     fn drain_queue(&mut self) {
         for (kind, value) in self.core.drain_queue() {
             if kind == 1 {
@@ -164,4 +202,10 @@ $M{your finger}{the mouse} to slide it in that direction.
 $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
+const HINT_TEXT: &str = "\
+Each pass over the rotator turns a block a quarter
+turn clockwise, so a block may need to pass over
+it more than once.  A push-pop stops a block, unless
+the block hits it from the side it's facing.";
+
 // ========================================================================= //
@@ -218,3 +218,49 @@ $M{Tap}{Click} on a character in the scene to hear their
 words of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    fn typing(text: &str) -> Event {
+        Event::TextInput(text.to_string())
+    }
+
+    #[test]
+    fn typing_each_answer_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::CrossSauce;
+        game.cross_sauce.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.cross_sauce);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+
+        // A wrong letter is rejected, but what was right so far is kept, so
+        // typing the rest of the answer finishes it.
+        let answer = game.cross_sauce.current_answer();
+        events.extend(vec![typing(&answer[..2]), typing("Q")]);
+        events.extend(vec![Event::ClockTick; 10]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.cross_sauce.num_clues_done(), 0);
+
+        events.extend(vec![typing(&answer[2..])]);
+        events.extend(vec![Event::ClockTick; 30]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.cross_sauce.num_clues_done(), 1);
+        while !game.cross_sauce.is_solved() {
+            let done = game.cross_sauce.num_clues_done();
+            events.extend(vec![typing(game.cross_sauce.current_answer())]);
+            events.extend(vec![Event::ClockTick; 30]);
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+            assert_eq!(game.cross_sauce.num_clues_done(), done + 1);
+        }
+    }
+}
+
+// ========================================================================= //
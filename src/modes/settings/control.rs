@@ -0,0 +1,272 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use crate::gui::{
+    Element, Event, Keycode, ScaleMode, SpeechOutput, Window, LANGUAGES,
+};
+use crate::modes::Mode;
+use crate::save::{Location, SaveData, MAX_VOLUME, MAX_WINDOW_SCALE};
+
+use super::view::{Cmd, Setting, View, SETTINGS};
+
+// ========================================================================= //
+
+const VOLUME_STEP: i32 = 10;
+const SCENE_SPEEDS: &[u32] = &[50, 75, 100, 150, 200, 300, 400];
+const AUTO_ADVANCE_MILLIS: &[u32] = &[0, 1000, 2000, 3000, 5000];
+
+// ========================================================================= //
+
+pub fn run_settings(
+    window: &mut Window,
+    data: &mut SaveData,
+    back: Option<Location>,
+) -> Mode {
+    let mut selected = 0;
+    loop {
+        let cmd = match choose_cmd(window, data, selected) {
+            Some(cmd) => cmd,
+            None => return Mode::Quit,
+        };
+        match cmd {
+            Cmd::Prev => {
+                selected = (selected + SETTINGS.len() - 1) % SETTINGS.len();
+            }
+            Cmd::Next => selected = (selected + 1) % SETTINGS.len(),
+            Cmd::Decrease => change_setting(window, data, selected, -1),
+            Cmd::Increase => change_setting(window, data, selected, 1),
+            Cmd::Done => {
+                return back.map(Mode::Location).unwrap_or(Mode::Title);
+            }
+        }
+    }
+}
+
+/// Shows the settings dialog until the user picks a command.  Returns `None`
+/// if the user quit the game instead.
+fn choose_cmd(
+    window: &mut Window,
+    data: &mut SaveData,
+    selected: usize,
+) -> Option<Cmd> {
+    let mut view = {
+        let visible = window.visible_rect();
+        View::new(&mut window.resources(), visible, data.prefs(), selected)
+    };
+    window.render(data, &view);
+    loop {
+        let mut action = match window.next_event() {
+            Event::Quit => return None,
+            Event::KeyDown(Keycode::Up, _) => return Some(Cmd::Prev),
+            Event::KeyDown(Keycode::Down, _) => return Some(Cmd::Next),
+            Event::KeyDown(Keycode::Left, _) => return Some(Cmd::Decrease),
+            Event::KeyDown(Keycode::Right, _) => return Some(Cmd::Increase),
+            Event::KeyDown(Keycode::Escape, _)
+            | Event::KeyDown(Keycode::Return, _) => return Some(Cmd::Done),
+            event => view.handle_event(&event, data),
+        };
+        window.play_sounds(action.drain_sounds());
+        if let Some(&cmd) = action.value() {
+            return Some(cmd);
+        } else if action.should_redraw() {
+            window.render(data, &view);
+        }
+    }
+}
+
+/// Changes the selected setting by one step in the direction of `delta`,
+/// applies it to the window, and saves it right away.
+fn change_setting(
+    window: &mut Window,
+    data: &mut SaveData,
+    selected: usize,
+    delta: i32,
+) {
+    {
+        let prefs = data.prefs_mut();
+        match SETTINGS[selected] {
+            Setting::Language => {
+                let index = LANGUAGES
+                    .iter()
+                    .position(|&(code, _)| code == prefs.language())
+                    .unwrap_or(0) as i32;
+                let count = LANGUAGES.len() as i32;
+                let (code, _) =
+                    LANGUAGES[(index + delta).rem_euclid(count) as usize];
+                prefs.set_language(code);
+                window.set_language(code);
+            }
+            Setting::Fullscreen => {
+                let fullscreen = !prefs.fullscreen();
+                prefs.set_fullscreen(fullscreen);
+                window.set_fullscreen(fullscreen);
+                window.set_window_scale(prefs.window_scale());
+            }
+            Setting::WindowScale => {
+                let scale =
+                    step(prefs.window_scale(), delta, MAX_WINDOW_SCALE);
+                prefs.set_window_scale(scale.max(1));
+                window.set_window_scale(prefs.window_scale());
+            }
+            Setting::ScaleMode => {
+                let modes = ScaleMode::all();
+                let index = modes
+                    .iter()
+                    .position(|&mode| mode == prefs.scale_mode())
+                    .unwrap_or(0) as i32;
+                let count = modes.len() as i32;
+                let mode = modes[(index + delta).rem_euclid(count) as usize];
+                prefs.set_scale_mode(mode);
+                window.set_scale_mode(mode);
+            }
+            Setting::MasterVolume => {
+                let volume = prefs.master_volume();
+                let delta = delta * VOLUME_STEP;
+                prefs.set_master_volume(step(volume, delta, MAX_VOLUME));
+            }
+            Setting::SoundVolume => {
+                let volume = prefs.sound_volume();
+                let delta = delta * VOLUME_STEP;
+                prefs.set_sound_volume(step(volume, delta, MAX_VOLUME));
+            }
+            Setting::Mute => {
+                let muted = !prefs.muted();
+                prefs.set_muted(muted);
+            }
+            Setting::ColorBlind => {
+                let color_blind = !prefs.color_blind();
+                prefs.set_color_blind(color_blind);
+                window.set_color_blind_mode(color_blind);
+            }
+            Setting::SceneSpeed => {
+                let speed =
+                    step_through(SCENE_SPEEDS, prefs.scene_speed(), delta);
+                prefs.set_scene_speed(speed);
+            }
+            Setting::AutoAdvance => {
+                let millis = step_through(
+                    AUTO_ADVANCE_MILLIS,
+                    prefs.auto_advance_millis(),
+                    delta,
+                );
+                prefs.set_auto_advance_millis(millis);
+            }
+            Setting::SpeechOutput => {
+                // A named pipe can only be chosen by editing the prefs file,
+                // since it needs a path; cycling away from it turns output
+                // off.
+                let outputs = [
+                    SpeechOutput::Off,
+                    SpeechOutput::Stdout,
+                    SpeechOutput::SpeechDispatcher(None),
+                ];
+                let count = outputs.len() as i32;
+                let output = match outputs
+                    .iter()
+                    .position(|output| output == prefs.speech_output())
+                {
+                    Some(index) => {
+                        let index = (index as i32 + delta).rem_euclid(count);
+                        outputs[index as usize].clone()
+                    }
+                    None => SpeechOutput::Off,
+                };
+                window.set_speech_output(&output);
+                prefs.set_speech_output(output);
+            }
+        }
+        window.set_sound_gain(prefs.sound_gain());
+        window.set_scene_pacing(prefs.scene_pacing());
+    }
+    if let Err(error) = data.save_to_disk() {
+        println!("Failed to save game: {}", error);
+    }
+}
+
+fn step(value: u32, delta: i32, max: u32) -> u32 {
+    (value as i32 + delta).max(0).min(max as i32) as u32
+}
+
+/// Returns the next of the given choices (which must be sorted) above or
+/// below `value`, stopping at either end.
+fn step_through(choices: &[u32], value: u32, delta: i32) -> u32 {
+    let index = if delta > 0 {
+        choices.iter().position(|&choice| choice > value)
+    } else {
+        choices.iter().rposition(|&choice| choice < value)
+    };
+    index.map(|index| choices[index]).unwrap_or(value)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::run_settings;
+    use crate::gui::{
+        Event, KeyMod, Keycode, ScaleMode, SpeechOutput, Window,
+    };
+    use crate::modes::Mode;
+    use crate::save::{Location, SaveData};
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown(keycode, KeyMod::none())
+    }
+
+    #[test]
+    fn keys_change_settings_and_return() {
+        let mut window = Window::headless((576, 384));
+        let path = std::env::temp_dir().join("syzygy-settings/save.toml");
+        let mut data = SaveData::new(path);
+        window.queue_events(vec![
+            key(Keycode::Right),
+            key(Keycode::Up),
+            key(Keycode::Up),
+            key(Keycode::Right),
+            key(Keycode::Right),
+            key(Keycode::Up),
+            key(Keycode::Right),
+            key(Keycode::Up),
+            key(Keycode::Right),
+            key(Keycode::Up),
+            key(Keycode::Up),
+            key(Keycode::Up),
+            key(Keycode::Left),
+            key(Keycode::Left),
+            key(Keycode::Up),
+            key(Keycode::Right),
+            key(Keycode::Up),
+            key(Keycode::Right),
+            key(Keycode::Escape),
+        ]);
+        let mode = run_settings(&mut window, &mut data, Some(Location::Map));
+        assert!(matches!(mode, Mode::Location(Location::Map)));
+        assert_eq!(data.prefs().language(), "en");
+        assert_eq!(data.prefs().master_volume(), 80);
+        assert_eq!(data.prefs().scale_mode(), ScaleMode::Letterbox);
+        assert_eq!(data.prefs().window_scale(), 2);
+        assert!(!data.prefs().muted());
+        assert!(data.prefs().color_blind());
+        assert_eq!(data.prefs().scene_speed(), 150);
+        assert_eq!(data.prefs().auto_advance_millis(), 2000);
+        assert_eq!(data.prefs().speech_output(), &SpeechOutput::Off);
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,187 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::rc::Rc;
+
+use crate::elements::DialogBox;
+use crate::gui::{
+    Action, Background, Canvas, Element, Event, Rect, Resources, ScaleMode,
+    SpeechOutput, LANGUAGES,
+};
+use crate::save::{Prefs, SaveData};
+
+// ========================================================================= //
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Cmd {
+    Prev,
+    Next,
+    Decrease,
+    Increase,
+    Done,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Setting {
+    Language,
+    Fullscreen,
+    WindowScale,
+    ScaleMode,
+    MasterVolume,
+    SoundVolume,
+    Mute,
+    ColorBlind,
+    SceneSpeed,
+    AutoAdvance,
+    SpeechOutput,
+}
+
+pub const SETTINGS: &[Setting] = &[
+    Setting::Language,
+    Setting::Fullscreen,
+    Setting::WindowScale,
+    Setting::ScaleMode,
+    Setting::MasterVolume,
+    Setting::SoundVolume,
+    Setting::Mute,
+    Setting::ColorBlind,
+    Setting::SceneSpeed,
+    Setting::AutoAdvance,
+    Setting::SpeechOutput,
+];
+
+impl Setting {
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Language => "Language",
+            Setting::Fullscreen => "Fullscreen",
+            Setting::WindowScale => "Window size",
+            Setting::ScaleMode => "Scaling",
+            Setting::MasterVolume => "Volume",
+            Setting::SoundVolume => "Sound effects",
+            Setting::Mute => "Mute",
+            Setting::ColorBlind => "Color-blind mode",
+            Setting::SceneSpeed => "Cutscene speed",
+            Setting::AutoAdvance => "Auto-advance speech",
+            Setting::SpeechOutput => "Screen reader output",
+        }
+    }
+
+    fn value_text(self, prefs: &Prefs) -> String {
+        match self {
+            Setting::Language => LANGUAGES
+                .iter()
+                .find(|&&(code, _)| code == prefs.language())
+                .map_or(prefs.language(), |&(_, name)| name)
+                .to_string(),
+            Setting::Fullscreen => on_off(prefs.fullscreen()),
+            Setting::WindowScale => format!("{}x", prefs.window_scale()),
+            Setting::ScaleMode => match prefs.scale_mode() {
+                ScaleMode::IntegerScale => "Pixel-perfect",
+                ScaleMode::Fill => "Fill window",
+                ScaleMode::Letterbox => "Fit with letterbox",
+            }
+            .to_string(),
+            Setting::MasterVolume => format!("{}%", prefs.master_volume()),
+            Setting::SoundVolume => format!("{}%", prefs.sound_volume()),
+            Setting::Mute => on_off(prefs.muted()),
+            Setting::ColorBlind => on_off(prefs.color_blind()),
+            Setting::SceneSpeed => format!("{}%", prefs.scene_speed()),
+            Setting::AutoAdvance => match prefs.auto_advance_millis() {
+                0 => "Off".to_string(),
+                millis => format!("After {}s", millis as f64 / 1000.0),
+            },
+            Setting::SpeechOutput => match prefs.speech_output() {
+                SpeechOutput::Off => "Off",
+                SpeechOutput::Stdout => "Console",
+                SpeechOutput::Pipe(_) => "Named pipe",
+                SpeechOutput::SpeechDispatcher(_) => "Speech Dispatcher",
+            }
+            .to_string(),
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_string()
+}
+
+// ========================================================================= //
+
+pub struct View {
+    background: Rc<Background>,
+    dialog: DialogBox<Cmd>,
+}
+
+impl View {
+    pub fn new(
+        resources: &mut Resources,
+        visible: Rect,
+        prefs: &Prefs,
+        selected: usize,
+    ) -> View {
+        let mut text =
+            format!("$C{}$L\n", resources.translate_common("Settings"));
+        for (index, &setting) in SETTINGS.iter().enumerate() {
+            let marker = if index == selected { "> " } else { "" };
+            text.push_str(&format!(
+                "\n{}{}$R{}$L",
+                marker,
+                resources.translate_common(setting.label()),
+                setting.value_text(prefs)
+            ));
+        }
+        if let Some(&setting) = SETTINGS.get(selected) {
+            resources.announcer().announce(&format!(
+                "{}: {}",
+                resources.translate_common(setting.label()),
+                setting.value_text(prefs)
+            ));
+        }
+        let buttons = vec![
+            ("Up".to_string(), Cmd::Prev),
+            ("Down".to_string(), Cmd::Next),
+            ("-".to_string(), Cmd::Decrease),
+            ("+".to_string(), Cmd::Increase),
+            ("Done".to_string(), Cmd::Done),
+        ];
+        View {
+            background: resources.get_background("space"),
+            dialog: DialogBox::new(resources, visible, &text, buttons),
+        }
+    }
+}
+
+impl Element<SaveData, Cmd> for View {
+    fn draw(&self, _: &SaveData, canvas: &mut Canvas) {
+        canvas.clear(self.background.color());
+        canvas.draw_background(&self.background);
+        self.dialog.draw(&(), canvas);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        _: &mut SaveData,
+    ) -> Action<Cmd> {
+        self.dialog.handle_event(event, &mut ())
+    }
+}
+
+// ========================================================================= //
@@ -174,3 +174,51 @@ $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState, SimpleState};
+
+    fn drag_along(pipe: &[Point]) -> Vec<Event> {
+        let pixel = |coords: Point| {
+            Point::new(128 + 24 * coords.x() + 12, 48 + 24 * coords.y() + 12)
+        };
+        let mut events = vec![Event::MouseDown(pixel(pipe[0]))];
+        for &coords in pipe[1..].iter() {
+            events.push(Event::MouseDrag(pixel(coords)));
+        }
+        events.push(Event::MouseUp);
+        events
+    }
+
+    #[test]
+    fn laying_each_stage_solution_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::PlaneAndSimple;
+        game.plane_and_simple.visit();
+        let mut view = View::new(
+            &mut window.resources(),
+            visible,
+            &game.plane_and_simple,
+        );
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        for stage_grid in SimpleState::stage_grids() {
+            assert!(!game.plane_and_simple.is_solved());
+            let grid = game.plane_and_simple.grid();
+            assert!(grid.objects() == stage_grid.objects());
+            // Dragging along each of the last stage's pipes removes it, and
+            // then this stage's solution can be laid from scratch.
+            let solution = stage_grid.solution().unwrap().unwrap();
+            for pipe in grid.pipes().iter().chain(solution.pipes()) {
+                events.extend(drag_along(pipe));
+            }
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        }
+        assert!(game.plane_and_simple.is_solved());
+    }
+}
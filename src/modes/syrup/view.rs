@@ -21,13 +21,16 @@ use std::cmp;
 use std::rc::Rc;
 
 use super::scenes;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{
+    color_blind_rgb, color_glyph, draw_color_glyph, FadeStyle, PuzzleCmd,
+    PuzzleCore, PuzzleView,
+};
 use crate::gui::{
     Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
     Sound, Sprite,
 };
 use crate::modes::SOLVED_INFO_TEXT;
-use crate::save::{Game, PrimaryColor, PuzzleState, SyrupState};
+use crate::save::{Game, MixedColor, PrimaryColor, PuzzleState, SyrupState};
 
 // ========================================================================= //
 
@@ -183,6 +186,8 @@ struct ToggleLight {
     hilight: i32,
     font: Rc<Font>,
     letter: Option<char>,
+    color_blind: bool,
+    glyph_font: Rc<Font>,
 }
 
 impl ToggleLight {
@@ -201,6 +206,8 @@ impl ToggleLight {
             hilight: 0,
             font: resources.get_font("block"),
             letter: None,
+            color_blind: resources.color_blind_mode(),
+            glyph_font: resources.get_font("tiny"),
         }
     }
 
@@ -212,37 +219,40 @@ impl ToggleLight {
     fn set_hilight(&mut self, color: i32) {
         self.hilight = color;
     }
+
+    fn rgb(&self, color: MixedColor) -> (u8, u8, u8) {
+        if self.color_blind {
+            color_blind_rgb(color)
+        } else {
+            (
+                if color.has_red() { 255 } else { 0 },
+                if color.has_green() { 255 } else { 0 },
+                if color.has_blue() { 255 } else { 0 },
+            )
+        }
+    }
+
+    fn hilight_color(&self) -> Option<MixedColor> {
+        match self.hilight {
+            1 => Some(MixedColor::Red),
+            2 => Some(MixedColor::Green),
+            3 => Some(MixedColor::Blue),
+            _ => None,
+        }
+    }
 }
 
 impl Element<SyrupState, (i32, i32)> for ToggleLight {
-    fn draw(&self, _state: &SyrupState, canvas: &mut Canvas) {
+    fn draw(&self, state: &SyrupState, canvas: &mut Canvas) {
         let mut canvas = canvas.subcanvas(self.rect());
-        if self.hilight == 1 {
+        if let Some(color) = self.hilight_color() {
             draw_light(
                 &mut canvas,
                 0,
                 0,
                 (0, 0, 0),
                 MAX_LIGHT_RADIUS,
-                (255, 0, 0),
-            );
-        } else if self.hilight == 2 {
-            draw_light(
-                &mut canvas,
-                0,
-                0,
-                (0, 0, 0),
-                MAX_LIGHT_RADIUS,
-                (0, 255, 0),
-            );
-        } else if self.hilight == 3 {
-            draw_light(
-                &mut canvas,
-                0,
-                0,
-                (0, 0, 0),
-                MAX_LIGHT_RADIUS,
-                (0, 0, 255),
+                self.rgb(color),
             );
         } else if self.red_radius <= self.green_radius
             && self.red_radius <= self.blue_radius
@@ -253,18 +263,18 @@ impl Element<SyrupState, (i32, i32)> for ToggleLight {
                     &mut canvas,
                     self.red_radius,
                     self.green_radius,
-                    (0, 255, 255),
+                    self.rgb(MixedColor::Cyan),
                     self.blue_radius,
-                    (0, 0, 255),
+                    self.rgb(MixedColor::Blue),
                 );
             } else {
                 draw_light(
                     &mut canvas,
                     self.red_radius,
                     self.blue_radius,
-                    (0, 255, 255),
+                    self.rgb(MixedColor::Cyan),
                     self.green_radius,
-                    (0, 255, 0),
+                    self.rgb(MixedColor::Green),
                 );
             }
         } else if self.green_radius <= self.blue_radius {
@@ -274,18 +284,18 @@ impl Element<SyrupState, (i32, i32)> for ToggleLight {
                     &mut canvas,
                     self.green_radius,
                     self.red_radius,
-                    (255, 0, 255),
+                    self.rgb(MixedColor::Magenta),
                     self.blue_radius,
-                    (0, 0, 255),
+                    self.rgb(MixedColor::Blue),
                 );
             } else {
                 draw_light(
                     &mut canvas,
                     self.green_radius,
                     self.blue_radius,
-                    (255, 0, 255),
+                    self.rgb(MixedColor::Magenta),
                     self.red_radius,
-                    (255, 0, 0),
+                    self.rgb(MixedColor::Red),
                 );
             }
         } else {
@@ -295,18 +305,18 @@ impl Element<SyrupState, (i32, i32)> for ToggleLight {
                     &mut canvas,
                     self.blue_radius,
                     self.red_radius,
-                    (255, 255, 0),
+                    self.rgb(MixedColor::Yellow),
                     self.green_radius,
-                    (0, 255, 0),
+                    self.rgb(MixedColor::Green),
                 );
             } else {
                 draw_light(
                     &mut canvas,
                     self.blue_radius,
                     self.green_radius,
-                    (255, 255, 0),
+                    self.rgb(MixedColor::Yellow),
                     self.red_radius,
-                    (255, 0, 0),
+                    self.rgb(MixedColor::Red),
                 );
             }
         }
@@ -320,6 +330,20 @@ impl Element<SyrupState, (i32, i32)> for ToggleLight {
             );
         }
         canvas.draw_sprite_centered(&self.frame, center);
+        if self.color_blind {
+            let color = self.hilight_color().unwrap_or_else(|| {
+                let (red, green, blue) = state.light_colors(self.position);
+                MixedColor::from_rgb(red, green, blue)
+            });
+            if color != MixedColor::Black {
+                draw_color_glyph(
+                    &mut canvas,
+                    &self.glyph_font,
+                    center + Point::new(9, 9),
+                    color_glyph(color),
+                );
+            }
+        }
     }
 
     fn handle_event(
@@ -352,6 +376,8 @@ impl Element<SyrupState, (i32, i32)> for ToggleLight {
 struct NextColor {
     sprites: Vec<Sprite>,
     visible: bool,
+    color_blind: bool,
+    glyph_font: Rc<Font>,
 }
 
 impl NextColor {
@@ -359,6 +385,8 @@ impl NextColor {
         NextColor {
             sprites: resources.get_sprites("light/color"),
             visible: false,
+            color_blind: resources.color_blind_mode(),
+            glyph_font: resources.get_font("tiny"),
         }
     }
 }
@@ -367,13 +395,21 @@ impl Element<SyrupState, PuzzleCmd> for NextColor {
     fn draw(&self, state: &SyrupState, canvas: &mut Canvas) {
         if self.visible {
             canvas.fill_rect((0, 0, 127), Rect::new(454, 70, 36, 36));
-            let sprite_index = match state.next_color() {
-                PrimaryColor::Red => 0,
-                PrimaryColor::Green => 1,
-                PrimaryColor::Blue => 2,
+            let (sprite_index, color) = match state.next_color() {
+                PrimaryColor::Red => (0, MixedColor::Red),
+                PrimaryColor::Green => (1, MixedColor::Green),
+                PrimaryColor::Blue => (2, MixedColor::Blue),
             };
             canvas
                 .draw_sprite(&self.sprites[sprite_index], Point::new(461, 77));
+            if self.color_blind {
+                draw_color_glyph(
+                    canvas,
+                    &self.glyph_font,
+                    Point::new(472, 88),
+                    color_glyph(color),
+                );
+            }
         }
     }
 
@@ -458,3 +494,28 @@ $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::View;
+    use crate::gui::{Event, EventScript, KeyMod, Keycode, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    #[test]
+    fn hint_key_does_nothing_without_hints() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::LightSyrup;
+        game.light_syrup.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.light_syrup);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        events.extend(vec![Event::KeyDown(Keycode::F3, KeyMod::none())]);
+        assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        assert_eq!(game.hints_used(Location::LightSyrup), 0);
+    }
+}
+
+// ========================================================================= //
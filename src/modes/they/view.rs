@@ -193,3 +193,74 @@ Each button performs a different transformation.
 $M{Tap}{Click} on a character in the scene to hear their words of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{View, RETRY_DELAY};
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState};
+
+    fn click_button(index: i8) -> Event {
+        let (col, row) = (index as i32 % 3, index as i32 / 3);
+        Event::MouseDown(Point::new(136 + 80 * col, 56 + 48 * row))
+    }
+
+    /// Finds the order of buttons that solves the puzzle by trying every
+    /// one.
+    fn solution() -> Vec<i8> {
+        let solved_letters = {
+            let mut state = Game::new().the_y_factor;
+            state.solve();
+            state.letters().clone()
+        };
+        let mut state = Game::new().the_y_factor;
+        (0..6i32.pow(6))
+            .map(|n| (0..6).map(|i| (n / 6i32.pow(i) % 6) as i8).collect())
+            .filter(|seq: &Vec<i8>| (0..6).all(|index| seq.contains(&index)))
+            .find(|seq| {
+                state.set_sequence(seq.clone());
+                state.letters() == &solved_letters
+            })
+            .unwrap()
+    }
+
+    fn play_sequence(
+        view: &mut View,
+        game: &mut Game,
+        events: &mut EventScript,
+        sequence: &[i8],
+    ) {
+        for &index in sequence {
+            events.extend(vec![click_button(index), Event::MouseUp]);
+        }
+        let ticks = RETRY_DELAY as usize + 10;
+        events.extend(vec![Event::ClockTick; ticks]);
+        assert_eq!(play_puzzle(events, view, game), None);
+    }
+
+    #[test]
+    fn clicking_buttons_in_the_right_order_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::TheYFactor;
+        game.the_y_factor.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.the_y_factor);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let solution = solution();
+
+        // Using every button in the wrong order starts over.
+        let mut wrong = solution.clone();
+        wrong.reverse();
+        play_sequence(&mut view, &mut game, &mut events, &wrong);
+        assert!(!game.the_y_factor.is_solved());
+        assert!(game.the_y_factor.sequence().is_empty());
+
+        play_sequence(&mut view, &mut game, &mut events, &solution);
+        assert!(game.the_y_factor.is_solved());
+    }
+}
+
+// ========================================================================= //
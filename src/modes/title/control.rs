@@ -17,17 +17,43 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use crate::gui::{Element, Event, Window};
+use crate::gui::{Element, Event, Keycode, Window};
 use crate::modes::{run_info_box, Mode};
 use crate::save::SaveData;
 
-use super::view::{Cmd, ConfirmEraseView, View, ABOUT_BOX_TEXT};
+use super::view::{
+    recovery_text, Cmd, ConfirmView, SlotCmd, SlotNameView, SlotsView, View,
+    ABOUT_BOX_TEXT,
+};
 
 // ========================================================================= //
 
 pub fn run_title_screen(window: &mut Window, data: &mut SaveData) -> Mode {
+    window.set_text_section("title");
     let mut view = View::new(&mut window.resources());
     window.render(data, &view);
+    if let Some(recovery) = data.recovery() {
+        let text = recovery_text(recovery);
+        // Escape keeps the restored data rather than throwing it away.
+        let (buttons, cancel) = if recovery.restored_from.is_some() {
+            let buttons = vec![
+                ("Start Over".to_string(), false),
+                ("Restore".to_string(), true),
+            ];
+            (buttons, 1)
+        } else {
+            (vec![("OK".to_string(), true)], 0)
+        };
+        let confirmation =
+            confirm(window, &view, data, &text, buttons, cancel);
+        let keep_data = match confirmation {
+            Confirmation::Confirm(value) => value,
+            Confirmation::Quit => return Mode::Quit,
+        };
+        data.resolve_recovery(keep_data);
+        save_game(data);
+        window.render(data, &view);
+    }
     loop {
         let mut action = match window.next_event() {
             Event::Quit => return Mode::Quit,
@@ -40,23 +66,33 @@ pub fn run_title_screen(window: &mut Window, data: &mut SaveData) -> Mode {
                     return Mode::Location(game.location);
                 }
                 let location = data.start_new_game().location;
-                if let Err(error) = data.save_to_disk() {
-                    println!("Failed to save game: {}", error);
-                }
+                save_game(data);
                 return Mode::Location(location);
             }
             Some(&Cmd::EraseGame) => {
-                let confirmed = match confirm_erase(window, &view, data) {
+                let text = format!(
+                    "Really erase game data in {}?\nAll progress will be \
+                     lost!",
+                    data.current_slot()
+                );
+                let (buttons, cancel) = confirm_buttons("Erase");
+                let confirmation =
+                    confirm(window, &view, data, &text, buttons, cancel);
+                let confirmed = match confirmation {
                     Confirmation::Confirm(value) => value,
                     Confirmation::Quit => return Mode::Quit,
                 };
                 view.reset_buttons();
                 if confirmed {
                     data.erase_game();
-                    if let Err(error) = data.save_to_disk() {
-                        println!("Failed to save game: {}", error);
-                    }
+                    save_game(data);
+                }
+            }
+            Some(&Cmd::ManageSlots) => {
+                if !manage_slots(window, &view, data) {
+                    return Mode::Quit;
                 }
+                view.reset_buttons();
             }
             Some(&Cmd::ShowAboutBox) => {
                 if !run_info_box(window, &view, data, ABOUT_BOX_TEXT) {
@@ -64,6 +100,7 @@ pub fn run_title_screen(window: &mut Window, data: &mut SaveData) -> Mode {
                 }
                 view.reset_buttons();
             }
+            Some(&Cmd::ShowSettings) => return Mode::Settings(None),
             Some(&Cmd::Quit) => return Mode::Quit,
             None => {}
         }
@@ -73,21 +110,37 @@ pub fn run_title_screen(window: &mut Window, data: &mut SaveData) -> Mode {
     }
 }
 
+fn save_game(data: &mut SaveData) {
+    if let Err(error) = data.save_to_disk() {
+        println!("Failed to save game: {}", error);
+    }
+}
+
 // ========================================================================= //
 
-enum Confirmation {
-    Confirm(bool),
+enum Confirmation<T> {
+    Confirm(T),
     Quit,
 }
 
-fn confirm_erase(
+fn confirm(
     window: &mut Window,
     title_view: &View,
     data: &mut SaveData,
-) -> Confirmation {
+    text: &str,
+    buttons: Vec<(String, bool)>,
+    cancel: usize,
+) -> Confirmation<bool> {
     let mut view = {
         let visible = window.visible_rect();
-        ConfirmEraseView::new(&mut window.resources(), visible, title_view)
+        ConfirmView::new(
+            &mut window.resources(),
+            visible,
+            title_view,
+            text,
+            buttons,
+            cancel,
+        )
     };
     window.render(data, &view);
     loop {
@@ -103,4 +156,269 @@ fn confirm_erase(
     }
 }
 
+/// Returns the buttons for confirming a destructive action, along with the
+/// index of the Cancel button, which the Escape key should choose.
+fn confirm_buttons(confirm_label: &str) -> (Vec<(String, bool)>, usize) {
+    let buttons =
+        vec![("Cancel".to_string(), false), (confirm_label.to_string(), true)];
+    (buttons, 0)
+}
+
+// ========================================================================= //
+
+/// Runs the game slot dialog until the user closes it.  Returns false if the
+/// user quit the game instead.
+fn manage_slots(
+    window: &mut Window,
+    title_view: &View,
+    data: &mut SaveData,
+) -> bool {
+    loop {
+        let cmd = match choose_slot_cmd(window, title_view, data) {
+            Confirmation::Confirm(cmd) => cmd,
+            Confirmation::Quit => return false,
+        };
+        match cmd {
+            SlotCmd::Prev | SlotCmd::Next => {
+                let names: Vec<String> = data
+                    .slot_names()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                let index = names
+                    .iter()
+                    .position(|name| name == data.current_slot())
+                    .unwrap_or(0);
+                let index = if cmd == SlotCmd::Next {
+                    (index + 1) % names.len()
+                } else {
+                    (index + names.len() - 1) % names.len()
+                };
+                data.switch_slot(&names[index]);
+            }
+            SlotCmd::New => {
+                let default_name = data.unused_slot_name();
+                let prompt = "Name for the new game slot:";
+                let name = match enter_slot_name(
+                    window,
+                    title_view,
+                    data,
+                    prompt,
+                    default_name,
+                ) {
+                    Confirmation::Confirm(name) => name,
+                    Confirmation::Quit => return false,
+                };
+                if let Some(name) = name {
+                    if data.create_slot(&name) {
+                        data.switch_slot(&name);
+                    }
+                }
+            }
+            SlotCmd::Copy => {
+                let from = data.current_slot().to_string();
+                let default_name = data.unused_slot_name();
+                let prompt = format!("Name for the copy of {}:", from);
+                let name = match enter_slot_name(
+                    window,
+                    title_view,
+                    data,
+                    &prompt,
+                    default_name,
+                ) {
+                    Confirmation::Confirm(name) => name,
+                    Confirmation::Quit => return false,
+                };
+                if let Some(name) = name {
+                    if data.copy_slot(&from, &name) {
+                        data.switch_slot(&name);
+                    }
+                }
+            }
+            SlotCmd::Delete => {
+                let name = data.current_slot().to_string();
+                let text = format!(
+                    "Really delete game slot {}?\nAll progress in it will \
+                     be lost!",
+                    name
+                );
+                let (buttons, cancel) = confirm_buttons("Delete");
+                let confirmation =
+                    confirm(window, title_view, data, &text, buttons, cancel);
+                match confirmation {
+                    Confirmation::Confirm(true) => {
+                        data.delete_slot(&name);
+                    }
+                    Confirmation::Confirm(false) => {}
+                    Confirmation::Quit => return false,
+                }
+            }
+            SlotCmd::Done => return true,
+        }
+        save_game(data);
+    }
+}
+
+fn choose_slot_cmd(
+    window: &mut Window,
+    title_view: &View,
+    data: &mut SaveData,
+) -> Confirmation<SlotCmd> {
+    let mut view = {
+        let visible = window.visible_rect();
+        SlotsView::new(&mut window.resources(), visible, title_view, data)
+    };
+    window.render(data, &view);
+    loop {
+        let mut action = match window.next_event() {
+            Event::Quit => return Confirmation::Quit,
+            Event::KeyDown(Keycode::Escape, _) => {
+                return Confirmation::Confirm(SlotCmd::Done);
+            }
+            event => view.handle_event(&event, data),
+        };
+        window.play_sounds(action.drain_sounds());
+        if let Some(&cmd) = action.value() {
+            return Confirmation::Confirm(cmd);
+        } else if action.should_redraw() {
+            window.render(data, &view);
+        }
+    }
+}
+
+/// Prompts the user to type a slot name.  Returns `None` if the user cancels.
+fn enter_slot_name(
+    window: &mut Window,
+    title_view: &View,
+    data: &mut SaveData,
+    prompt: &str,
+    mut name: String,
+) -> Confirmation<Option<String>> {
+    loop {
+        let mut view = {
+            let visible = window.visible_rect();
+            SlotNameView::new(
+                &mut window.resources(),
+                visible,
+                title_view,
+                prompt,
+                &name,
+            )
+        };
+        window.render(data, &view);
+        loop {
+            let action = match window.next_event() {
+                Event::Quit => return Confirmation::Quit,
+                Event::TextInput(text) => {
+                    let max_len = SaveData::max_slot_name_len();
+                    for chr in text.chars() {
+                        if SaveData::is_valid_slot_name_char(chr)
+                            && name.chars().count() < max_len
+                        {
+                            name.push(chr);
+                        }
+                    }
+                    break;
+                }
+                Event::KeyDown(Keycode::Backspace, _) => {
+                    name.pop();
+                    break;
+                }
+                Event::KeyDown(Keycode::Return, _) => {
+                    return Confirmation::Confirm(valid_slot_name(&name));
+                }
+                Event::KeyDown(Keycode::Escape, _) => {
+                    return Confirmation::Confirm(None);
+                }
+                event => view.handle_event(&event, data),
+            };
+            match action.value() {
+                Some(&true) => {
+                    return Confirmation::Confirm(valid_slot_name(&name));
+                }
+                Some(&false) => return Confirmation::Confirm(None),
+                None => {}
+            }
+            if action.should_redraw() {
+                window.render(data, &view);
+            }
+        }
+    }
+}
+
+fn valid_slot_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    if SaveData::is_valid_slot_name(name) {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_title_screen;
+    use crate::gui::{Announcer, Event, KeyMod, Keycode, Window};
+    use crate::modes::Mode;
+    use crate::save::SaveData;
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown(keycode, KeyMod::none())
+    }
+
+    fn run_with_keys(keys: &[Keycode]) -> Mode {
+        let mut window = Window::headless((576, 384));
+        let path = std::env::temp_dir().join("syzygy-title/save.toml");
+        let mut data = SaveData::new(path);
+        let mut events = vec![Event::ClockTick; 100];
+        events.extend(keys.iter().map(|&keycode| key(keycode)));
+        events.extend(vec![Event::ClockTick; 100]);
+        window.queue_events(events);
+        run_title_screen(&mut window, &mut data)
+    }
+
+    #[test]
+    fn keys_choose_title_buttons() {
+        let mode = run_with_keys(&[Keycode::Return]);
+        assert!(matches!(mode, Mode::Location(_)));
+        // The first arrow key press only shows the focus.
+        let mode =
+            run_with_keys(&[Keycode::Left, Keycode::Left, Keycode::Return]);
+        assert!(matches!(mode, Mode::Settings(None)));
+    }
+
+    #[test]
+    fn focused_buttons_are_announced() {
+        let mut window = Window::headless((576, 384));
+        let (announcer, spoken) = Announcer::capture();
+        window.set_announcer(announcer);
+        let path = std::env::temp_dir().join("syzygy-title/save.toml");
+        let mut data = SaveData::new(path);
+        window.queue_events(vec![Event::ClockTick; 100]);
+        window.queue_events(vec![
+            key(Keycode::Left),
+            key(Keycode::Left),
+            key(Keycode::Down),
+        ]);
+        run_title_screen(&mut window, &mut data);
+        let spoken: Vec<String> = spoken.try_iter().collect();
+        assert_eq!(spoken, vec!["New Game", "Settings", "About"]);
+    }
+
+    #[test]
+    fn about_box_closes_with_keys() {
+        // Open the About box and close it, then go to the settings.
+        let mode = run_with_keys(&[
+            Keycode::Down,
+            Keycode::Down,
+            Keycode::Left,
+            Keycode::Return,
+            Keycode::Escape,
+            Keycode::Up,
+            Keycode::Return,
+        ]);
+        assert!(matches!(mode, Mode::Settings(None)));
+    }
+}
+
 // ========================================================================= //
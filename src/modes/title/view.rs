@@ -19,12 +19,18 @@
 
 use std::rc::Rc;
 
-use crate::elements::{DialogBox, FadeStyle, ScreenFade};
+use crate::elements::{
+    nearest_in_direction, DialogBox, FadeStyle, ScreenFade,
+};
 use crate::gui::{
-    Action, Align, Background, Canvas, Element, Event, Font, Point, Rect,
-    Resources, Sound, Sprite,
+    Action, Align, Announcer, Background, Canvas, Element, Event, Font,
+    Keycode, Point, Rect, Resources, Sound, Sprite,
 };
-use crate::save::SaveData;
+use crate::save::{Direction, SaveData, SaveRecovery};
+
+// ========================================================================= //
+
+const FOCUS_RING_COLOR: (u8, u8, u8) = (255, 255, 128);
 
 // ========================================================================= //
 
@@ -32,10 +38,22 @@ use crate::save::SaveData;
 pub enum Cmd {
     StartGame,
     EraseGame,
+    ManageSlots,
     ShowAboutBox,
+    ShowSettings,
     Quit,
 }
 
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum SlotCmd {
+    Prev,
+    Next,
+    New,
+    Copy,
+    Delete,
+    Done,
+}
+
 // ========================================================================= //
 
 pub struct View {
@@ -48,6 +66,9 @@ pub struct View {
     buttons: Vec<Button>,
     title_font_1: Rc<Font>,
     title_font_2: Rc<Font>,
+    slot_font: Rc<Font>,
+    focus: Option<usize>,
+    announcer: Announcer,
 }
 
 impl View {
@@ -55,7 +76,7 @@ impl View {
         let center_x = 288;
         let upper_y = 226;
         let lower_y = 302;
-        let spacing = 128;
+        let spacing = 112;
         View {
             screen_fade: ScreenFade::new(
                 resources,
@@ -75,22 +96,35 @@ impl View {
                 ),
                 Button::new(
                     resources,
-                    Point::new(center_x - spacing, lower_y),
+                    Point::new(center_x - 3 * spacing / 2, upper_y),
+                    Cmd::ShowSettings,
+                ),
+                Button::new(
+                    resources,
+                    Point::new(center_x - 3 * spacing / 2, lower_y),
                     Cmd::ShowAboutBox,
                 ),
                 Button::new(
                     resources,
-                    Point::new(center_x, lower_y),
+                    Point::new(center_x - spacing / 2, lower_y),
+                    Cmd::ManageSlots,
+                ),
+                Button::new(
+                    resources,
+                    Point::new(center_x + spacing / 2, lower_y),
                     Cmd::EraseGame,
                 ),
                 Button::new(
                     resources,
-                    Point::new(center_x + spacing, lower_y),
+                    Point::new(center_x + 3 * spacing / 2, lower_y),
                     Cmd::Quit,
                 ),
             ],
             title_font_1: resources.get_font("title1"),
             title_font_2: resources.get_font("title2"),
+            slot_font: resources.get_font("roman"),
+            focus: None,
+            announcer: resources.announcer(),
         }
     }
 
@@ -99,6 +133,29 @@ impl View {
             button.active = false;
         }
     }
+
+    /// Moves the keyboard focus to the nearest button in the given direction,
+    /// or to the first button if none has the focus yet.
+    fn move_focus(&mut self, data: &SaveData, dir: Direction) -> Action<Cmd> {
+        let centers: Vec<(usize, Point)> = self
+            .buttons
+            .iter()
+            .enumerate()
+            .filter(|&(_, button)| button.is_shown(data))
+            .map(|(index, button)| (index, button.center))
+            .collect();
+        self.focus = match self.focus {
+            Some(index) => {
+                let from = self.buttons[index].center;
+                nearest_in_direction(&centers, from, dir).or(Some(index))
+            }
+            None => Some(0),
+        };
+        if let Some(index) = self.focus {
+            self.announcer.announce(self.buttons[index].label(data));
+        }
+        Action::redraw().and_stop()
+    }
 }
 
 impl Element<SaveData, Cmd> for View {
@@ -131,7 +188,26 @@ impl Element<SaveData, Cmd> for View {
             Point::new(288, 165),
             "SYZYGY",
         );
+        canvas.draw_text(
+            &self.slot_font,
+            Align::Center,
+            Point::new(288, 258),
+            data.current_slot(),
+        );
         self.buttons.draw(data, canvas);
+        let focused = self.focus.map(|index| &self.buttons[index]);
+        if let Some(button) = focused.filter(|button| button.is_shown(data)) {
+            let rect = button.rect();
+            canvas.draw_rect(
+                FOCUS_RING_COLOR,
+                Rect::new(
+                    rect.x() - 2,
+                    rect.y() - 2,
+                    rect.width() + 4,
+                    rect.height() + 4,
+                ),
+            );
+        }
         self.screen_fade.draw(&(), canvas);
     }
 
@@ -142,9 +218,43 @@ impl Element<SaveData, Cmd> for View {
     ) -> Action<Cmd> {
         let mut action = self.screen_fade.handle_event(event, &mut ());
         if !action.should_stop() {
-            let mut subaction = self.buttons.handle_event(event, data);
+            let mut subaction = match event {
+                &Event::KeyDown(Keycode::Up, _) => {
+                    self.move_focus(data, Direction::North)
+                }
+                &Event::KeyDown(Keycode::Down, _) => {
+                    self.move_focus(data, Direction::South)
+                }
+                &Event::KeyDown(Keycode::Left, _) => {
+                    self.move_focus(data, Direction::West)
+                }
+                &Event::KeyDown(Keycode::Right, _) => {
+                    self.move_focus(data, Direction::East)
+                }
+                &Event::KeyDown(Keycode::Return, _)
+                | &Event::KeyDown(Keycode::Space, _) => {
+                    // With nothing focused yet, Return starts the game.
+                    let button = &mut self.buttons[self.focus.unwrap_or(0)];
+                    if button.is_shown(data) {
+                        button.press()
+                    } else {
+                        Action::ignore()
+                    }
+                }
+                &Event::MouseDown(_) => {
+                    let mut subaction = self.buttons.handle_event(event, data);
+                    if self.focus.take().is_some() {
+                        subaction.also_redraw();
+                    }
+                    subaction
+                }
+                _ => self.buttons.handle_event(event, data),
+            };
             if let Some(&cmd) = subaction.value() {
-                if cmd == Cmd::StartGame || cmd == Cmd::Quit {
+                if cmd == Cmd::StartGame
+                    || cmd == Cmd::ShowSettings
+                    || cmd == Cmd::Quit
+                {
                     self.screen_fade.fade_out_and_return(cmd);
                     subaction = subaction.but_no_value();
                 }
@@ -179,27 +289,39 @@ impl Button {
     fn rect(&self) -> Rect {
         Rect::new(self.center.x() - 48, self.center.y() - 12, 96, 24)
     }
+
+    fn label(&self, data: &SaveData) -> &'static str {
+        match self.command {
+            Cmd::StartGame if data.game().is_none() => "New Game",
+            Cmd::StartGame => "Continue",
+            Cmd::ManageSlots => "Game Slots",
+            Cmd::ShowSettings => "Settings",
+            Cmd::ShowAboutBox => "About",
+            Cmd::EraseGame => "Erase Game",
+            Cmd::Quit => "Quit",
+        }
+    }
+
+    fn is_shown(&self, data: &SaveData) -> bool {
+        self.command != Cmd::EraseGame || data.game().is_some()
+    }
+
+    fn press(&mut self) -> Action<Cmd> {
+        self.active = true;
+        Action::redraw().and_play_sound(Sound::beep()).and_return(self.command)
+    }
 }
 
 impl Element<SaveData, Cmd> for Button {
     fn draw(&self, data: &SaveData, canvas: &mut Canvas) {
-        let (mut sprite_index, label, x_offset) = match self.command {
-            Cmd::StartGame => {
-                let label = if data.game().is_none() {
-                    "New Game"
-                } else {
-                    "Continue"
-                };
-                (0, label, 0)
-            }
-            Cmd::ShowAboutBox => (2, "About", 4),
-            Cmd::EraseGame => {
-                if data.game().is_none() {
-                    return;
-                }
-                (4, "Erase Game", 0)
-            }
-            Cmd::Quit => (6, "Quit", -4),
+        if !self.is_shown(data) {
+            return;
+        }
+        let (mut sprite_index, x_offset) = match self.command {
+            Cmd::StartGame | Cmd::ManageSlots | Cmd::ShowSettings => (0, 0),
+            Cmd::ShowAboutBox => (2, 4),
+            Cmd::EraseGame => (4, 0),
+            Cmd::Quit => (6, -4),
         };
         if self.active {
             sprite_index += 1;
@@ -209,7 +331,7 @@ impl Element<SaveData, Cmd> for Button {
             &self.font,
             Align::Center,
             self.center + Point::new(x_offset, 4),
-            label,
+            self.label(data),
         );
     }
 
@@ -218,15 +340,12 @@ impl Element<SaveData, Cmd> for Button {
         event: &Event,
         data: &mut SaveData,
     ) -> Action<Cmd> {
-        if self.command == Cmd::EraseGame && data.game().is_none() {
+        if !self.is_shown(data) {
             return Action::ignore();
         }
         match event {
             &Event::MouseDown(pt) if self.rect().contains_point(pt) => {
-                self.active = true;
-                Action::redraw()
-                    .and_play_sound(Sound::beep())
-                    .and_return(self.command)
+                self.press()
             }
             _ => Action::ignore(),
         }
@@ -254,26 +373,151 @@ $CThanks for playing!");
 
 // ========================================================================= //
 
-pub struct ConfirmEraseView<'a> {
+const MAX_RECOVERY_ISSUES_SHOWN: usize = 6;
+
+pub fn recovery_text(recovery: &SaveRecovery) -> String {
+    let file_name = recovery
+        .set_aside
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('$', "$$"))
+        .unwrap_or_default();
+    let mut text = format!(
+        "Your save file could not be read completely.\n\
+         A copy of it has been kept as $i{}$r\n",
+        file_name
+    );
+    if recovery.restored_from.is_some() {
+        text.push_str(
+            "\nYour progress has been restored from the most recent\n\
+             backup that could be read.  Restore it, or start over?",
+        );
+    } else if recovery.issues.is_empty() {
+        text.push_str("\nA new save file has been started.");
+    }
+    if !recovery.issues.is_empty() {
+        text.push_str("\nThe following saved data had to be reset:");
+        for issue in recovery.issues.iter().take(MAX_RECOVERY_ISSUES_SHOWN) {
+            text.push_str(&format!("\n  {}", issue).replace('$', "$$"));
+        }
+        if recovery.issues.len() > MAX_RECOVERY_ISSUES_SHOWN {
+            let num_more = recovery.issues.len() - MAX_RECOVERY_ISSUES_SHOWN;
+            text.push_str(&format!("\n  ...and {} more", num_more));
+        }
+    }
+    text
+}
+
+pub struct ConfirmView<'a> {
+    title_view: &'a View,
+    dialog: DialogBox<bool>,
+}
+
+impl<'a> ConfirmView<'a> {
+    pub fn new(
+        resources: &mut Resources,
+        visible: Rect,
+        title_view: &'a View,
+        text: &str,
+        buttons: Vec<(String, bool)>,
+        cancel: usize,
+    ) -> ConfirmView<'a> {
+        let mut dialog = DialogBox::new(resources, visible, text, buttons);
+        dialog.set_cancel_button(cancel);
+        ConfirmView { title_view, dialog }
+    }
+}
+
+impl<'a> Element<SaveData, bool> for ConfirmView<'a> {
+    fn draw(&self, data: &SaveData, canvas: &mut Canvas) {
+        self.title_view.draw(data, canvas);
+        self.dialog.draw(&(), canvas);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        _data: &mut SaveData,
+    ) -> Action<bool> {
+        self.dialog.handle_event(event, &mut ())
+    }
+}
+
+// ========================================================================= //
+
+pub struct SlotsView<'a> {
+    title_view: &'a View,
+    dialog: DialogBox<SlotCmd>,
+}
+
+impl<'a> SlotsView<'a> {
+    pub fn new(
+        resources: &mut Resources,
+        visible: Rect,
+        title_view: &'a View,
+        data: &SaveData,
+    ) -> SlotsView<'a> {
+        let mut text = "$CGame slots$L\n".to_string();
+        for &name in data.slot_names().iter() {
+            let marker = if name == data.current_slot() { "> " } else { "" };
+            let status =
+                if data.slot_has_game(name) { "In progress" } else { "Empty" };
+            text.push_str(&format!("\n{}{}$R{}$L", marker, name, status));
+        }
+        let mut buttons = vec![
+            ("<".to_string(), SlotCmd::Prev),
+            (">".to_string(), SlotCmd::Next),
+            ("New".to_string(), SlotCmd::New),
+            ("Copy".to_string(), SlotCmd::Copy),
+        ];
+        if data.slot_names().len() > 1 {
+            buttons.push(("Delete".to_string(), SlotCmd::Delete));
+        }
+        buttons.push(("Done".to_string(), SlotCmd::Done));
+        let dialog = DialogBox::new(resources, visible, &text, buttons);
+        SlotsView { title_view, dialog }
+    }
+}
+
+impl<'a> Element<SaveData, SlotCmd> for SlotsView<'a> {
+    fn draw(&self, data: &SaveData, canvas: &mut Canvas) {
+        self.title_view.draw(data, canvas);
+        self.dialog.draw(&(), canvas);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        _data: &mut SaveData,
+    ) -> Action<SlotCmd> {
+        self.dialog.handle_event(event, &mut ())
+    }
+}
+
+// ========================================================================= //
+
+pub struct SlotNameView<'a> {
     title_view: &'a View,
     dialog: DialogBox<bool>,
 }
 
-impl<'a> ConfirmEraseView<'a> {
+impl<'a> SlotNameView<'a> {
     pub fn new(
         resources: &mut Resources,
         visible: Rect,
         title_view: &'a View,
-    ) -> ConfirmEraseView<'a> {
-        let text = "Really erase game data?\nAll progress will be lost!";
+        prompt: &str,
+        name: &str,
+    ) -> SlotNameView<'a> {
+        let text = format!("{}\n\n$C{}_", prompt, name);
         let buttons =
-            vec![("Cancel".to_string(), false), ("Erase".to_string(), true)];
-        let dialog = DialogBox::new(resources, visible, text, buttons);
-        ConfirmEraseView { title_view, dialog }
+            vec![("Cancel".to_string(), false), ("OK".to_string(), true)];
+        let mut dialog = DialogBox::new(resources, visible, &text, buttons);
+        dialog.set_cancel_button(0);
+        SlotNameView { title_view, dialog }
     }
 }
 
-impl<'a> Element<SaveData, bool> for ConfirmEraseView<'a> {
+impl<'a> Element<SaveData, bool> for SlotNameView<'a> {
     fn draw(&self, data: &SaveData, canvas: &mut Canvas) {
         self.title_view.draw(data, canvas);
         self.dialog.draw(&(), canvas);
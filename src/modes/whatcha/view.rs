@@ -159,3 +159,92 @@ $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
 // ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use super::View;
+    use crate::gui::{Event, EventScript, Point, Window};
+    use crate::modes::play_puzzle;
+    use crate::save::{Game, Location, PuzzleState, WhatchaState};
+
+    fn positions(state: &WhatchaState) -> Vec<i32> {
+        let columns = state.columns();
+        (0..columns.num_columns())
+            .map(|col| columns.column_position(col))
+            .collect()
+    }
+
+    /// Returns the fewest column rotations that solve the puzzle, found by a
+    /// breadth-first search over column positions.
+    fn solution(state: &WhatchaState) -> Vec<(usize, i32)> {
+        let columns = state.columns();
+        let goal = {
+            let mut solved = Game::new().whatcha_column;
+            solved.solve();
+            positions(&solved)
+        };
+        let mut came_from = HashMap::new();
+        let mut queue = VecDeque::new();
+        came_from.insert(positions(state), None);
+        queue.push_back(positions(state));
+        while let Some(current) = queue.pop_front() {
+            if current == goal {
+                let mut moves = Vec::new();
+                let mut node = current;
+                while let Some((prev, rotation)) = came_from[&node].clone() {
+                    moves.push(rotation);
+                    node = prev;
+                }
+                moves.reverse();
+                return moves;
+            }
+            for col in 0..columns.num_columns() {
+                for by in 1..(columns.column_word_len(col) as i32) {
+                    let mut next = current.clone();
+                    for &(other, factor) in columns.column_linkages(col) {
+                        let len = columns.column_word_len(other) as i32;
+                        next[other] =
+                            (next[other] - by * factor).rem_euclid(len);
+                    }
+                    if !came_from.contains_key(&next) {
+                        came_from.insert(
+                            next.clone(),
+                            Some((current.clone(), (col, by))),
+                        );
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        panic!("No solution from {:?}", positions(state));
+    }
+
+    #[test]
+    fn dragging_columns_solves_puzzle() {
+        let mut window = Window::headless((576, 384));
+        let visible = window.visible_rect();
+        let mut game = Game::new();
+        game.location = Location::WhatchaColumn;
+        game.whatcha_column.visit();
+        let mut view =
+            View::new(&mut window.resources(), visible, &game.whatcha_column);
+        let mut events = EventScript::new(vec![Event::ClockTick; 20]);
+        let moves = solution(&game.whatcha_column);
+        assert!(!moves.is_empty());
+        for &(col, by) in moves.iter() {
+            assert!(!game.whatcha_column.is_solved());
+            // Drag the column's top letter down (or up) by whole letters.
+            let offset = game.whatcha_column.columns().column_offset(col);
+            let start = Point::new(290 + 32 * col as i32, 120 + 24 * offset);
+            events.extend(vec![
+                Event::MouseDown(start),
+                Event::MouseDrag(start.offset(0, 24 * by)),
+                Event::MouseUp,
+            ]);
+            assert_eq!(play_puzzle(&mut events, &mut view, &mut game), None);
+        }
+        assert!(game.whatcha_column.is_solved());
+    }
+}
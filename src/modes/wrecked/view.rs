@@ -21,7 +21,10 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use super::scenes;
-use crate::elements::{FadeStyle, PuzzleCmd, PuzzleCore, PuzzleView};
+use crate::elements::{
+    CursorCmd, CursorGrid, CursorStyle, FadeStyle, GridCursor, PuzzleCmd,
+    PuzzleCore, PuzzleView,
+};
 use crate::gui::{
     Action, Align, Canvas, Element, Event, Font, Point, Rect, Resources,
     Sprite,
@@ -238,11 +241,14 @@ impl Drag {
 
 const GRID_LEFT: i32 = 84;
 const GRID_TOP: i32 = 132;
+const NUM_COLS: i32 = 9;
+const NUM_ROWS: i32 = 7;
 
 pub struct WreckedGrid {
     tile_sprites: Vec<Sprite>,
     hole_sprites: Vec<Sprite>,
     drag: Option<Drag>,
+    cursor: GridCursor,
     font: Rc<Font>,
     letters: HashMap<(i32, i32), char>,
 }
@@ -253,12 +259,19 @@ impl WreckedGrid {
             tile_sprites: resources.get_sprites("wrecked/tiles"),
             hole_sprites: resources.get_sprites("wrecked/holes"),
             drag: None,
+            cursor: GridCursor::new(
+                GRID_LEFT,
+                GRID_TOP,
+                TILE_SIZE,
+                CursorStyle::Push,
+            ),
             font: resources.get_font("danger"),
             letters: HashMap::new(),
         }
     }
 
     fn clear_drag(&mut self, state: &mut WreckedState) {
+        self.cursor.release();
         if let Some(drag) = self.drag.take() {
             if let Some((dir, rank, by)) = drag.accum() {
                 state.shift_tiles(dir, rank, -by);
@@ -269,9 +282,9 @@ impl WreckedGrid {
 
 impl Element<WreckedState, (Direction, i32, i32)> for WreckedGrid {
     fn draw(&self, state: &WreckedState, canvas: &mut Canvas) {
-        for row in 0..7 {
+        for row in 0..NUM_ROWS {
             let top = GRID_TOP + row * TILE_SIZE;
-            for col in 0..9 {
+            for col in 0..NUM_COLS {
                 if state.tile_at(col, row).is_none() {
                     let left = GRID_LEFT + col * TILE_SIZE;
                     let rect = Rect::new(left, top, TILE_USIZE, TILE_USIZE);
@@ -280,9 +293,9 @@ impl Element<WreckedState, (Direction, i32, i32)> for WreckedGrid {
                 }
             }
         }
-        for row in 0..7 {
+        for row in 0..NUM_ROWS {
             let top = GRID_TOP + row * TILE_SIZE;
-            for col in 0..9 {
+            for col in 0..NUM_COLS {
                 let left = GRID_LEFT + col * TILE_SIZE;
                 let mut pt = Point::new(left, top);
                 if let Some(ref drag) = self.drag {
@@ -303,6 +316,7 @@ impl Element<WreckedState, (Direction, i32, i32)> for WreckedGrid {
                 }
             }
         }
+        self.cursor.draw(state, canvas);
     }
 
     fn handle_event(
@@ -310,8 +324,45 @@ impl Element<WreckedState, (Direction, i32, i32)> for WreckedGrid {
         event: &Event,
         state: &mut WreckedState,
     ) -> Action<(Direction, i32, i32)> {
-        let rect =
-            Rect::new(GRID_LEFT, GRID_TOP, 9 * TILE_USIZE, 7 * TILE_USIZE);
+        if self.drag.is_none() {
+            let action = self.cursor.handle_event(event, state);
+            if action.should_stop() {
+                return match action.value() {
+                    Some(_) if state.is_solved() => Action::ignore(),
+                    Some(&CursorCmd::Push(coords, dir)) => {
+                        let rank = if dir.is_vertical() {
+                            coords.x()
+                        } else {
+                            coords.y()
+                        };
+                        state.shift_tiles(dir, rank, 1);
+                        let mut next = coords;
+                        loop {
+                            next += dir.delta();
+                            next = Point::new(
+                                next.x().rem_euclid(NUM_COLS),
+                                next.y().rem_euclid(NUM_ROWS),
+                            );
+                            if state.tile_at(next.x(), next.y()).is_some() {
+                                break;
+                            }
+                        }
+                        self.cursor.follow(next);
+                        if state.is_solved() {
+                            self.cursor.release();
+                        }
+                        action.but_return((dir, rank, 1))
+                    }
+                    _ => action.but_no_value(),
+                };
+            }
+        }
+        let rect = Rect::new(
+            GRID_LEFT,
+            GRID_TOP,
+            NUM_COLS as u32 * TILE_USIZE,
+            NUM_ROWS as u32 * TILE_USIZE,
+        );
         match event {
             &Event::MouseDown(pt) if !state.is_solved() => {
                 if rect.contains_point(pt) {
@@ -445,6 +496,16 @@ impl Element<WreckedState, PuzzleCmd> for WreckedDisplay {
     }
 }
 
+impl CursorGrid for WreckedState {
+    fn grid_size(&self) -> (i32, i32) {
+        (NUM_COLS, NUM_ROWS)
+    }
+
+    fn can_pick_up(&self, coords: Point) -> bool {
+        !self.is_solved() && self.tile_at(coords.x(), coords.y()).is_some()
+    }
+}
+
 // ========================================================================= //
 
 const LETTERS: &[(i32, i32, char)] = &[
@@ -466,4 +527,57 @@ to shift that whole row or column.
 $M{Tap}{Click} on a character in the scene to hear their words
 of wisdom.";
 
+#[cfg(test)]
+mod tests {
+    use super::{WreckedGrid, NUM_COLS, NUM_ROWS};
+    use crate::gui::{Element, Event, KeyMod, Keycode, Window};
+    use crate::save::{Access, Direction, PuzzleState, WreckedState};
+
+    fn tiles(state: &WreckedState) -> Vec<Option<usize>> {
+        let mut tiles = Vec::new();
+        for row in 0..NUM_ROWS {
+            for col in 0..NUM_COLS {
+                tiles.push(state.tile_at(col, row));
+            }
+        }
+        tiles
+    }
+
+    #[test]
+    fn arrow_keys_do_nothing_once_solved() {
+        let mut window = Window::headless((576, 384));
+        let mut grid = WreckedGrid::new(&mut window.resources());
+        let mut state = WreckedState::new();
+        state.solve();
+        let (col, row) = (0..NUM_ROWS)
+            .flat_map(|row| (0..NUM_COLS).map(move |col| (col, row)))
+            .find(|&(col, row)| state.tile_at(col, row).is_some())
+            .unwrap();
+        state.shift_tiles(Direction::West, row, 1);
+        *state.access_mut() = Access::Unsolved;
+
+        // Pick up a tile in the shifted row and push it back to solve the
+        // puzzle.
+        let mut keys = vec![Keycode::Right];
+        keys.extend(vec![Keycode::Right; col as usize]);
+        keys.extend(vec![Keycode::Down; row as usize]);
+        keys.extend(vec![Keycode::Return, Keycode::Right]);
+        for keycode in keys {
+            let event = Event::KeyDown(keycode, KeyMod::none());
+            grid.handle_event(&event, &mut state);
+        }
+        assert!(state.is_solved());
+        assert_eq!(grid.cursor.held(), None);
+        let solved = tiles(&state);
+
+        // Further arrow keys leave the solved grid alone.
+        for &keycode in &[Keycode::Right, Keycode::Down] {
+            let event = Event::KeyDown(keycode, KeyMod::none());
+            let action = grid.handle_event(&event, &mut state);
+            assert!(action.value().is_none());
+        }
+        assert_eq!(tiles(&state), solved);
+    }
+}
+
 // ========================================================================= //
@@ -0,0 +1,225 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use toml;
+
+use crate::gui::{Event, KeyMod, Keycode, Point};
+use crate::save::util::{pop_array, to_table, Tomlable};
+use crate::save::Location;
+
+// ========================================================================= //
+
+const EVENTS_KEY: &str = "events";
+const FRAME_KEY: &str = "frame";
+const KEY_KEY: &str = "key";
+const KIND_KEY: &str = "kind";
+const MODS_KEY: &str = "mods";
+const POINT_KEY: &str = "pt";
+const SAVE_KEY: &str = "save";
+const SEED_KEY: &str = "seed";
+const START_KEY: &str = "start";
+const TEXT_KEY: &str = "text";
+
+// ========================================================================= //
+
+/// A session recorded with `--record`: the save data and random seed that
+/// the session started with, followed by every event the game saw.
+pub struct Recording {
+    pub seed: u32,
+    pub start: Option<Location>,
+    pub save: toml::Value,
+    pub events: Vec<Event>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> io::Result<Recording> {
+        let string = fs::read_to_string(path)?;
+        let value = string.parse::<toml::Value>().map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+        })?;
+        Ok(Recording::from_toml(value))
+    }
+
+    fn from_toml(value: toml::Value) -> Recording {
+        let mut table = to_table(value);
+        let seed = u32::pop_from_table(&mut table, SEED_KEY);
+        let start = table
+            .remove(START_KEY)
+            .and_then(|value| value.as_str().and_then(Location::from_key));
+        let save = table
+            .remove(SAVE_KEY)
+            .unwrap_or(toml::Value::Table(toml::value::Table::new()));
+        let events = pop_array(&mut table, EVENTS_KEY)
+            .into_iter()
+            .filter_map(event_from_toml)
+            .collect();
+        Recording { seed, start, save, events }
+    }
+}
+
+// ========================================================================= //
+
+/// Writes a recording to disk as the session goes, so that it survives the
+/// game crashing.
+pub struct Recorder {
+    file: fs::File,
+    frame: i64,
+}
+
+impl Recorder {
+    pub fn create(
+        path: &Path,
+        seed: u32,
+        start: Option<Location>,
+        save: toml::Value,
+    ) -> io::Result<Recorder> {
+        let mut table = toml::value::Table::new();
+        table.insert(SEED_KEY.to_string(), seed.to_toml());
+        if let Some(location) = start {
+            table.insert(START_KEY.to_string(), location.to_toml());
+        }
+        table.insert(SAVE_KEY.to_string(), save);
+        let mut file = fs::File::create(path)?;
+        file.write_all(toml::Value::Table(table).to_string().as_bytes())?;
+        Ok(Recorder { file, frame: 0 })
+    }
+
+    /// Appends an event to the recording, tagged with the index of the frame
+    /// (i.e. the number of clock ticks before it) that it happened in.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        let entry = event_to_toml(self.frame, event);
+        write!(self.file, "\n[[{}]]\n{}", EVENTS_KEY, entry)?;
+        self.file.flush()?;
+        if *event == Event::ClockTick {
+            self.frame += 1;
+        }
+        Ok(())
+    }
+}
+
+// ========================================================================= //
+
+fn event_to_toml(frame: i64, event: &Event) -> toml::Value {
+    let mut table = toml::value::Table::new();
+    table.insert(FRAME_KEY.to_string(), toml::Value::Integer(frame));
+    let kind = match *event {
+        Event::Quit => "quit",
+        Event::ClockTick => "tick",
+        Event::MouseDrag(pt) => {
+            table.insert(POINT_KEY.to_string(), pt.to_toml());
+            "drag"
+        }
+        Event::MouseDown(pt) => {
+            table.insert(POINT_KEY.to_string(), pt.to_toml());
+            "down"
+        }
+        Event::MouseUp => "up",
+        Event::KeyDown(keycode, keymod) => {
+            table.insert(
+                KEY_KEY.to_string(),
+                toml::Value::Integer(keycode as i64),
+            );
+            table.insert(
+                MODS_KEY.to_string(),
+                toml::Value::Integer(keymod.bits() as i64),
+            );
+            "key"
+        }
+        Event::TextInput(ref text) => {
+            table.insert(TEXT_KEY.to_string(), text.to_toml());
+            "text"
+        }
+    };
+    table.insert(KIND_KEY.to_string(), kind.to_string().to_toml());
+    toml::Value::Table(table)
+}
+
+fn event_from_toml(value: toml::Value) -> Option<Event> {
+    let mut table = to_table(value);
+    match String::pop_from_table(&mut table, KIND_KEY).as_str() {
+        "quit" => Some(Event::Quit),
+        "tick" => Some(Event::ClockTick),
+        "drag" => Some(Event::MouseDrag(Point::pop_from_table(
+            &mut table, POINT_KEY,
+        ))),
+        "down" => Some(Event::MouseDown(Point::pop_from_table(
+            &mut table, POINT_KEY,
+        ))),
+        "up" => Some(Event::MouseUp),
+        "key" => {
+            let keycode = i32::pop_from_table(&mut table, KEY_KEY);
+            let bits = u8::pop_from_table(&mut table, MODS_KEY);
+            Keycode::from_i32(keycode)
+                .map(|key| Event::KeyDown(key, KeyMod::from_bits(bits)))
+        }
+        "text" => Some(Event::TextInput(String::pop_from_table(
+            &mut table, TEXT_KEY,
+        ))),
+        _ => None,
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use toml;
+
+    use super::{Recorder, Recording};
+    use crate::gui::{Event, KeyMod, Keycode, Point};
+    use crate::save::Location;
+
+    #[test]
+    fn recording_round_trip() {
+        let events = vec![
+            Event::ClockTick,
+            Event::MouseDown(Point::new(-3, 17)),
+            Event::MouseDrag(Point::new(40, 21)),
+            Event::MouseUp,
+            Event::ClockTick,
+            Event::KeyDown(Keycode::Z, KeyMod::command() | KeyMod::shift()),
+            Event::TextInput("\"Hi!\"".to_string()),
+            Event::Quit,
+        ];
+        let mut save = toml::value::Table::new();
+        save.insert("version".to_string(), toml::Value::Integer(1));
+        let save = toml::Value::Table(save);
+
+        let path = std::env::temp_dir().join("syzygy-recording-test.toml");
+        let start = Some(Location::Prolog);
+        let mut recorder =
+            Recorder::create(&path, 0xdeadbeef, start, save.clone()).unwrap();
+        for event in events.iter() {
+            recorder.record(event).unwrap();
+        }
+        drop(recorder);
+
+        let recording = Recording::load(&path).unwrap();
+        assert_eq!(recording.seed, 0xdeadbeef);
+        assert_eq!(recording.start, start);
+        assert_eq!(recording.save, save);
+        assert_eq!(recording.events, events);
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+// ========================================================================= //
@@ -34,6 +34,24 @@ pub enum Access {
 }
 
 impl Access {
+    pub fn all() -> &'static [Access] {
+        ALL_ACCESSES
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Access::Unvisited => "unvisited",
+            Access::Unsolved => "unsolved",
+            Access::Solved => "solved",
+            Access::BeginReplay => "begin_replay",
+            Access::Replaying => "replaying",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Access> {
+        Access::all().iter().copied().find(|access| access.key() == key)
+    }
+
     pub fn has_been_solved(&self) -> bool {
         *self >= Access::Solved
     }
@@ -71,31 +89,22 @@ impl Default for Access {
 
 impl Tomlable for Access {
     fn to_toml(&self) -> toml::Value {
-        let string = match *self {
-            Access::Unvisited => "unvisited",
-            Access::Unsolved => "unsolved",
-            Access::Solved => "solved",
-            Access::BeginReplay => "begin_replay",
-            Access::Replaying => "replaying",
-        };
-        toml::Value::String(string.to_string())
+        toml::Value::String(self.key().to_string())
     }
 
     fn from_toml(value: toml::Value) -> Access {
-        if let Some(string) = value.as_str() {
-            match string {
-                "unvisited" => return Access::Unvisited,
-                "unsolved" => return Access::Unsolved,
-                "solved" => return Access::Solved,
-                "begin_replay" => return Access::BeginReplay,
-                "replaying" => return Access::Replaying,
-                _ => {}
-            }
-        }
-        Default::default()
+        value.as_str().and_then(Access::from_key).unwrap_or_default()
     }
 }
 
+const ALL_ACCESSES: &[Access] = &[
+    Access::Unvisited,
+    Access::Unsolved,
+    Access::Solved,
+    Access::BeginReplay,
+    Access::Replaying,
+];
+
 // ========================================================================= //
 
 #[cfg(test)]
@@ -105,18 +114,19 @@ mod tests {
 
     #[test]
     fn toml_round_trip() {
-        let all = &[
-            Access::Unvisited,
-            Access::Unsolved,
-            Access::Solved,
-            Access::BeginReplay,
-            Access::Replaying,
-        ];
-        for &original in all.iter() {
+        for &original in Access::all() {
             let result = Access::from_toml(original.to_toml());
             assert_eq!(result, original);
         }
     }
+
+    #[test]
+    fn from_key() {
+        for &access in Access::all() {
+            assert_eq!(Access::from_key(access.key()), Some(access));
+        }
+        assert_eq!(Access::from_key("open"), None);
+    }
 }
 
 // ========================================================================= //
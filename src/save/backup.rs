@@ -0,0 +1,207 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ========================================================================= //
+
+const BACKUP_EXTENSION: &str = "bak";
+const SET_ASIDE_EXTENSION: &str = "corrupt";
+const TEMP_EXTENSION: &str = "tmp";
+
+// ========================================================================= //
+
+/// Writes `contents` to `path` without ever leaving a partially-written file
+/// there: the data is first written and synced to a temporary file in the
+/// same directory, which is then renamed over the destination.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = sibling_path(path, TEMP_EXTENSION);
+    {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    if let Err(error) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error);
+    }
+    sync_parent_dir(path);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+// ========================================================================= //
+
+/// Copies the file at `path` (if there is one) to a new timestamped backup
+/// next to it, and then deletes the oldest backups so that at most
+/// `max_backups` remain.
+pub fn make_backup(path: &Path, max_backups: usize) -> io::Result<()> {
+    if max_backups > 0 && path.is_file() {
+        fs::copy(path, timestamped_path(path, BACKUP_EXTENSION))?;
+    }
+    for old_backup in list_backups(path)?.into_iter().skip(max_backups) {
+        fs::remove_file(old_backup)?;
+    }
+    Ok(())
+}
+
+/// Moves the (unreadable) file at `path` out of the way, to a timestamped
+/// name next to it, so that it won't be overwritten by the next save.
+/// Returns the new path.
+pub fn move_aside(path: &Path) -> io::Result<PathBuf> {
+    let new_path = timestamped_path(path, SET_ASIDE_EXTENSION);
+    fs::rename(path, &new_path)?;
+    Ok(new_path)
+}
+
+/// Like `move_aside`, but leaves the original file in place.
+pub fn copy_aside(path: &Path) -> io::Result<PathBuf> {
+    let new_path = timestamped_path(path, SET_ASIDE_EXTENSION);
+    fs::copy(path, &new_path)?;
+    Ok(new_path)
+}
+
+/// Returns the paths of all backups of the file at `path`, newest first.
+pub fn list_backups(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let dir = match path.parent() {
+        Some(dir) if dir.is_dir() => dir,
+        _ => return Ok(Vec::new()),
+    };
+    let prefix = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => format!("{}.", name),
+        None => return Ok(Vec::new()),
+    };
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let timestamp = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&prefix as &str))
+            .and_then(|rest| rest.strip_suffix(&suffix as &str))
+            .and_then(|stamp| stamp.parse::<u128>().ok());
+        if let Some(timestamp) = timestamp {
+            backups.push((timestamp, entry.path()));
+        }
+    }
+    backups.sort();
+    Ok(backups.into_iter().rev().map(|(_, path)| path).collect())
+}
+
+fn timestamped_path(path: &Path, extension: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    sibling_path(path, &format!("{}.{}", millis, extension))
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{
+        copy_aside, list_backups, make_backup, move_aside, write_atomically,
+    };
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "syzygy-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn atomic_write_replaces_file() {
+        let dir = test_dir("atomic");
+        let path = dir.join("save_data.toml");
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backups_are_pruned() {
+        let dir = test_dir("backups");
+        let path = dir.join("save_data.toml");
+        make_backup(&path, 2).unwrap();
+        assert!(list_backups(&path).unwrap().is_empty());
+        for index in 0..4 {
+            fs::write(&path, format!("{}", index)).unwrap();
+            make_backup(&path, 2).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let backups = list_backups(&path).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "3");
+        assert_eq!(fs::read_to_string(&backups[1]).unwrap(), "2");
+        make_backup(&path, 0).unwrap();
+        assert!(list_backups(&path).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn set_aside_files_are_not_backups() {
+        let dir = test_dir("aside");
+        let path = dir.join("save_data.toml");
+        fs::write(&path, "garbage").unwrap();
+        let copy = copy_aside(&path).unwrap();
+        assert!(path.is_file());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let moved = move_aside(&path).unwrap();
+        assert!(!path.exists());
+        assert_ne!(copy, moved);
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "garbage");
+        assert!(list_backups(&path).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+// ========================================================================= //
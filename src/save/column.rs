@@ -18,8 +18,10 @@
 // +--------------------------------------------------------------------------+
 
 use num_integer::mod_floor;
+use rand::Rng;
 use toml;
 
+use crate::save::random::{seeded_rng, Scramble};
 use crate::save::util::Tomlable;
 
 // ========================================================================= //
@@ -29,6 +31,7 @@ struct Column {
     linkages: Vec<(usize, i32)>,
     offset: i32,
     current_position: i32,
+    initial_position: i32,
     solved_position: i32,
 }
 
@@ -43,27 +46,35 @@ impl Columns {
         spec: &[(&str, i32, i32, &[(usize, i32)])],
         array: toml::value::Array,
     ) -> Columns {
-        let mut columns: Vec<Column> = spec
+        let columns: Vec<Column> = spec
             .iter()
             .map(|&(word, offset, solved, linkages)| Column {
                 letters: word.chars().collect(),
                 linkages: linkages.iter().cloned().collect(),
                 offset,
                 current_position: 0,
+                initial_position: 0,
                 solved_position: solved,
             })
             .collect();
+        let mut columns = Columns { columns };
+        columns.set_positions_from_toml(array);
+        columns
+    }
+
+    /// Moves columns to the positions saved by `to_toml`, leaving any
+    /// columns with missing or invalid positions where they are.
+    pub fn set_positions_from_toml(&mut self, array: toml::value::Array) {
         for (index, value) in array.into_iter().enumerate() {
-            if index >= columns.len() {
+            if index >= self.columns.len() {
                 break;
             }
-            let column = &mut columns[index];
+            let column = &mut self.columns[index];
             let value = i32::from_toml(value);
             if value >= 0 && (value as usize) < column.letters.len() {
                 column.current_position = value;
             }
         }
-        Columns { columns }
     }
 
     pub fn to_toml(&self) -> toml::Value {
@@ -143,7 +154,7 @@ impl Columns {
 
     pub fn can_reset(&self) -> bool {
         for column in self.columns.iter() {
-            if column.current_position != 0 {
+            if column.current_position != column.initial_position {
                 return true;
             }
         }
@@ -152,7 +163,39 @@ impl Columns {
 
     pub fn reset(&mut self) {
         for column in self.columns.iter_mut() {
-            column.current_position = 0;
+            column.current_position = column.initial_position;
+        }
+    }
+
+    /// Scrambles the columns from their solved positions by `num_moves`
+    /// random column rotations (or more, if those happen to leave them
+    /// solved), and makes that the position that `reset` returns to.  The
+    /// same seed always gives the same scramble.
+    pub fn scramble(&mut self, scramble: Scramble) {
+        let num_moves = scramble.num_moves;
+        self.solve();
+        let movable: Vec<usize> = (0..self.columns.len())
+            .filter(|&col| !self.columns[col].linkages.is_empty())
+            .collect();
+        let mut rng = seeded_rng(scramble.seed);
+        let mut last_col = None;
+        let mut moves = 0;
+        while !movable.is_empty()
+            && (moves < num_moves || (num_moves > 0 && self.is_solved()))
+        {
+            let col = movable[rng.gen_range(0, movable.len())];
+            // Rotating the same column twice in a row would only count as one
+            // move.
+            if movable.len() > 1 && Some(col) == last_col {
+                continue;
+            }
+            let len = self.columns[col].letters.len() as i32;
+            self.rotate_column(col, rng.gen_range(1, len));
+            last_col = Some(col);
+            moves += 1;
+        }
+        for column in self.columns.iter_mut() {
+            column.initial_position = column.current_position;
         }
     }
 }
@@ -17,86 +17,265 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use super::game::Game;
-use super::prefs::Prefs;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use toml;
 
-use super::util::to_table;
+use super::backup::{
+    copy_aside, list_backups, make_backup, move_aside, write_atomically,
+};
+use super::game::Game;
+use super::migrate::{migrate, save_version, CURRENT_VERSION, VERSION_KEY};
+use super::prefs::Prefs;
+use super::util::{to_table, Tomlable};
+use super::validate::{validate_game, validate_table, IssueKind, SaveIssue};
 
 // ========================================================================= //
 
+const CURRENT_SLOT_KEY: &str = "current_slot";
 const GAME_KEY: &str = "game";
 const PREFS_KEY: &str = "prefs";
+const SLOTS_KEY: &str = "slots";
+
+const DEFAULT_SLOT_NAME: &str = "Slot 1";
+const MAX_SLOT_NAME_LEN: usize = 16;
 
 // ========================================================================= //
 
 pub struct SaveData {
     path: PathBuf,
     prefs: Prefs,
-    game: Option<Game>,
+    slots: BTreeMap<String, Option<Game>>,
+    current_slot: String,
+    issues: Vec<SaveIssue>,
+    recovery: Option<SaveRecovery>,
+}
+
+/// Describes what went wrong when loading the save file from disk, so that
+/// the player can be told about it.
+pub struct SaveRecovery {
+    /// Where the original save file was preserved.
+    pub set_aside: PathBuf,
+    /// The backup that the save data was restored from, if any.
+    pub restored_from: Option<PathBuf>,
+    /// The keys that were dropped or coerced while loading.
+    pub issues: Vec<SaveIssue>,
 }
 
 impl SaveData {
     pub fn new(path: PathBuf) -> SaveData {
-        SaveData { path, prefs: Prefs::with_defaults(), game: None }
+        let mut slots = BTreeMap::new();
+        slots.insert(DEFAULT_SLOT_NAME.to_string(), None);
+        SaveData {
+            path,
+            prefs: Prefs::with_defaults(),
+            slots,
+            current_slot: DEFAULT_SLOT_NAME.to_string(),
+            issues: Vec::new(),
+            recovery: None,
+        }
     }
 
     fn from_toml(path: PathBuf, mut table: toml::value::Table) -> SaveData {
+        migrate(&mut table);
         let mut data = SaveData::new(path);
-        if let Some(prefs) =
-            table.get(PREFS_KEY).and_then(toml::Value::as_table)
-        {
-            data.prefs = Prefs::from_toml(prefs);
+        if let Some(prefs) = table.get(PREFS_KEY) {
+            if let Some(prefs_table) = prefs.as_table() {
+                data.prefs = Prefs::from_toml(prefs_table);
+            }
+            data.issues.extend(validate_table(
+                PREFS_KEY,
+                prefs,
+                &data.prefs.to_toml(),
+            ));
         }
-        if let Some(game) = table.remove(GAME_KEY) {
-            data.game = Some(Game::from_toml(game));
+        let mut slots = BTreeMap::new();
+        for (name, slot) in to_table(
+            table.remove(SLOTS_KEY).unwrap_or(toml::Value::Boolean(false)),
+        ) {
+            if SaveData::is_valid_slot_name(&name) {
+                let game = to_table(slot)
+                    .remove(GAME_KEY)
+                    .map(|game| data.load_game(&name, game));
+                slots.insert(name, game);
+            } else {
+                data.issues.push(SaveIssue {
+                    slot: Some(name),
+                    location: None,
+                    field: "slot name".to_string(),
+                    kind: IssueKind::Dropped,
+                });
+            }
+        }
+        if !slots.is_empty() {
+            data.slots = slots;
+            let current = String::pop_from_table(&mut table, CURRENT_SLOT_KEY);
+            data.current_slot = if data.slots.contains_key(&current) {
+                current
+            } else {
+                data.slots.keys().next().unwrap().clone()
+            };
         }
         data
     }
 
+    fn load_game(&mut self, slot: &str, value: toml::Value) -> Game {
+        let game = Game::from_toml(value.clone());
+        self.issues.extend(validate_game(slot, &value, &game));
+        game
+    }
+
     fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
+        table.insert(
+            VERSION_KEY.to_string(),
+            toml::Value::Integer(CURRENT_VERSION),
+        );
         table.insert(PREFS_KEY.to_string(), self.prefs.to_toml());
-        if let Some(ref game) = self.game {
-            table.insert(GAME_KEY.to_string(), game.to_toml());
+        table
+            .insert(CURRENT_SLOT_KEY.to_string(), self.current_slot.to_toml());
+        let mut slots = toml::value::Table::new();
+        for (name, game) in self.slots.iter() {
+            let mut slot = toml::value::Table::new();
+            if let Some(ref game) = *game {
+                slot.insert(GAME_KEY.to_string(), game.to_toml());
+            }
+            slots.insert(name.clone(), toml::Value::Table(slot));
         }
+        table.insert(SLOTS_KEY.to_string(), toml::Value::Table(slots));
         toml::Value::Table(table)
     }
 
+    /// Returns everything that `save_to_disk` would write, without writing
+    /// it.
+    pub fn snapshot(&self) -> toml::Value {
+        self.to_toml()
+    }
+
+    /// Rebuilds save data from a `snapshot`, to be saved to the given path
+    /// from then on.
+    pub fn from_snapshot(path: PathBuf, snapshot: toml::Value) -> SaveData {
+        SaveData::from_toml(path, to_table(snapshot))
+    }
+
     pub fn save_to_disk(&mut self) -> io::Result<()> {
         let string = self.to_toml().to_string();
         fs::create_dir_all(self.path.parent().unwrap())?;
-        let mut file = fs::File::create(&self.path)?;
-        file.write_all(string.as_bytes())?;
+        if let Err(error) =
+            make_backup(&self.path, self.prefs.num_backups() as usize)
+        {
+            println!("Failed to back up save file: {}", error);
+        }
+        write_atomically(&self.path, string.as_bytes())?;
         if cfg!(debug_assertions) {
             println!("Saved game to disk.");
         }
         Ok(())
     }
 
-    fn load_from_disk(path: PathBuf) -> io::Result<SaveData> {
-        let mut file = fs::File::open(&path)?;
+    /// Reads the top-level save table from the given file.  A file written by
+    /// a newer version of the game is an error, just like an unparseable one,
+    /// since loading it and saving over it would lose whatever it added.
+    fn load_from_file(path: &Path) -> io::Result<toml::value::Table> {
+        let mut file = fs::File::open(path)?;
         let mut string = String::new();
         file.read_to_string(&mut string)?;
-        match string.parse::<toml::Value>() {
-            Ok(value) => Ok(SaveData::from_toml(path, to_table(value))),
-            Err(_) => Err(io::Error::new(
+        let table = match string.parse::<toml::Value>() {
+            Ok(value) => to_table(value),
+            Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "failed to parse toml",
+                ));
+            }
+        };
+        let version = save_version(&table);
+        if version > CURRENT_VERSION {
+            return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                "failed to parse toml",
-            )),
+                format!(
+                    "save format {} is from a newer version of the game",
+                    version
+                ),
+            ));
+        }
+        Ok(table)
+    }
+
+    /// Loads the save file at `path` without changing anything on disk:
+    /// unlike `load_or_create`, this fails if the file doesn't exist or
+    /// can't be parsed, rather than creating or setting aside the file.
+    pub fn load_existing(path: PathBuf) -> io::Result<SaveData> {
+        let table = SaveData::load_from_file(&path)?;
+        let data = SaveData::from_toml(path, table);
+        for issue in data.issues.iter() {
+            println!("Problem in save file: {}", issue);
         }
+        Ok(data)
     }
 
     pub fn load_or_create(path: PathBuf) -> io::Result<SaveData> {
-        if path.is_file() {
-            SaveData::load_from_disk(path)
-        } else {
+        if !path.is_file() {
             let mut data = SaveData::new(path);
             data.save_to_disk()?;
-            Ok(data)
+            return Ok(data);
+        }
+        let error = match SaveData::load_from_file(&path) {
+            Ok(table) => {
+                let mut data = SaveData::from_toml(path, table);
+                if !data.issues.is_empty() {
+                    for issue in data.issues.iter() {
+                        println!("Problem in save file: {}", issue);
+                    }
+                    let set_aside = copy_aside(&data.path)?;
+                    data.recovery = Some(SaveRecovery {
+                        set_aside,
+                        restored_from: None,
+                        issues: data.issues.clone(),
+                    });
+                }
+                return Ok(data);
+            }
+            Err(error) => error,
+        };
+        println!("Failed to load save file {:?}: {}", path, error);
+        let set_aside = move_aside(&path)?;
+        let mut restored = None;
+        for backup in list_backups(&path)? {
+            if let Ok(table) = SaveData::load_from_file(&backup) {
+                restored =
+                    Some((SaveData::from_toml(path.clone(), table), backup));
+                break;
+            }
+        }
+        let (mut data, restored_from) = match restored {
+            Some((data, backup)) => (data, Some(backup)),
+            None => (SaveData::new(path), None),
+        };
+        let issues = data.issues.clone();
+        data.recovery =
+            Some(SaveRecovery { set_aside, restored_from, issues });
+        Ok(data)
+    }
+
+    /// Returns information about any problems that occurred while loading the
+    /// save file, if the player hasn't been told about them yet.
+    pub fn recovery(&self) -> Option<&SaveRecovery> {
+        self.recovery.as_ref()
+    }
+
+    /// Marks the load problems as acknowledged.  If `keep_data` is false, any
+    /// game data that was restored from a backup is discarded, and every slot
+    /// starts over from scratch.
+    pub fn resolve_recovery(&mut self, keep_data: bool) {
+        if self.recovery.take().is_some() && !keep_data {
+            let path = self.path.clone();
+            let prefs =
+                std::mem::replace(&mut self.prefs, Prefs::with_defaults());
+            *self = SaveData::new(path);
+            self.prefs = prefs;
         }
     }
 
@@ -110,27 +289,368 @@ impl SaveData {
     }
 
     pub fn game(&self) -> Option<&Game> {
-        match self.game {
-            Some(ref game) => Some(game),
-            None => None,
-        }
+        self.slots.get(&self.current_slot).and_then(Option::as_ref)
     }
 
     pub fn game_mut(&mut self) -> &mut Game {
-        if let Some(ref mut game) = self.game {
-            game
+        if self.game().is_some() {
+            self.slots.get_mut(&self.current_slot).unwrap().as_mut().unwrap()
         } else {
             self.start_new_game()
         }
     }
 
     pub fn start_new_game(&mut self) -> &mut Game {
-        self.game = Some(Game::new());
-        self.game.as_mut().unwrap()
+        let slot = self.slots.entry(self.current_slot.clone()).or_default();
+        *slot = Some(Game::new());
+        slot.as_mut().unwrap()
     }
 
     pub fn erase_game(&mut self) {
-        self.game = None;
+        self.slots.insert(self.current_slot.clone(), None);
+    }
+
+    /// Returns true if the given string may be used as the name of a game
+    /// slot.
+    pub fn is_valid_slot_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.chars().count() <= MAX_SLOT_NAME_LEN
+            && name.trim() == name
+            && name.chars().all(SaveData::is_valid_slot_name_char)
+    }
+
+    pub fn is_valid_slot_name_char(chr: char) -> bool {
+        chr.is_ascii_alphanumeric() || chr == ' ' || chr == '-' || chr == '_'
+    }
+
+    pub fn max_slot_name_len() -> usize {
+        MAX_SLOT_NAME_LEN
+    }
+
+    /// Returns the names of all game slots, in sorted order.
+    pub fn slot_names(&self) -> Vec<&str> {
+        self.slots.keys().map(String::as_str).collect()
+    }
+
+    pub fn current_slot(&self) -> &str {
+        &self.current_slot
+    }
+
+    pub fn slot_has_game(&self, name: &str) -> bool {
+        matches!(self.slots.get(name), Some(Some(_)))
+    }
+
+    /// Returns an unused slot name of the form "Slot N".
+    pub fn unused_slot_name(&self) -> String {
+        let mut number = self.slots.len() + 1;
+        loop {
+            let name = format!("Slot {}", number);
+            if !self.slots.contains_key(&name) {
+                return name;
+            }
+            number += 1;
+        }
+    }
+
+    /// Creates a new, empty game slot.  Returns false (and does nothing) if
+    /// the name is invalid or already in use.
+    pub fn create_slot(&mut self, name: &str) -> bool {
+        if !SaveData::is_valid_slot_name(name) || self.slots.contains_key(name)
+        {
+            return false;
+        }
+        self.slots.insert(name.to_string(), None);
+        true
+    }
+
+    /// Makes the named slot the current one.  Returns false (and does
+    /// nothing) if there is no such slot.
+    pub fn switch_slot(&mut self, name: &str) -> bool {
+        if !self.slots.contains_key(name) {
+            return false;
+        }
+        self.current_slot = name.to_string();
+        true
+    }
+
+    /// Creates a new slot holding a copy of another slot's game.  Returns
+    /// false (and does nothing) if the source slot doesn't exist, or if the
+    /// new name is invalid or already in use.
+    pub fn copy_slot(&mut self, from: &str, to: &str) -> bool {
+        if !SaveData::is_valid_slot_name(to) || self.slots.contains_key(to) {
+            return false;
+        }
+        let copy = match self.slots.get(from) {
+            Some(game) => {
+                game.as_ref().map(|game| Game::from_toml(game.to_toml()))
+            }
+            None => return false,
+        };
+        self.slots.insert(to.to_string(), copy);
+        true
+    }
+
+    /// Deletes a game slot.  If it was the current slot, the first remaining
+    /// slot becomes current.  Returns false (and does nothing) if there is no
+    /// such slot, or if it is the only slot.
+    pub fn delete_slot(&mut self, name: &str) -> bool {
+        if self.slots.len() <= 1 || self.slots.remove(name).is_none() {
+            return false;
+        }
+        if self.current_slot == name {
+            self.current_slot = self.slots.keys().next().unwrap().clone();
+        }
+        true
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use toml;
+
+    use super::{SaveData, CURRENT_VERSION, DEFAULT_SLOT_NAME, PREFS_KEY};
+    use crate::save::util::{to_table, Tomlable};
+    use crate::save::{Access, Location, PuzzleState};
+
+    const SAVE_V0: &str = include_str!("testdata/save_v0.toml");
+    const SAVE_V1: &str = include_str!("testdata/save_v1.toml");
+
+    fn new_data() -> SaveData {
+        SaveData::new(PathBuf::from("save_data.toml"))
+    }
+
+    #[test]
+    fn new_data_has_one_empty_slot() {
+        let data = new_data();
+        assert_eq!(data.slot_names(), vec![DEFAULT_SLOT_NAME]);
+        assert_eq!(data.current_slot(), DEFAULT_SLOT_NAME);
+        assert!(data.game().is_none());
+    }
+
+    #[test]
+    fn slot_names() {
+        assert!(SaveData::is_valid_slot_name("Slot 1"));
+        assert!(SaveData::is_valid_slot_name("qa-late_game"));
+        assert!(!SaveData::is_valid_slot_name(""));
+        assert!(!SaveData::is_valid_slot_name(" Slot"));
+        assert!(!SaveData::is_valid_slot_name("Slot "));
+        assert!(!SaveData::is_valid_slot_name("$iSlot"));
+        assert!(!SaveData::is_valid_slot_name("Seventeen chars!!"));
+    }
+
+    #[test]
+    fn create_switch_copy_delete() {
+        let mut data = new_data();
+        data.game_mut().location = Location::Map;
+        assert!(data.create_slot("Alice"));
+        assert!(!data.create_slot("Alice"));
+        assert!(data.switch_slot("Alice"));
+        assert!(data.game().is_none());
+        assert!(!data.switch_slot("Bob"));
+        assert_eq!(data.current_slot(), "Alice");
+
+        data.start_new_game().prolog.visit();
+        assert!(data.copy_slot("Alice", "Bob"));
+        assert!(!data.copy_slot("Carol", "Dave"));
+        assert_eq!(data.slot_names(), vec!["Alice", "Bob", "Slot 1"]);
+        assert!(data.switch_slot("Bob"));
+        assert_eq!(data.game().unwrap().prolog.access(), Access::Solved);
+
+        assert!(data.delete_slot("Bob"));
+        assert_eq!(data.current_slot(), "Alice");
+        assert!(data.delete_slot("Alice"));
+        assert!(!data.delete_slot("Slot 1"));
+        assert_eq!(data.current_slot(), "Slot 1");
+        assert_eq!(data.game().unwrap().location, Location::Map);
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let mut data = new_data();
+        data.game_mut().location = Location::Map;
+        assert!(data.create_slot("Empty"));
+        assert!(data.create_slot("Other"));
+        assert!(data.switch_slot("Other"));
+        data.start_new_game();
+
+        let table = to_table(data.to_toml());
+        let data = SaveData::from_toml(PathBuf::new(), table);
+        assert_eq!(data.slot_names(), vec!["Empty", "Other", "Slot 1"]);
+        assert_eq!(data.current_slot(), "Other");
+        assert_eq!(data.game().unwrap().location, Location::Prolog);
+        assert!(!data.slot_has_game("Empty"));
+        assert!(data.slot_has_game("Slot 1"));
+    }
+
+    #[test]
+    fn load_corrupt_file_from_backup() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-recover-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("save_data.toml");
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        assert!(data.recovery().is_none());
+        data.game_mut().location = Location::Map;
+        data.save_to_disk().unwrap();
+        fs::write(&path, "this is [not valid toml").unwrap();
+
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        assert!(!path.exists());
+        {
+            let recovery = data.recovery().unwrap();
+            assert!(recovery.restored_from.is_some());
+            assert!(recovery.issues.is_empty());
+            assert_eq!(
+                fs::read_to_string(&recovery.set_aside).unwrap(),
+                "this is [not valid toml"
+            );
+        }
+        assert!(data.game().is_none());
+        data.resolve_recovery(true);
+        assert!(data.recovery().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_newer_file_from_backup() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-newer-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("save_data.toml");
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        data.game_mut().location = Location::Map;
+        data.save_to_disk().unwrap();
+        // Saving again backs up the file with the game at the map.
+        data.game_mut().location = Location::LightSyrup;
+        data.save_to_disk().unwrap();
+        let newer = format!("version = {}\n", CURRENT_VERSION + 1);
+        fs::write(&path, &newer).unwrap();
+
+        let data = SaveData::load_or_create(path.clone()).unwrap();
+        assert!(!path.exists());
+        let recovery = data.recovery().unwrap();
+        assert!(recovery.restored_from.is_some());
+        assert_eq!(fs::read_to_string(&recovery.set_aside).unwrap(), newer);
+        assert_eq!(data.game().unwrap().location, Location::Map);
+        assert!(SaveData::load_existing(recovery.set_aside.clone()).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_backup_does_not_prevent_save() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-backup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("save_data.toml");
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        // Directories with backup names can't be removed as old backups.
+        for stamp in 1..10 {
+            fs::create_dir(dir.join(format!("save_data.toml.{}.bak", stamp)))
+                .unwrap();
+        }
+        data.game_mut().location = Location::Map;
+        data.save_to_disk().unwrap();
+        let data = SaveData::load_or_create(path.clone()).unwrap();
+        assert_eq!(data.game().unwrap().location, Location::Map);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_existing_leaves_disk_alone() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-existing-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("save_data.toml");
+        assert!(SaveData::load_existing(path.clone()).is_err());
+        assert!(!dir.exists());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "this is [not valid toml").unwrap();
+        assert!(SaveData::load_existing(path.clone()).is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::write(&path, "[game]\nlocation = \"map\"\nprolog = 5\n").unwrap();
+        let data = SaveData::load_existing(path.clone()).unwrap();
+        assert_eq!(data.game().unwrap().location, Location::Map);
+        assert!(data.recovery().is_none());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hints_used_load_without_issues() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-hints-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("save_data.toml");
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        let game = data.game_mut();
+        game.location = Location::LightSyrup;
+        game.light_syrup.visit();
+        game.use_hint(Location::LightSyrup);
+        data.save_to_disk().unwrap();
+
+        let data = SaveData::load_or_create(path.clone()).unwrap();
+        assert!(data.recovery().is_none());
+        assert_eq!(data.game().unwrap().hints_used(Location::LightSyrup), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_file_with_invalid_fields() {
+        let dir = std::env::temp_dir()
+            .join(format!("syzygy-test-invalid-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("save_data.toml");
+        fs::write(&path, "[game]\nlocation = \"map\"\nprolog = 5\n").unwrap();
+
+        let mut data = SaveData::load_or_create(path.clone()).unwrap();
+        assert!(path.is_file());
+        assert_eq!(data.recovery().unwrap().issues.len(), 1);
+        assert_eq!(
+            data.recovery().unwrap().issues[0].location,
+            Some(Location::Prolog)
+        );
+        assert_eq!(data.game().unwrap().location, Location::Map);
+        data.resolve_recovery(false);
+        assert!(data.game().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn from_single_game_toml() {
+        let mut game = toml::value::Table::new();
+        game.insert("location".to_string(), Location::Map.to_toml());
+        let mut table = toml::value::Table::new();
+        table.insert("game".to_string(), toml::Value::Table(game));
+
+        let data = SaveData::from_toml(PathBuf::new(), table);
+        assert_eq!(data.slot_names(), vec![DEFAULT_SLOT_NAME]);
+        assert_eq!(data.game().unwrap().location, Location::Map);
+    }
+
+    #[test]
+    fn fixture_round_trips() {
+        // The fixtures predate most prefs, so compare the prefs they do have
+        // and check that everything else matches the version 1 fixture.
+        let mut v1 = to_table(SAVE_V1.parse::<toml::Value>().unwrap());
+        let v1_prefs = to_table(v1.remove(PREFS_KEY).unwrap());
+        for fixture in &[SAVE_V0, SAVE_V1] {
+            let table = to_table(fixture.parse::<toml::Value>().unwrap());
+            let data = SaveData::from_toml(PathBuf::new(), table);
+            assert!(data.issues.is_empty());
+            let mut saved = to_table(data.to_toml());
+            let prefs = to_table(saved.remove(PREFS_KEY).unwrap());
+            assert_eq!(saved, v1);
+            for (key, value) in v1_prefs.iter() {
+                assert_eq!(prefs.get(key), Some(value), "prefs.{}", key);
+            }
+        }
     }
 }
 
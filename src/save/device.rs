@@ -17,9 +17,11 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use toml;
 
+use crate::gui::Point;
 use crate::save::util::{to_table, Tomlable};
 use crate::save::{Direction, MixedColor};
 
@@ -29,6 +31,18 @@ const COORDS_KEY: &str = "coords";
 const DEVICE_KEY: &str = "device";
 const DIRECTION_KEY: &str = "direction";
 
+// How far a beam reaches into an empty cell from the side it enters by, in
+// pixels; half a cell, or a little less if it runs under a crossing beam.
+const OPEN_REACH: i32 = 16;
+const CROSSED_REACH: i32 = 14;
+
+const DIRECTIONS: [Direction; 4] =
+    [Direction::East, Direction::South, Direction::West, Direction::North];
+
+// What the solver can put in an undecided cell: nothing, or the device at
+// some index into its pool of unplaced devices, facing some direction.
+type Choice = Option<(usize, Direction)>;
+
 // ========================================================================= //
 
 #[derive(Clone)]
@@ -223,6 +237,766 @@ impl DeviceGrid {
         }
         false
     }
+
+    fn index(&self, col: i32, row: i32) -> usize {
+        (row * self.num_cols + col) as usize
+    }
+
+    /// Traces the laser beams from each emitter through the grid.
+    pub fn lasers(&self) -> Lasers {
+        self.trace_lasers(|_| false).lasers
+    }
+
+    /// Traces the laser beams through the grid, stopping each beam short of
+    /// any empty cell for which `stop_at` returns true.  Those beams are left
+    /// as the trace's heads, to be traced further once it's known what goes
+    /// in those cells.
+    fn trace_lasers<F: Fn(Point) -> bool>(&self, stop_at: F) -> Trace {
+        let mut lasers = Lasers::default();
+        let mut heads = Vec::new();
+        let mut queue: VecDeque<(Point, Direction, MixedColor)> =
+            VecDeque::new();
+        for row in 0..self.num_rows {
+            for col in 0..self.num_cols {
+                match self.get(col, row) {
+                    Some((Device::Emitter(color), dir)) => {
+                        if color != MixedColor::Black {
+                            let coords = Point::new(col, row);
+                            lasers.beams.insert((coords, dir), (color, 10));
+                            queue.push_back((coords, dir, color));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        while let Some((coords, laser_dir, color)) = queue.pop_front() {
+            let next = coords + laser_dir.delta();
+            let anti_dir = laser_dir.opposite();
+            if lasers.beams.contains_key(&(next, anti_dir)) {
+                if !lasers.sparks.contains_key(&(next, anti_dir)) {
+                    lasers.sparks.insert((coords, laser_dir), 0);
+                }
+                continue;
+            }
+            let device = self.get(next.x(), next.y());
+            if device.is_none() && stop_at(next) {
+                heads.push((coords, laser_dir, color));
+                continue;
+            }
+            match device {
+                Some((Device::Wall, _)) | Some((Device::Emitter(_), _)) => {
+                    lasers.sparks.insert((coords, laser_dir), 0);
+                }
+                Some((Device::Channel, ch_dir))
+                    if !ch_dir.is_parallel_to(laser_dir) =>
+                {
+                    lasers.sparks.insert((coords, laser_dir), 0);
+                }
+                Some((Device::Channel, _))
+                | Some((Device::CrossChannel, _))
+                | None => {
+                    let perp_dir = laser_dir.rotated_cw();
+                    let dist = if lasers.beams.contains_key(&(next, perp_dir))
+                    {
+                        CROSSED_REACH
+                    } else {
+                        OPEN_REACH
+                    };
+                    lasers.beams.insert((next, anti_dir), (color, dist));
+                    lasers.beams.insert((next, laser_dir), (color, dist));
+                    queue.push_back((next, laser_dir, color));
+                }
+                Some((Device::Detector(det_color), det_dir)) => {
+                    if det_dir == anti_dir {
+                        lasers.beams.insert((next, anti_dir), (color, 10));
+                        if det_color != color {
+                            lasers.sparks.insert((next, anti_dir), 10);
+                        }
+                    } else {
+                        lasers.sparks.insert((coords, laser_dir), 0);
+                    }
+                }
+                Some((Device::Mirror, mir_dir)) => {
+                    let reflect_dir = reflection(mir_dir, laser_dir);
+                    lasers.beams.insert((next, anti_dir), (color, 15));
+                    lasers.beams.insert((next, reflect_dir), (color, 15));
+                    queue.push_back((next, reflect_dir, color));
+                }
+                Some((Device::Splitter, split_dir)) => {
+                    if split_dir == laser_dir {
+                        lasers.beams.insert((next, anti_dir), (color, 6));
+                        let left_dir = laser_dir.rotated_ccw();
+                        let right_dir = laser_dir.rotated_cw();
+                        lasers.beams.insert((next, left_dir), (color, 6));
+                        lasers.beams.insert((next, right_dir), (color, 6));
+                        lasers.sparks.remove(&(next, left_dir));
+                        lasers.sparks.remove(&(next, right_dir));
+                        queue.push_back((next, left_dir, color));
+                        queue.push_back((next, right_dir, color));
+                    } else if split_dir == anti_dir {
+                        lasers.beams.insert((next, anti_dir), (color, 3));
+                        lasers.sparks.insert((next, anti_dir), 3);
+                    } else {
+                        lasers.beams.insert((next, anti_dir), (color, 6));
+                        lasers.sparks.insert((next, anti_dir), 6);
+                    }
+                }
+                Some((Device::Mixer, mixer_dir)) => {
+                    if mixer_dir == laser_dir {
+                        lasers.beams.insert((next, anti_dir), (color, 1));
+                        lasers.sparks.insert((next, anti_dir), 1);
+                    } else if mixer_dir == anti_dir {
+                        lasers.beams.insert((next, anti_dir), (color, 3));
+                        lasers.sparks.insert((next, anti_dir), 3);
+                    } else {
+                        lasers.beams.insert((next, anti_dir), (color, 3));
+                        if let Some(&(other, _)) =
+                            lasers.beams.get(&(next, laser_dir))
+                        {
+                            let output = mixer_output(color, other);
+                            lasers
+                                .beams
+                                .insert((next, mixer_dir), (output, 3));
+                            lasers.sparks.remove(&(next, mixer_dir));
+                            queue.push_back((next, mixer_dir, output));
+                        }
+                    }
+                }
+            }
+        }
+        Trace { lasers, heads }
+    }
+
+    /// Searches for a way to place and rotate the grid's movable devices so
+    /// that every detector is satisfied, and returns the solved grid, or
+    /// `None` if there isn't one.  Devices that the solution doesn't need
+    /// are left where they are when possible.
+    #[cfg(test)]
+    pub fn solution(&self) -> Option<DeviceGrid> {
+        self.solution_unless_cancelled(&AtomicBool::new(false))
+    }
+
+    /// Like `solution`, but gives up (returning `None`) once `cancelled` is
+    /// set, so that the search can run on another thread.
+    pub fn solution_unless_cancelled(
+        &self,
+        cancelled: &AtomicBool,
+    ) -> Option<DeviceGrid> {
+        let mut grid = self.clone();
+        grid.clear_all_movable_objects();
+        let mut pool: Vec<(Device, usize)> = Vec::new();
+        for &(device, _) in self.grid.iter().flatten() {
+            if !device.is_moveable() {
+                continue;
+            }
+            match pool.iter_mut().find(|&&mut (kind, _)| kind == device) {
+                Some(entry) => entry.1 += 1,
+                None => pool.push((device, 1)),
+            }
+        }
+        let mut decided = vec![false; self.grid.len()];
+        let mut dead_ends = HashSet::new();
+        if grid.search_solution(
+            self,
+            &mut pool,
+            &mut decided,
+            &mut dead_ends,
+            cancelled,
+        ) {
+            grid.is_modified = true;
+            Some(grid)
+        } else {
+            None
+        }
+    }
+
+    /// Places devices from `pool` onto the cells that beams pass through, one
+    /// cell at a time as the beams reach them.  Cells marked in `decided`
+    /// have already been chosen (either to hold a device, or to stay empty).
+    /// Returns true, with the remaining devices parked out of the way, if
+    /// every detector ends up satisfied.  The same choices can be reached in
+    /// different orders, so `dead_ends` keeps track of the ones (as encoded
+    /// by `choice_key`) already known to lead nowhere.
+    fn search_solution(
+        &mut self,
+        original: &DeviceGrid,
+        pool: &mut Vec<(Device, usize)>,
+        decided: &mut Vec<bool>,
+        dead_ends: &mut HashSet<Vec<u8>>,
+        cancelled: &AtomicBool,
+    ) -> bool {
+        if cancelled.load(Ordering::Relaxed) {
+            return false;
+        }
+        let key = self.choice_key(decided);
+        if dead_ends.contains(&key) {
+            return false;
+        }
+        let trace = match self.assess(pool, decided) {
+            Some((trace, _)) => trace,
+            None => return false,
+        };
+        if trace.heads.is_empty() {
+            return self.lasers().all_detectors_satisfied(self)
+                && self.park_devices(original, pool);
+        }
+        // Decide the cell with the fewest choices that could still work, so
+        // that dead ends turn up as early as possible.
+        let mut best: Option<(usize, Vec<Choice>)> = None;
+        let mut seen = Vec::new();
+        for &(coords, dir, _) in trace.heads.iter() {
+            let next = coords + dir.delta();
+            let index = self.index(next.x(), next.y());
+            if seen.contains(&index) {
+                continue;
+            }
+            seen.push(index);
+            let choices = self.ranked_choices(index, pool, decided);
+            let is_dead_end = choices.is_empty();
+            if best.iter().all(|(_, other)| choices.len() < other.len()) {
+                best = Some((index, choices));
+            }
+            if is_dead_end {
+                break;
+            }
+        }
+        let (index, choices) = best.unwrap();
+        decided[index] = true;
+        for choice in choices {
+            if let Some((kind, dir)) = choice {
+                pool[kind].1 -= 1;
+                self.grid[index] = Some((pool[kind].0, dir));
+                if self.search_solution(
+                    original, pool, decided, dead_ends, cancelled,
+                ) {
+                    return true;
+                }
+                pool[kind].1 += 1;
+            } else {
+                self.grid[index] = None;
+                if self.search_solution(
+                    original, pool, decided, dead_ends, cancelled,
+                ) {
+                    return true;
+                }
+            }
+        }
+        self.grid[index] = None;
+        decided[index] = false;
+        dead_ends.insert(key);
+        false
+    }
+
+    /// Encodes the choices made so far for each cell as one byte per cell.
+    fn choice_key(&self, decided: &[bool]) -> Vec<u8> {
+        let dir_code = |dir| match dir {
+            Direction::East => 0,
+            Direction::South => 1,
+            Direction::West => 2,
+            Direction::North => 3,
+        };
+        self.grid
+            .iter()
+            .zip(decided)
+            .map(|(&cell, &is_decided)| match cell {
+                _ if !is_decided => 0,
+                Some((Device::Mirror, dir)) => 2 + dir_code(dir),
+                Some((Device::Splitter, dir)) => 6 + dir_code(dir),
+                Some((Device::Mixer, dir)) => 10 + dir_code(dir),
+                _ => 1,
+            })
+            .collect()
+    }
+
+    /// Returns the choices for the undecided empty cell at `index`, most
+    /// promising first: `None` to leave it empty, or the `pool` entry of the
+    /// device to put there and the direction to point it.  Choices that
+    /// would make the grid unsolvable are left out.
+    fn ranked_choices(
+        &mut self,
+        index: usize,
+        pool: &mut [(Device, usize)],
+        decided: &mut [bool],
+    ) -> Vec<Choice> {
+        decided[index] = true;
+        let mut ranked = Vec::new();
+        if let Some((_, estimate)) = self.assess(pool, decided) {
+            ranked.push((estimate, None));
+        }
+        for kind in 0..pool.len() {
+            let (device, count) = pool[kind];
+            if count == 0 {
+                continue;
+            }
+            pool[kind].1 -= 1;
+            for dir in device.distinct_directions() {
+                self.grid[index] = Some((device, dir));
+                if let Some((_, estimate)) = self.assess(pool, decided) {
+                    ranked.push((estimate, Some((kind, dir))));
+                }
+            }
+            pool[kind].1 += 1;
+        }
+        self.grid[index] = None;
+        decided[index] = false;
+        ranked.sort_by_key(|&(estimate, _)| estimate);
+        ranked.into_iter().map(|(_, choice)| choice).collect()
+    }
+
+    /// Traces the beams up to the first undecided cell in each one's path.
+    /// Returns the trace along with a rough estimate of how many more
+    /// devices the unsatisfied detectors need, or `None` if there's no way
+    /// to satisfy them all from here.
+    fn assess(
+        &self,
+        pool: &[(Device, usize)],
+        decided: &[bool],
+    ) -> Option<(Trace, usize)> {
+        let trace = self.trace_lasers(|coords| {
+            !decided[self.index(coords.x(), coords.y())]
+        });
+        // A detector that has already been hit by the wrong color of laser
+        // can't be fixed without changing cells we've already decided on.
+        let mut unsatisfied = Vec::new();
+        for row in 0..self.num_rows {
+            for col in 0..self.num_cols {
+                if let Some((Device::Detector(color), dir)) =
+                    self.get(col, row)
+                {
+                    let coords = Point::new(col, row);
+                    match trace.lasers.beams.get(&(coords, dir)) {
+                        Some(&(laser, _)) if laser == color => {}
+                        Some(_) => return None,
+                        None => unsatisfied.push((coords, dir, color)),
+                    }
+                }
+            }
+        }
+        if unsatisfied.is_empty() {
+            return Some((trace, 0));
+        }
+        let estimate =
+            self.could_still_satisfy(&trace, &unsatisfied, pool, decided)?;
+        Some((trace, estimate))
+    }
+
+    /// Returns `None` if there's no way that the beams still being traced in
+    /// `trace` could go on to satisfy the `unsatisfied` detectors, however
+    /// the devices left in `pool` were placed.  Otherwise, returns a rough
+    /// estimate of how many more devices they need.
+    fn could_still_satisfy(
+        &self,
+        trace: &Trace,
+        unsatisfied: &[(Point, Direction, MixedColor)],
+        pool: &[(Device, usize)],
+        decided: &[bool],
+    ) -> Option<usize> {
+        let budget: usize = pool.iter().map(|&(_, count)| count).sum();
+        let in_pool = |kind: Device| {
+            pool.iter()
+                .filter(|&&(device, _)| device == kind)
+                .map(|&(_, count)| count)
+                .sum::<usize>()
+        };
+        // Splitters not yet hit from behind can each still split one beam,
+        // and mixers that haven't made a beam yet can each still make one
+        // (noting the color of the first beam, if any, already fed to them).
+        let mut max_splits = in_pool(Device::Splitter);
+        let mut mixers = vec![None; in_pool(Device::Mixer)];
+        let mut placed_mixers = Vec::new();
+        for (index, &cell) in self.grid.iter().enumerate() {
+            let coords = Point::new(
+                index as i32 % self.num_cols,
+                index as i32 / self.num_cols,
+            );
+            let lit = |dir| trace.lasers.beams.contains_key(&(coords, dir));
+            match cell {
+                Some((Device::Splitter, dir)) if !lit(dir.opposite()) => {
+                    max_splits += 1;
+                }
+                Some((Device::Mixer, dir)) => {
+                    if !lit(dir) {
+                        let side = |side_dir| {
+                            trace.lasers.beams.get(&(coords, side_dir))
+                        };
+                        let input = side(dir.rotated_cw())
+                            .or_else(|| side(dir.rotated_ccw()))
+                            .map(|&(color, _)| color);
+                        mixers.push(input);
+                        placed_mixers.push((coords, dir));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Each beam can only satisfy one detector, and splitters can only
+        // copy the colors there already are; a mixer can make a new color,
+        // but takes two beams to make one (unless it's already been fed the
+        // first).  Working out every way that several mixers could be used
+        // isn't worth it, so only check this with at most one.
+        if mixers.len() <= 1 {
+            let colors: Vec<MixedColor> =
+                trace.heads.iter().map(|&(_, _, color)| color).collect();
+            let targets: Vec<MixedColor> =
+                unsatisfied.iter().map(|&(_, _, color)| color).collect();
+            match splits_needed(&colors, &targets, mixers.first().cloned()) {
+                Some(splits) if splits <= max_splits => {}
+                _ => return None,
+            }
+        } else if unsatisfied.len() > trace.heads.len() + max_splits {
+            return None;
+        }
+
+        // Each detector must be reachable by a beam of the right color,
+        // turning wherever a device could still be placed, without needing
+        // more devices than are left.  That beam can come straight from a
+        // beam of that color, or from a mixer fed by two beams whose colors
+        // mix to it.
+        let can_turn = budget > 0;
+        let mut colors: Vec<MixedColor> = Vec::new();
+        for &(_, _, color) in trace.heads.iter() {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        let color_costs: Vec<HashMap<(Point, Direction), usize>> = colors
+            .iter()
+            .map(|&color| {
+                let heads = trace
+                    .heads
+                    .iter()
+                    .filter(|&&(_, _, head)| head == color)
+                    .map(|&(coords, dir, _)| (coords, dir));
+                self.turn_costs(heads, can_turn, decided)
+            })
+            .collect();
+        let mut input_colors = colors.clone();
+        for &fed in mixers.iter().flatten() {
+            if !input_colors.contains(&fed) {
+                input_colors.push(fed);
+            }
+        }
+        let cost_of = |color: MixedColor, state: (Point, Direction)| {
+            let index = colors.iter().position(|&c| c == color)?;
+            color_costs[index].get(&state).cloned()
+        };
+        let mut estimate = 0;
+        for &(coords, dir, color) in unsatisfied.iter() {
+            let entry = (coords, dir.opposite());
+            let mut best = cost_of(color, entry).unwrap_or(usize::MAX);
+            if best > budget && !mixers.is_empty() {
+                let costs_from = self.costs_to(entry, can_turn, decided);
+                let mut sites: Vec<(Point, Direction, usize)> = placed_mixers
+                    .iter()
+                    .map(|&(mixer, mixer_dir)| (mixer, mixer_dir, 0))
+                    .collect();
+                if can_turn && in_pool(Device::Mixer) > 0 {
+                    for (index, &cell) in self.grid.iter().enumerate() {
+                        if cell.is_none() && !decided[index] {
+                            let mixer = Point::new(
+                                index as i32 % self.num_cols,
+                                index as i32 / self.num_cols,
+                            );
+                            for &mixer_dir in DIRECTIONS.iter() {
+                                sites.push((mixer, mixer_dir, 1));
+                            }
+                        }
+                    }
+                }
+                for (mixer, mixer_dir, extra) in sites {
+                    let output = (mixer + mixer_dir.delta(), mixer_dir);
+                    let out_cost = match costs_from.get(&output) {
+                        Some(&cost) => cost,
+                        None => continue,
+                    };
+                    let left = (mixer, mixer_dir.rotated_cw());
+                    let right = (mixer, mixer_dir.rotated_ccw());
+                    let fed = |side: Direction| {
+                        trace
+                            .lasers
+                            .beams
+                            .get(&(mixer, side))
+                            .map(|&(fed_color, _)| fed_color)
+                    };
+                    for &color1 in input_colors.iter() {
+                        for &color2 in input_colors.iter() {
+                            if mixer_output(color1, color2) != color {
+                                continue;
+                            }
+                            let in1 = match fed(left.1.opposite()) {
+                                Some(fed_color) if fed_color == color1 => 0,
+                                Some(_) => continue,
+                                None => match cost_of(color1, left) {
+                                    Some(cost) => cost,
+                                    None => continue,
+                                },
+                            };
+                            let in2 = match fed(right.1.opposite()) {
+                                Some(fed_color) if fed_color == color2 => 0,
+                                Some(_) => continue,
+                                None => match cost_of(color2, right) {
+                                    Some(cost) => cost,
+                                    None => continue,
+                                },
+                            };
+                            let cost = in1.max(in2).max(out_cost) + extra;
+                            best = best.min(cost);
+                        }
+                    }
+                }
+            }
+            if best > budget {
+                return None;
+            }
+            estimate += best + 1;
+        }
+        Some(estimate)
+    }
+
+    /// Returns where a beam entering the cell at `coords` heading in `dir`
+    /// could leave it, along with how many devices would have to be placed
+    /// to make it do so (see `turn_costs`).
+    fn exits(
+        &self,
+        coords: Point,
+        dir: Direction,
+        can_turn: bool,
+        decided: &[bool],
+    ) -> Vec<(Direction, usize)> {
+        match self.get(coords.x(), coords.y()) {
+            Some((Device::Channel, ch_dir)) if ch_dir.is_parallel_to(dir) => {
+                vec![(dir, 0)]
+            }
+            Some((Device::CrossChannel, _)) => vec![(dir, 0)],
+            None => {
+                let index = self.index(coords.x(), coords.y());
+                if can_turn && !decided[index] {
+                    vec![
+                        (dir, 0),
+                        (dir.rotated_cw(), 1),
+                        (dir.rotated_ccw(), 1),
+                    ]
+                } else {
+                    vec![(dir, 0)]
+                }
+            }
+            Some((Device::Mirror, mir_dir)) => {
+                vec![(reflection(mir_dir, dir), 0)]
+            }
+            Some((Device::Splitter, split_dir)) if split_dir == dir => {
+                vec![(dir.rotated_cw(), 0), (dir.rotated_ccw(), 0)]
+            }
+            Some((Device::Mixer, mixer_dir))
+                if !mixer_dir.is_parallel_to(dir) =>
+            {
+                vec![(mixer_dir, 0)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn contains(&self, coords: Point) -> bool {
+        coords.x() >= 0
+            && coords.x() < self.num_cols
+            && coords.y() >= 0
+            && coords.y() < self.num_rows
+    }
+
+    /// Returns each cell that a beam leaving one of the `heads` could enter,
+    /// along with the direction it would be heading, mapped to the fewest
+    /// devices that would have to be placed to get it there, if it could
+    /// turn in any empty cell that hasn't been decided yet (when `can_turn`
+    /// is true).
+    fn turn_costs<I: Iterator<Item = (Point, Direction)>>(
+        &self,
+        heads: I,
+        can_turn: bool,
+        decided: &[bool],
+    ) -> HashMap<(Point, Direction), usize> {
+        let mut costs = HashMap::new();
+        let mut queue: VecDeque<(Point, Direction, usize)> =
+            heads.map(|(coords, dir)| (coords, dir, 0)).collect();
+        while let Some((coords, dir, cost)) = queue.pop_front() {
+            let next = coords + dir.delta();
+            if !self.contains(next)
+                || costs.get(&(next, dir)).iter().any(|&&old| old <= cost)
+            {
+                continue;
+            }
+            costs.insert((next, dir), cost);
+            for (exit, extra) in self.exits(next, dir, can_turn, decided) {
+                if extra == 0 {
+                    queue.push_front((next, exit, cost));
+                } else {
+                    queue.push_back((next, exit, cost + extra));
+                }
+            }
+        }
+        costs
+    }
+
+    /// Like `turn_costs`, but the other way around: returns each cell that a
+    /// beam could enter, along with the direction it would be heading,
+    /// mapped to the fewest devices that would have to be placed for it to
+    /// go on to enter the cell and direction given by `target`.
+    fn costs_to(
+        &self,
+        target: (Point, Direction),
+        can_turn: bool,
+        decided: &[bool],
+    ) -> HashMap<(Point, Direction), usize> {
+        let mut costs = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((target.0, target.1, 0));
+        while let Some((coords, dir, cost)) = queue.pop_front() {
+            if costs.get(&(coords, dir)).iter().any(|&&old| old <= cost) {
+                continue;
+            }
+            costs.insert((coords, dir), cost);
+            let prev = coords - dir.delta();
+            if !self.contains(prev) {
+                continue;
+            }
+            for &entry_dir in DIRECTIONS.iter() {
+                for (exit, extra) in
+                    self.exits(prev, entry_dir, can_turn, decided)
+                {
+                    if exit != dir {
+                        continue;
+                    } else if extra == 0 {
+                        queue.push_front((prev, entry_dir, cost));
+                    } else {
+                        queue.push_back((prev, entry_dir, cost + extra));
+                    }
+                }
+            }
+        }
+        costs
+    }
+
+    /// Puts the devices left in `pool` into empty cells that no beam passes
+    /// through, preferring cells that held the same device in `original`.
+    fn park_devices(
+        &mut self,
+        original: &DeviceGrid,
+        pool: &[(Device, usize)],
+    ) -> bool {
+        let lit: HashSet<Point> =
+            self.lasers().beams.keys().map(|&(coords, _)| coords).collect();
+        let mut free: Vec<usize> = (0..self.grid.len())
+            .filter(|&index| {
+                let col = index as i32 % self.num_cols;
+                let row = index as i32 / self.num_cols;
+                self.grid[index].is_none()
+                    && !lit.contains(&Point::new(col, row))
+            })
+            .collect();
+        let mut remaining = Vec::new();
+        for &(device, mut count) in pool.iter() {
+            for &index in free.iter() {
+                if count == 0 {
+                    break;
+                }
+                if let Some((original_device, dir)) = original.grid[index] {
+                    if original_device == device {
+                        self.grid[index] = Some((device, dir));
+                        count -= 1;
+                    }
+                }
+            }
+            remaining.push((device, count));
+        }
+        free.retain(|&index| self.grid[index].is_none());
+        let mut free = free.into_iter();
+        for (device, count) in remaining {
+            for _ in 0..count {
+                match free.next() {
+                    Some(index) => {
+                        self.grid[index] = Some((device, Direction::East));
+                    }
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+// ========================================================================= //
+
+/// The laser beams running through a `DeviceGrid`.
+#[derive(Default)]
+pub struct Lasers {
+    beams: HashMap<(Point, Direction), (MixedColor, i32)>,
+    sparks: HashMap<(Point, Direction), i32>,
+}
+
+impl Lasers {
+    /// Returns the color of the beam leaving each side of each cell, along
+    /// with how far into the cell it reaches, in pixels.
+    pub fn beams(&self) -> &HashMap<(Point, Direction), (MixedColor, i32)> {
+        &self.beams
+    }
+
+    /// Returns the cell sides where beams are blocked, along with how far in
+    /// from that side the spark is, in pixels.
+    pub fn sparks(&self) -> &HashMap<(Point, Direction), i32> {
+        &self.sparks
+    }
+
+    pub fn satisfied_detector_positions(
+        &self,
+        grid: &DeviceGrid,
+    ) -> HashSet<(i32, i32)> {
+        let mut positions = HashSet::new();
+        let (num_cols, num_rows) = grid.size();
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                match grid.get(col, row) {
+                    Some((Device::Detector(color), dir)) => {
+                        let coords = Point::new(col, row);
+                        match self.beams.get(&(coords, dir)) {
+                            Some(&(laser, _)) if laser == color => {
+                                positions.insert((col, row));
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        positions
+    }
+
+    pub fn all_detectors_satisfied(&self, grid: &DeviceGrid) -> bool {
+        let (num_cols, num_rows) = grid.size();
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                match grid.get(col, row) {
+                    Some((Device::Detector(color), dir)) => {
+                        let coords = Point::new(col, row);
+                        match self.beams.get(&(coords, dir)) {
+                            Some(&(laser, _)) if laser == color => {}
+                            _ => return false,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+}
+
+/// The result of tracing lasers through a grid, possibly stopping some beams
+/// early.
+struct Trace {
+    lasers: Lasers,
+    /// The beams that were stopped short, as the cell each is leaving, the
+    /// direction it's heading, and its color.
+    heads: Vec<(Point, Direction, MixedColor)>,
 }
 
 // ========================================================================= //
@@ -247,6 +1021,16 @@ impl Device {
         }
     }
 
+    /// Returns the directions this device can face that each make a
+    /// difference to the laser beams passing through it.  (A mirror works
+    /// the same facing either way.)
+    fn distinct_directions(self) -> Vec<Direction> {
+        match self {
+            Device::Mirror => vec![Direction::East, Direction::South],
+            _ => DIRECTIONS.to_vec(),
+        }
+    }
+
     #[cfg(test)]
     pub fn all() -> Vec<Device> {
         let mut devices = vec![
@@ -328,11 +1112,91 @@ impl Tomlable for Device {
 
 // ========================================================================= //
 
+/// Returns the fewest times that beams of the given `colors` would have to
+/// be split for there to be a beam of the right color for each of the
+/// `targets`, or `None` if some color can't be had at all.  If `mixer` is
+/// given, one mixer is available to use as well, possibly having already
+/// been fed a beam of some color.
+fn splits_needed(
+    colors: &[MixedColor],
+    targets: &[MixedColor],
+    mixer: Option<Option<MixedColor>>,
+) -> Option<usize> {
+    let shortfall = |supply: &[MixedColor], demand: &[MixedColor]| {
+        let mut total = 0;
+        for (index, &color) in demand.iter().enumerate() {
+            if demand[..index].contains(&color) {
+                continue;
+            }
+            let wanted = demand.iter().filter(|&&c| c == color).count();
+            let have = supply.iter().filter(|&&c| c == color).count();
+            if have == 0 {
+                return None;
+            }
+            total += wanted.saturating_sub(have);
+        }
+        Some(total)
+    };
+    let mut best = shortfall(colors, targets);
+    if let Some(fed) = mixer {
+        for (index, &color1) in colors.iter().enumerate() {
+            let inputs: Vec<MixedColor> = match fed {
+                Some(color2) => vec![color2],
+                None => colors[index..].to_vec(),
+            };
+            for color2 in inputs {
+                let mut supply = colors.to_vec();
+                supply.push(mixer_output(color1, color2));
+                let mut demand = targets.to_vec();
+                demand.push(color1);
+                if fed.is_none() {
+                    demand.push(color2);
+                }
+                if let Some(splits) = shortfall(&supply, &demand) {
+                    best = Some(best.map_or(splits, |b| b.min(splits)));
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Returns the direction a laser beam heading in `laser_dir` leaves a mirror
+/// facing `mir_dir` in.
+fn reflection(mir_dir: Direction, laser_dir: Direction) -> Direction {
+    let reflect_dir = match laser_dir {
+        Direction::East => Direction::North,
+        Direction::South => Direction::West,
+        Direction::West => Direction::South,
+        Direction::North => Direction::East,
+    };
+    if mir_dir.is_vertical() {
+        reflect_dir.opposite()
+    } else {
+        reflect_dir
+    }
+}
+
+fn mixer_output(color1: MixedColor, color2: MixedColor) -> MixedColor {
+    let red = (color1.has_red() && color2.has_red())
+        || (color1.has_green() && color2.has_blue())
+        || (color1.has_blue() && color2.has_green());
+    let green = (color1.has_green() && color2.has_green())
+        || (color1.has_red() && color2.has_blue())
+        || (color1.has_blue() && color2.has_red());
+    let blue = (color1.has_blue() && color2.has_blue())
+        || (color1.has_red() && color2.has_green())
+        || (color1.has_green() && color2.has_red());
+    MixedColor::from_rgb(red, green, blue)
+}
+
+// ========================================================================= //
+
 #[cfg(test)]
 mod tests {
     use super::{Device, DeviceGrid};
     use crate::save::util::{to_array, Tomlable};
-    use crate::save::Direction;
+    use crate::save::{Direction, MixedColor};
 
     #[test]
     fn device_toml_round_trip() {
@@ -353,6 +1217,35 @@ mod tests {
         let result = DeviceGrid::from_toml(to_array(grid.to_toml()), &default);
         assert_eq!(result.grid, grid.grid);
     }
+
+    #[test]
+    fn solution_places_and_rotates_devices() {
+        let mut grid = DeviceGrid::new(3, 3);
+        grid.set(0, 0, Device::Emitter(MixedColor::Red), Direction::East);
+        grid.set(2, 2, Device::Detector(MixedColor::Red), Direction::North);
+        grid.set(1, 1, Device::Wall, Direction::East);
+        grid.set(0, 2, Device::Mirror, Direction::East);
+        assert!(!grid.lasers().all_detectors_satisfied(&grid));
+        let solution = grid.solution().unwrap();
+        assert!(solution.lasers().all_detectors_satisfied(&solution));
+        assert_eq!(
+            solution.get(2, 0),
+            Some((Device::Mirror, Direction::South))
+        );
+        assert_eq!(solution.get(0, 2), None);
+    }
+
+    #[test]
+    fn solution_needs_enough_devices() {
+        let mut grid = DeviceGrid::new(3, 3);
+        grid.set(0, 0, Device::Emitter(MixedColor::Red), Direction::East);
+        grid.set(2, 2, Device::Detector(MixedColor::Red), Direction::North);
+        assert!(grid.solution().is_none());
+        grid.set(1, 2, Device::Mirror, Direction::East);
+        assert!(grid.solution().is_some());
+        grid.set(2, 2, Device::Detector(MixedColor::Blue), Direction::North);
+        assert!(grid.solution().is_none());
+    }
 }
 
 // ========================================================================= //
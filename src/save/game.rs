@@ -17,18 +17,20 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+use std::collections::HashMap;
 use toml;
 
-use crate::save::util::{to_table, Tomlable};
+use crate::save::random::Scramble;
+use crate::save::util::{to_table, Tomlable, HINTS_KEY};
 use crate::save::{
-    AtticState, AutoState, BlackState, BlameState, BlindState, CubeState,
-    DayState, DisconState, DotsState, DoubleState, FailureState, FictionState,
-    FinaleState, GearsState, GroundState, HeadedState, HexState, IcyEmState,
-    JogState, LaneState, LevelUpState, LineState, Location, LogLevelState,
-    MeetState, MissedState, NoReturnState, OrderState, PasswordState,
-    PovState, PrologState, PuzzleState, RightState, SauceState, ServesState,
-    SimpleState, StarState, SyrupState, SyzygyState, TheYState, TreadState,
-    WhatchaState, WreckedState,
+    Access, AtticState, AutoState, BlackState, BlameState, BlindState,
+    CubeState, DayState, DisconState, DotsState, DoubleState, FailureState,
+    FictionState, FinaleState, GearsState, GroundState, HeadedState, HexState,
+    IcyEmState, JogState, LaneState, LevelUpState, LineState, Location,
+    LogLevelState, MeetState, MissedState, NoReturnState, OrderState,
+    PasswordState, PovState, PrologState, PuzzleState, RightState, SauceState,
+    ServesState, SimpleState, StarState, SyrupState, SyzygyState, TheYState,
+    TreadState, WhatchaState, WreckedState,
 };
 
 // ========================================================================= //
@@ -36,6 +38,13 @@ use crate::save::{
 const EVER_CLICKED_INFO_KEY: &str = "ever_clicked_info";
 const LOCATION_KEY: &str = "location";
 
+const SCRAMBLE_LOCATIONS: &[Location] = &[
+    Location::CubeTangle,
+    Location::HexSpangled,
+    Location::WhatchaColumn,
+    Location::WreckedAngle,
+];
+
 // ========================================================================= //
 
 pub struct Game {
@@ -82,6 +91,7 @@ pub struct Game {
     pub wrecked_angle: WreckedState,
     pub finale: FinaleState,
     pub ever_clicked_info: bool,
+    hints_used: HashMap<Location, u32>,
 }
 
 impl Game {
@@ -93,6 +103,17 @@ impl Game {
 
     pub fn from_toml(value: toml::Value) -> Game {
         let mut table = to_table(value);
+        let mut hints_used = HashMap::new();
+        for &location in Location::all() {
+            if let Some(&mut toml::Value::Table(ref mut subtable)) =
+                table.get_mut(location.key())
+            {
+                let hints = u32::pop_from_table(subtable, HINTS_KEY);
+                if hints > 0 {
+                    hints_used.insert(location, hints);
+                }
+            }
+        }
         let table_ref = &mut table;
         Game {
             location: Location::pop_from_table(table_ref, LOCATION_KEY),
@@ -141,6 +162,7 @@ impl Game {
                 table_ref,
                 EVER_CLICKED_INFO_KEY,
             ),
+            hints_used,
         }
     }
 
@@ -150,11 +172,20 @@ impl Game {
         for &location in Location::all() {
             if location != Location::Map {
                 let puzzle_state = self.puzzle_state(location);
-                if puzzle_state.has_been_visited() {
-                    table.insert(
-                        location.key().to_string(),
-                        puzzle_state.to_toml(),
-                    );
+                if puzzle_state.has_been_visited()
+                    || puzzle_state.is_scrambled()
+                {
+                    let mut value = puzzle_state.to_toml();
+                    let hints = self.hints_used(location);
+                    if hints > 0 {
+                        if let toml::Value::Table(ref mut subtable) = value {
+                            subtable.insert(
+                                HINTS_KEY.to_string(),
+                                hints.to_toml(),
+                            );
+                        }
+                    }
+                    table.insert(location.key().to_string(), value);
                 }
             }
         }
@@ -165,6 +196,15 @@ impl Game {
         toml::Value::Table(table)
     }
 
+    /// Returns how many hints the player has asked for on the given puzzle.
+    pub fn hints_used(&self, location: Location) -> u32 {
+        self.hints_used.get(&location).cloned().unwrap_or(0)
+    }
+
+    pub fn use_hint(&mut self, location: Location) {
+        *self.hints_used.entry(location).or_insert(0) += 1;
+    }
+
     pub fn is_unlocked(&self, location: Location) -> bool {
         location.prereqs().iter().all(|&prereq| self.has_been_solved(prereq))
     }
@@ -173,6 +213,53 @@ impl Game {
         self.puzzle_state(location).has_been_solved()
     }
 
+    /// Returns true if the player has started on the given puzzle and not
+    /// yet finished it, whether for the first time or on a replay.
+    pub fn is_in_progress(&self, location: Location) -> bool {
+        let state = self.puzzle_state(location);
+        match state.access() {
+            Access::Unsolved => true,
+            Access::Replaying => state.can_reset(),
+            _ => false,
+        }
+    }
+
+    /// Returns the puzzles that can be dealt as fresh, scrambled instances.
+    pub fn scramble_locations() -> &'static [Location] {
+        SCRAMBLE_LOCATIONS
+    }
+
+    /// Replaces the given puzzle with a freshly dealt instance, or returns
+    /// false if that puzzle can't be scrambled.  If the puzzle has already
+    /// been solved, the new instance is set up as a replay, so that the
+    /// player's progress through the game is kept.
+    pub fn deal_scramble(
+        &mut self,
+        location: Location,
+        scramble: Scramble,
+    ) -> bool {
+        let was_solved = self.has_been_solved(location);
+        match location {
+            Location::CubeTangle => {
+                self.cube_tangle = CubeState::scrambled(scramble)
+            }
+            Location::HexSpangled => {
+                self.hex_spangled = HexState::scrambled(scramble)
+            }
+            Location::WhatchaColumn => {
+                self.whatcha_column = WhatchaState::scrambled(scramble)
+            }
+            Location::WreckedAngle => {
+                self.wrecked_angle = WreckedState::scrambled(scramble)
+            }
+            _ => return false,
+        }
+        if was_solved {
+            self.puzzle_state_mut(location).replay();
+        }
+        true
+    }
+
     pub fn puzzle_state(&self, loc: Location) -> &dyn PuzzleState {
         match loc {
             Location::Map => panic!("no PuzzleState for Map"),
@@ -282,6 +369,23 @@ mod tests {
         assert!(!game.ever_clicked_info);
         assert_eq!(game.prolog.access(), Access::Unvisited);
     }
+
+    #[test]
+    fn hints_toml_round_trip() {
+        let mut game = Game::new();
+        game.disconnected.visit();
+        game.use_hint(Location::Disconnected);
+        game.use_hint(Location::Disconnected);
+        game.use_hint(Location::CubeTangle);
+        assert_eq!(game.hints_used(Location::Disconnected), 2);
+
+        let game = Game::from_toml(game.to_toml());
+        assert_eq!(game.hints_used(Location::Disconnected), 2);
+        assert_eq!(game.disconnected.access(), Access::Unsolved);
+        // Hints for puzzles that were never visited aren't saved.
+        assert_eq!(game.hints_used(Location::CubeTangle), 0);
+        assert_eq!(game.hints_used(Location::Prolog), 0);
+    }
 }
 
 // ========================================================================= //
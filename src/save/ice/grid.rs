@@ -18,8 +18,10 @@
 // +--------------------------------------------------------------------------+
 
 use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use toml;
 
+use super::solver;
 use crate::gui::Point;
 use crate::save::ice::{Symbol, Transform};
 use crate::save::util::{pop_array, to_table, Tomlable};
@@ -323,6 +325,29 @@ impl ObjectGrid {
         true
     }
 
+    /// Returns the shortest sequence of `(coords, direction)` slides that puts
+    /// every ice block onto a matching goal, or `None` if there isn't one.
+    #[cfg(test)]
+    pub fn solution(&self) -> Option<Vec<(Point, Direction)>> {
+        solver::shortest_solution(self)
+    }
+
+    /// Like `solution`, but gives up and returns `None` as soon as
+    /// `cancelled` is set.
+    pub fn solution_unless_cancelled(
+        &self,
+        cancelled: &AtomicBool,
+    ) -> Option<Vec<(Point, Direction)>> {
+        solver::shortest_solution_unless_cancelled(self, cancelled)
+    }
+
+    /// Returns how many grid states reachable from this one can no longer be
+    /// solved.
+    #[cfg(test)]
+    pub fn count_dead_ends(&self) -> Option<usize> {
+        solver::count_dead_ends(self)
+    }
+
     pub fn solved(mut self) -> ObjectGrid {
         self.ice_blocks.clear();
         for (&coords, object) in self.objects.iter() {
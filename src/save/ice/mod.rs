@@ -18,6 +18,7 @@
 // +--------------------------------------------------------------------------+
 
 mod grid;
+mod solver;
 mod symbol;
 mod transform;
 
@@ -0,0 +1,842 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::gui::Point;
+use crate::save::ice::{Object, ObjectGrid, Symbol, Transform};
+use crate::save::Direction;
+
+// ========================================================================= //
+
+const MAX_BLOCKS: usize = 4;
+const MAX_PUSH_POPS: usize = 7;
+const SYMBOL_BITS: u32 = 6;
+const DIRECTION_BITS: u32 = 2;
+
+const UNKNOWN: u32 = u32::MAX;
+const UNREACHABLE: u32 = u32::MAX - 1;
+
+// ========================================================================= //
+
+/// A quick hasher for packed state keys, using the SplitMix64 finalizer.
+/// Searches hash millions of keys, and don't need the DoS resistance of the
+/// default hasher.
+#[derive(Default)]
+struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        let mut hash = self.0;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 ^= value;
+    }
+}
+
+type KeyMap<V> = HashMap<u64, V, BuildHasherDefault<KeyHasher>>;
+
+// ========================================================================= //
+
+#[derive(Clone, Copy, PartialEq)]
+enum Cell {
+    Outside,
+    Inside(Option<Object>),
+}
+
+/// The parts of an `ObjectGrid` that change as blocks slide.  Positions are
+/// cell indices, and symbols and directions are small codes; blocks and
+/// push-pops are kept in a fixed order, so that slides can refer to blocks by
+/// index.
+#[derive(Clone, Copy)]
+struct State {
+    blocks: [(i32, u32); MAX_BLOCKS],
+    push_pops: [(i32, u32); MAX_PUSH_POPS],
+}
+
+/// A search over the states of an `ObjectGrid`.  Each state is packed into a
+/// `u64`, so that large searches stay small in memory.
+struct Search {
+    /// The width of the padded grid; push-pops can be pushed off the edge of
+    /// the grid, so there is a margin of cells around it.
+    width: i32,
+    margin: i32,
+    cells: Vec<Cell>,
+    num_blocks: usize,
+    num_push_pops: usize,
+    position_bits: u32,
+    symbols: Vec<Symbol>,
+    /// Relaxed distances to a goal, indexed by symbol code and then cell.
+    distances: Vec<u32>,
+    start: State,
+}
+
+impl Search {
+    fn new(grid: &ObjectGrid) -> Option<Search> {
+        let (num_cols, num_rows) = grid.size();
+        let mut push_pops: Vec<(Point, Direction)> = grid
+            .objects()
+            .iter()
+            .filter_map(|(&coords, object)| match object {
+                &Object::PushPop(dir) => Some((coords, dir)),
+                _ => None,
+            })
+            .collect();
+        push_pops.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        let mut blocks: Vec<(Point, Symbol)> = grid
+            .ice_blocks()
+            .iter()
+            .map(|(&coords, &symbol)| (coords, symbol))
+            .collect();
+        blocks.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        if blocks.len() > MAX_BLOCKS || push_pops.len() > MAX_PUSH_POPS {
+            return None;
+        }
+
+        let margin = push_pops.len() as i32 + 1;
+        let width = num_cols + 2 * margin;
+        let height = num_rows + 2 * margin;
+        let mut cells = vec![Cell::Outside; (width * height) as usize];
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                let object = grid.objects().get(&Point::new(col, row));
+                let object = match object {
+                    Some(&Object::PushPop(_)) | None => None,
+                    Some(&object) => Some(object),
+                };
+                let index = (row + margin) * width + (col + margin);
+                cells[index as usize] = Cell::Inside(object);
+            }
+        }
+        let mut position_bits = 1;
+        while (1 << position_bits) < cells.len() {
+            position_bits += 1;
+        }
+        let num_bits = blocks.len() as u32 * (position_bits + SYMBOL_BITS)
+            + push_pops.len() as u32 * (position_bits + DIRECTION_BITS);
+        if num_bits > 64 {
+            return None;
+        }
+
+        let mut search = Search {
+            width,
+            margin,
+            cells,
+            num_blocks: blocks.len(),
+            num_push_pops: push_pops.len(),
+            position_bits,
+            symbols: Vec::new(),
+            distances: Vec::new(),
+            start: State {
+                blocks: [(0, 0); MAX_BLOCKS],
+                push_pops: [(0, 0); MAX_PUSH_POPS],
+            },
+        };
+        for (index, &(coords, symbol)) in blocks.iter().enumerate() {
+            let cell = search.cell_index(coords);
+            search.start.blocks[index] = (cell, search.symbol_code(symbol));
+        }
+        for (index, &(coords, dir)) in push_pops.iter().enumerate() {
+            let cell = search.cell_index(coords);
+            search.start.push_pops[index] = (cell, direction_code(dir));
+        }
+        Some(search)
+    }
+
+    fn cell_index(&self, coords: Point) -> i32 {
+        (coords.y() + self.margin) * self.width + (coords.x() + self.margin)
+    }
+
+    fn coords(&self, cell: i32) -> Point {
+        Point::new(
+            cell % self.width - self.margin,
+            cell / self.width - self.margin,
+        )
+    }
+
+    fn step(&self, dir: u32) -> i32 {
+        match dir {
+            0 => 1,
+            1 => self.width,
+            2 => -1,
+            _ => -self.width,
+        }
+    }
+
+    fn symbol_code(&mut self, symbol: Symbol) -> u32 {
+        if let Some(code) = self.symbols.iter().position(|&s| s == symbol) {
+            return code as u32;
+        }
+        self.symbols.push(symbol);
+        self.distances.extend(vec![UNKNOWN; self.cells.len()]);
+        debug_assert!(self.symbols.len() <= 1 << SYMBOL_BITS);
+        (self.symbols.len() - 1) as u32
+    }
+
+    fn is_goal(&self, cell: i32, code: u32) -> bool {
+        self.cells[cell as usize]
+            == Cell::Inside(Some(Object::Goal(self.symbols[code as usize])))
+    }
+
+    fn is_solved(&self, state: &State) -> bool {
+        state.blocks[..self.num_blocks]
+            .iter()
+            .all(|&(cell, code)| self.is_goal(cell, code))
+    }
+
+    /// Returns a lower bound on the number of slides needed to solve the grid
+    /// from `state`, or `None` if some block can never reach a goal.
+    fn estimate(&mut self, state: &State) -> Option<u32> {
+        let mut total = 0;
+        for index in 0..self.num_blocks {
+            let (cell, code) = state.blocks[index];
+            total += self.distance(cell, code)?;
+        }
+        Some(total)
+    }
+
+    /// Returns the number of slides a block needs to reach a matching goal
+    /// if it could stop anywhere along a slide and pass through push-pops,
+    /// or `None` if it can't reach one even then.
+    fn distance(&mut self, cell: i32, code: u32) -> Option<u32> {
+        let index = code as usize * self.cells.len() + cell as usize;
+        if self.distances[index] == UNKNOWN {
+            let mut visited = HashMap::new();
+            visited.insert((cell, code), 0);
+            let mut queue = VecDeque::new();
+            queue.push_back((cell, code));
+            let mut result = UNREACHABLE;
+            while let Some((cell, code)) = queue.pop_front() {
+                let distance = visited[&(cell, code)];
+                if self.is_goal(cell, code) {
+                    result = distance;
+                    break;
+                }
+                for dir in 0..4 {
+                    let step = self.step(dir);
+                    let mut next = cell;
+                    let mut transform = Transform::identity();
+                    loop {
+                        next += step;
+                        match self.cells[next as usize] {
+                            Cell::Outside
+                            | Cell::Inside(Some(Object::Gap))
+                            | Cell::Inside(Some(Object::Wall)) => break,
+                            cell => transform = transformed(transform, cell),
+                        }
+                        let symbol = self.symbols[code as usize];
+                        let next_code =
+                            self.symbol_code(symbol.transformed(transform));
+                        if let Entry::Vacant(entry) =
+                            visited.entry((next, next_code))
+                        {
+                            entry.insert(distance + 1);
+                            queue.push_back((next, next_code));
+                        }
+                    }
+                }
+            }
+            self.distances[index] = result;
+        }
+        match self.distances[index] {
+            UNREACHABLE => None,
+            distance => Some(distance),
+        }
+    }
+
+    fn block_at(&self, state: &State, cell: i32, except: usize) -> bool {
+        state.blocks[..self.num_blocks]
+            .iter()
+            .enumerate()
+            .any(|(index, &(block, _))| index != except && block == cell)
+    }
+
+    fn push_pop_at(
+        &self,
+        push_pops: &[(i32, u32)],
+        cell: i32,
+    ) -> Option<usize> {
+        push_pops[..self.num_push_pops].iter().position(|&(pp, _)| pp == cell)
+    }
+
+    /// Slides the `index`th block of `state`, following the same rules as
+    /// `ObjectGrid::slide_ice_block`.  Returns `None` if the block can't move.
+    fn slide(
+        &mut self,
+        state: &State,
+        index: usize,
+        dir: u32,
+    ) -> Option<State> {
+        let (start, code) = state.blocks[index];
+        let step = self.step(dir);
+        let mut push_pops = state.push_pops;
+        let mut cell = start;
+        let mut transform = Transform::identity();
+        loop {
+            let next = cell + step;
+            if self.cells[next as usize] == Cell::Outside
+                || self.block_at(state, next, index)
+            {
+                break;
+            }
+            if let Some(pp) = self.push_pop_at(&push_pops, next) {
+                if push_pops[pp].1 != (dir + 2) % 4 {
+                    break;
+                }
+                let mut pp_cell = next + step;
+                while self.push_pop_at(&push_pops, pp_cell).is_some()
+                    || matches!(
+                        self.cells[pp_cell as usize],
+                        Cell::Inside(Some(_))
+                    )
+                {
+                    pp_cell += step;
+                }
+                if self.block_at(state, pp_cell, index) {
+                    break;
+                }
+                push_pops[pp] = (pp_cell, dir);
+            } else {
+                match self.cells[next as usize] {
+                    Cell::Inside(Some(Object::Gap))
+                    | Cell::Inside(Some(Object::Wall)) => break,
+                    cell => transform = transformed(transform, cell),
+                }
+            }
+            cell = next;
+        }
+        if cell == start {
+            return None;
+        }
+        let symbol = self.symbols[code as usize].transformed(transform);
+        let mut blocks = state.blocks;
+        blocks[index] = (cell, self.symbol_code(symbol));
+        Some(State { blocks, push_pops })
+    }
+
+    /// Returns each state reachable from `state` in one slide, along with
+    /// the coordinates and direction of that slide.
+    fn successors(&mut self, state: &State) -> Vec<(i32, u32, State)> {
+        let mut successors = Vec::new();
+        for index in 0..self.num_blocks {
+            for dir in 0..4 {
+                if let Some(next) = self.slide(state, index, dir) {
+                    successors.push((state.blocks[index].0, dir, next));
+                }
+            }
+        }
+        successors
+    }
+
+    fn encode(&self, state: &State) -> u64 {
+        let mut key = 0;
+        for &(cell, code) in state.blocks[..self.num_blocks].iter() {
+            key = (key << self.position_bits) | cell as u64;
+            key = (key << SYMBOL_BITS) | code as u64;
+        }
+        for &(cell, dir) in state.push_pops[..self.num_push_pops].iter() {
+            key = (key << self.position_bits) | cell as u64;
+            key = (key << DIRECTION_BITS) | dir as u64;
+        }
+        key
+    }
+
+    fn decode(&self, mut key: u64) -> State {
+        let position_mask = (1 << self.position_bits) - 1;
+        let mut state = self.start;
+        for entry in state.push_pops[..self.num_push_pops].iter_mut().rev() {
+            let dir = (key & ((1 << DIRECTION_BITS) - 1)) as u32;
+            key >>= DIRECTION_BITS;
+            *entry = ((key & position_mask) as i32, dir);
+            key >>= self.position_bits;
+        }
+        for entry in state.blocks[..self.num_blocks].iter_mut().rev() {
+            let code = (key & ((1 << SYMBOL_BITS) - 1)) as u32;
+            key >>= SYMBOL_BITS;
+            *entry = ((key & position_mask) as i32, code);
+            key >>= self.position_bits;
+        }
+        state
+    }
+}
+
+fn direction_code(dir: Direction) -> u32 {
+    match dir {
+        Direction::East => 0,
+        Direction::South => 1,
+        Direction::West => 2,
+        Direction::North => 3,
+    }
+}
+
+fn direction(code: u32) -> Direction {
+    match code {
+        0 => Direction::East,
+        1 => Direction::South,
+        2 => Direction::West,
+        _ => Direction::North,
+    }
+}
+
+/// Returns the transform a block picks up by sliding over the given cell.
+fn transformed(transform: Transform, cell: Cell) -> Transform {
+    match cell {
+        Cell::Inside(Some(Object::Rotator)) => transform.rotated_cw(),
+        Cell::Inside(Some(Object::Reflector(false))) => {
+            transform.flipped_horz()
+        }
+        Cell::Inside(Some(Object::Reflector(true))) => {
+            transform.flipped_vert()
+        }
+        _ => transform,
+    }
+}
+
+// ========================================================================= //
+
+/// Searches for the shortest sequence of slides that puts every ice block in
+/// the grid onto a goal with a matching symbol.  Each move is the coordinates
+/// of the block to slide and the direction to slide it in.  Returns `None` if
+/// there is no such sequence, or if the grid has too many blocks and
+/// push-pops to search.
+#[cfg(test)]
+pub fn shortest_solution(
+    grid: &ObjectGrid,
+) -> Option<Vec<(Point, Direction)>> {
+    shortest_solution_unless_cancelled(grid, &AtomicBool::new(false))
+}
+
+/// Like `shortest_solution`, but gives up and returns `None` as soon as
+/// `cancelled` is set, so that a search running on another thread can be
+/// abandoned once its result is no longer wanted.
+pub fn shortest_solution_unless_cancelled(
+    grid: &ObjectGrid,
+    cancelled: &AtomicBool,
+) -> Option<Vec<(Point, Direction)>> {
+    // This is an A* search.  Each slide moves only one block, so the sum of
+    // the blocks' relaxed distances is a consistent heuristic.
+    let mut search = Search::new(grid)?;
+    let start = search.start;
+    let start_estimate = search.estimate(&start)?;
+    let start_key = search.encode(&start);
+    let mut best_moves: KeyMap<u32> = KeyMap::default();
+    best_moves.insert(start_key, 0);
+    // Each node is a state key, the index of the node it was reached from,
+    // and the cell and direction of the slide that reached it.
+    let mut nodes: Vec<(u64, u32, i32, u32)> = vec![(start_key, 0, 0, 0)];
+    let mut queue = BinaryHeap::new();
+    queue.push((Reverse(start_estimate), 0, 0));
+    while let Some((_, num_moves, node)) = queue.pop() {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let key = nodes[node as usize].0;
+        if best_moves[&key] < num_moves {
+            continue;
+        }
+        let state = search.decode(key);
+        if search.is_solved(&state) {
+            let mut moves = Vec::new();
+            let mut node = node as usize;
+            while node != 0 {
+                let (_, parent, cell, dir) = nodes[node];
+                moves.push((search.coords(cell), direction(dir)));
+                node = parent as usize;
+            }
+            moves.reverse();
+            return Some(moves);
+        }
+        for (cell, dir, next) in search.successors(&state) {
+            let key = search.encode(&next);
+            let best = best_moves.entry(key).or_insert(u32::MAX);
+            if *best <= num_moves + 1 {
+                continue;
+            }
+            *best = num_moves + 1;
+            if let Some(estimate) = search.estimate(&next) {
+                let index = nodes.len() as u32;
+                nodes.push((key, node, cell, dir));
+                let cost = num_moves + 1 + estimate;
+                queue.push((Reverse(cost), num_moves + 1, index));
+            }
+        }
+    }
+    None
+}
+
+/// Explores every state that can be reached from the grid by sliding blocks,
+/// and returns how many of them are dead ends, from which the grid can no
+/// longer be solved.  Returns `None` if the grid is too big to search.
+#[cfg(test)]
+pub fn count_dead_ends(grid: &ObjectGrid) -> Option<usize> {
+    // This is Tarjan's strongly-connected-components algorithm, run over the
+    // implicit graph of grid states.  Components are completed in reverse
+    // topological order, so by the time a component is completed, we already
+    // know whether each component it leads to can reach a solved state.
+    const UNFINISHED: u32 = u32::MAX;
+    let mut search = Search::new(grid)?;
+    let mut ids: KeyMap<u32> = KeyMap::default();
+    let mut lowlink: Vec<u32> = Vec::new();
+    let mut reaches_goal: Vec<bool> = Vec::new();
+    let mut component: Vec<u32> = Vec::new();
+    let mut component_good: Vec<bool> = Vec::new();
+    let mut stack: Vec<u32> = Vec::new();
+    let mut frames: Vec<(u32, Vec<u64>)> = Vec::new();
+    let mut next = Some(search.encode(&search.start));
+    loop {
+        if let Some(key) = next.take() {
+            let state = search.decode(key);
+            let id = lowlink.len() as u32;
+            ids.insert(key, id);
+            lowlink.push(id);
+            reaches_goal.push(search.is_solved(&state));
+            component.push(UNFINISHED);
+            stack.push(id);
+            let successors = search.successors(&state);
+            let keys = successors.iter().map(|s| search.encode(&s.2));
+            frames.push((id, keys.collect()));
+        }
+        let (id, successor) = match frames.last_mut() {
+            Some(&mut (id, ref mut keys)) => (id as usize, keys.pop()),
+            None => break,
+        };
+        if let Some(key) = successor {
+            match ids.get(&key) {
+                None => next = Some(key),
+                Some(&other) => {
+                    let other = other as usize;
+                    if component[other] == UNFINISHED {
+                        lowlink[id] = lowlink[id].min(lowlink[other]);
+                    } else if component_good[component[other] as usize] {
+                        reaches_goal[id] = true;
+                    }
+                }
+            }
+            continue;
+        }
+        frames.pop();
+        if lowlink[id] == id as u32 {
+            let split = stack.iter().rposition(|&member| member == id as u32);
+            let members = stack.split_off(split.unwrap());
+            let good = members.iter().any(|&m| reaches_goal[m as usize]);
+            for &member in members.iter() {
+                component[member as usize] = component_good.len() as u32;
+                reaches_goal[member as usize] = good;
+            }
+            component_good.push(good);
+        }
+        if let Some(&(parent, _)) = frames.last() {
+            let parent = parent as usize;
+            lowlink[parent] = lowlink[parent].min(lowlink[id]);
+            reaches_goal[parent] |= reaches_goal[id];
+        }
+    }
+    Some(reaches_goal.iter().filter(|&&good| !good).count())
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{direction, Search, State};
+    use crate::gui::Point;
+    use crate::save::ice::{Object, ObjectGrid, Symbol, Transform};
+    use crate::save::util::Tomlable;
+    use crate::save::Direction::{self, East, North, South, West};
+    use crate::save::{BlindState, MeetState, RightState, SyzygyState};
+
+    type Layout = (Vec<(Point, Symbol)>, Vec<(Point, Direction)>);
+
+    fn grid_layout(grid: &ObjectGrid) -> Layout {
+        let mut blocks: Vec<(Point, Symbol)> = grid
+            .ice_blocks()
+            .iter()
+            .map(|(&coords, &symbol)| (coords, symbol))
+            .collect();
+        blocks.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        let mut push_pops: Vec<(Point, Direction)> = grid
+            .objects()
+            .iter()
+            .filter_map(|(&coords, object)| match object {
+                &Object::PushPop(dir) => Some((coords, dir)),
+                _ => None,
+            })
+            .collect();
+        push_pops.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        (blocks, push_pops)
+    }
+
+    fn state_layout(search: &Search, state: &State) -> Layout {
+        let mut blocks: Vec<(Point, Symbol)> = state.blocks
+            [..search.num_blocks]
+            .iter()
+            .map(|&(cell, code)| {
+                (search.coords(cell), search.symbols[code as usize])
+            })
+            .collect();
+        blocks.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        let mut push_pops: Vec<(Point, Direction)> = state.push_pops
+            [..search.num_push_pops]
+            .iter()
+            .map(|&(cell, dir)| (search.coords(cell), direction(dir)))
+            .collect();
+        push_pops.sort_by_key(|&(coords, _)| (coords.y(), coords.x()));
+        (blocks, push_pops)
+    }
+
+    /// Walks every state reachable from `grid`, checking that each slide the
+    /// solver makes (once packed into a key and back) agrees with
+    /// `ObjectGrid::slide_ice_block`.  Returns the number of states visited.
+    fn check_slides_match_grid(grid: ObjectGrid) -> usize {
+        let mut search = Search::new(&grid).unwrap();
+        let start = search.start;
+        let mut seen = HashSet::new();
+        seen.insert(search.encode(&start));
+        let mut stack = vec![(grid, start)];
+        while let Some((grid, state)) = stack.pop() {
+            for index in 0..search.num_blocks {
+                let coords = search.coords(state.blocks[index].0);
+                for dir in 0..4 {
+                    let mut next_grid = grid.clone();
+                    let slide =
+                        next_grid.slide_ice_block(coords, direction(dir));
+                    let next = search.slide(&state, index, dir);
+                    assert_eq!(
+                        slide.is_some(),
+                        next.is_some(),
+                        "sliding {:?} {:?} from {:?}",
+                        coords,
+                        direction(dir),
+                        grid_layout(&grid)
+                    );
+                    if let Some(next) = next {
+                        let key = search.encode(&next);
+                        let next = search.decode(key);
+                        assert_eq!(
+                            state_layout(&search, &next),
+                            grid_layout(&next_grid),
+                            "sliding {:?} {:?} from {:?}",
+                            coords,
+                            direction(dir),
+                            grid_layout(&grid)
+                        );
+                        if seen.insert(key) {
+                            stack.push((next_grid, next));
+                        }
+                    }
+                }
+            }
+        }
+        seen.len()
+    }
+
+    #[test]
+    fn slides_match_object_grid() {
+        // Transforms, gaps and walls, with blocks stopping against each
+        // other.
+        let mut grid = ObjectGrid::new(4, 3);
+        grid.add_object(1, 0, Object::Rotator);
+        grid.add_object(2, 1, Object::Reflector(false));
+        grid.add_object(1, 2, Object::Reflector(true));
+        grid.add_object(3, 2, Object::Gap);
+        grid.add_object(0, 2, Object::Wall);
+        grid.add_object(2, 2, Object::Goal(Symbol::BlueCircle));
+        grid.add_ice_block(0, 0, Symbol::CyanQ(Transform::identity()));
+        grid.add_ice_block(3, 0, Symbol::YellowRhombus(false, true));
+        assert!(check_slides_match_grid(grid) > 1000);
+
+        // Push-pops facing different ways, in a row so that they get pushed past
+        // each other and off the edge of the grid.
+        let mut grid = ObjectGrid::new(5, 3);
+        grid.add_object(1, 1, Object::PushPop(Direction::West));
+        grid.add_object(2, 1, Object::PushPop(Direction::West));
+        grid.add_object(3, 0, Object::PushPop(Direction::South));
+        grid.add_object(4, 1, Object::Rotator);
+        grid.add_object(2, 2, Object::Goal(Symbol::GreenSquare));
+        grid.add_ice_block(0, 1, Symbol::RedTriangle(Direction::East));
+        grid.add_ice_block(0, 0, Symbol::GreenSquare);
+        assert!(check_slides_match_grid(grid) > 1000);
+    }
+
+    #[test]
+    fn solved_grid_needs_no_moves() {
+        let mut grid = ObjectGrid::new(3, 1);
+        grid.add_object(1, 0, Object::Goal(Symbol::BlueCircle));
+        grid.add_ice_block(1, 0, Symbol::BlueCircle);
+        assert_eq!(grid.solution(), Some(vec![]));
+    }
+
+    #[test]
+    fn blocks_stop_against_other_blocks() {
+        let mut grid = ObjectGrid::new(4, 1);
+        grid.add_object(2, 0, Object::Goal(Symbol::BlueCircle));
+        grid.add_object(3, 0, Object::Goal(Symbol::GreenSquare));
+        grid.add_ice_block(0, 0, Symbol::BlueCircle);
+        grid.add_ice_block(3, 0, Symbol::GreenSquare);
+        assert_eq!(
+            grid.solution(),
+            Some(vec![(Point::new(0, 0), Direction::East)])
+        );
+    }
+
+    #[test]
+    fn finds_shortest_solution() {
+        let mut grid = ObjectGrid::new(3, 3);
+        grid.add_object(1, 1, Object::Goal(Symbol::BlueCircle));
+        grid.add_object(2, 1, Object::Wall);
+        grid.add_object(0, 2, Object::Wall);
+        grid.add_ice_block(0, 0, Symbol::BlueCircle);
+        let moves = grid.solution().unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                (Point::new(0, 0), Direction::South),
+                (Point::new(0, 1), Direction::East),
+            ]
+        );
+        for (coords, dir) in moves {
+            assert!(grid.slide_ice_block(coords, dir).is_some());
+        }
+        assert!(grid.all_blocks_on_goals());
+    }
+
+    #[test]
+    fn symbols_must_match_goals() {
+        let goal = Symbol::CyanQ(Transform::identity().rotated_cw());
+        let mut grid = ObjectGrid::new(3, 1);
+        grid.add_object(2, 0, Object::Goal(goal));
+        grid.add_ice_block(0, 0, Symbol::CyanQ(Transform::identity()));
+        assert_eq!(grid.solution(), None);
+        grid.add_object(1, 0, Object::Rotator);
+        assert_eq!(
+            grid.solution(),
+            Some(vec![(Point::new(0, 0), Direction::East)])
+        );
+    }
+
+    #[test]
+    fn push_pops_block_slides_from_behind() {
+        let grid = |dir| {
+            let mut grid = ObjectGrid::new(4, 1);
+            grid.add_object(3, 0, Object::Goal(Symbol::BlueCircle));
+            grid.add_object(2, 0, Object::PushPop(dir));
+            grid.add_ice_block(0, 0, Symbol::BlueCircle);
+            grid
+        };
+        assert_eq!(grid(Direction::East).solution(), None);
+        assert_eq!(
+            grid(Direction::West).solution(),
+            Some(vec![(Point::new(0, 0), Direction::East)])
+        );
+    }
+
+    type Slides = &'static [(i32, i32, Direction)];
+
+    // The first slides of a shortest solution for some of the puzzles' grids.
+    // Replaying these before searching keeps the search quick, even in an
+    // unoptimized build.
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const BLIND_SLIDES: Slides = &[
+        (7, 4, West), (2, 0, East), (4, 4, North), (4, 0, East), (5, 0, South),
+        (6, 0, West), (5, 2, North), (0, 0, East), (4, 0, South), (7, 2, West),
+        (0, 2, North), (5, 0, West), (0, 0, South), (0, 6, East),
+        (3, 6, North), (3, 4, East), (4, 4, North), (4, 0, West),
+        (2, 0, South),
+    ];
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const RIGHT_SLIDES: Slides = &[
+        (9, 0, West), (0, 0, South), (1, 1, West), (0, 3, North), (0, 2, East),
+        (9, 2, West), (0, 1, East), (2, 1, South), (2, 5, East), (3, 4, East),
+        (5, 4, South), (9, 5, West), (6, 5, North), (5, 5, North),
+        (5, 4, West), (1, 4, North), (1, 0, East),
+    ];
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    const ARGONY_SLIDES: Slides = &[
+        (7, 0, South), (8, 0, West), (6, 0, South), (6, 3, West),
+        (5, 3, North), (5, 1, West), (0, 1, East), (7, 1, South), (7, 2, West),
+        (7, 3, North), (7, 1, West), (5, 0, South), (7, 0, West), (5, 1, West),
+    ];
+
+    #[test]
+    fn puzzle_grids_have_solutions() {
+        // Each puzzle's starting grid, the slides to replay, and the number
+        // of slides the shortest solution from there should take.
+        let empty = || toml::Value::Boolean(false);
+        let puzzles: Vec<(ObjectGrid, Slides, usize)> = vec![
+            (MeetState::from_toml(empty()).grid().clone(), &[], 30),
+            (BlindState::from_toml(empty()).grid().clone(), BLIND_SLIDES, 19),
+            (RightState::from_toml(empty()).grid().clone(), RIGHT_SLIDES, 17),
+            (
+                SyzygyState::from_toml(empty()).argony_grid().clone(),
+                ARGONY_SLIDES,
+                15,
+            ),
+        ];
+        for (mut grid, slides, num_moves) in puzzles {
+            for &(col, row, dir) in slides.iter() {
+                assert!(grid
+                    .slide_ice_block(Point::new(col, row), dir)
+                    .is_some());
+            }
+            let moves = grid.solution().unwrap();
+            assert_eq!(moves.len(), num_moves);
+            for (coords, dir) in moves {
+                assert!(grid.slide_ice_block(coords, dir).is_some());
+            }
+            assert!(grid.all_blocks_on_goals());
+        }
+    }
+
+    #[test]
+    fn count_dead_ends() {
+        let mut grid = ObjectGrid::new(3, 1);
+        grid.add_object(0, 0, Object::Goal(Symbol::BlueCircle));
+        grid.add_ice_block(1, 0, Symbol::BlueCircle);
+        assert_eq!(grid.count_dead_ends(), Some(0));
+
+        // Once the push-pop has been pushed off the grid, nothing can stop
+        // the block on the goal.
+        let mut grid = ObjectGrid::new(3, 1);
+        grid.add_object(1, 0, Object::Goal(Symbol::BlueCircle));
+        grid.add_object(2, 0, Object::PushPop(Direction::West));
+        grid.add_ice_block(0, 0, Symbol::BlueCircle);
+        assert_eq!(grid.solution(), None);
+        assert_eq!(grid.count_dead_ends(), Some(3));
+    }
+}
+
+// ========================================================================= //
@@ -21,6 +21,7 @@ use rand;
 use std::collections::{HashMap, HashSet};
 use toml;
 
+use crate::save::random;
 use crate::save::util::Tomlable;
 use crate::save::Direction;
 
@@ -166,11 +167,9 @@ impl Grid {
                 indices.push(index);
             }
         }
-        let sample = rand::seq::sample_slice(
-            &mut rand::thread_rng(),
-            &indices,
-            indices.len().min(num),
-        );
+        let sample = random::with_rng(|rng| {
+            rand::seq::sample_slice(rng, &indices, indices.len().min(num))
+        });
         for index in sample {
             self.values[index] = -self.values[index];
         }
@@ -0,0 +1,155 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use toml;
+
+use super::util::{pop_table, Tomlable};
+
+// ========================================================================= //
+
+pub const VERSION_KEY: &str = "version";
+
+/// The save format version written by this version of the game.  Whenever
+/// the layout of the save file changes in a way that old files can't be read
+/// as-is, bump this and add a migration to `MIGRATIONS`.
+pub const CURRENT_VERSION: i64 = 1;
+
+struct Migration {
+    /// The version that this migration upgrades from (to `from + 1`).
+    from: i64,
+    description: &'static str,
+    apply: fn(&mut toml::value::Table),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "move single game into a named slot",
+    apply: migrate_single_game_to_slots,
+}];
+
+// ========================================================================= //
+
+/// Returns the save format version of a top-level save table.  Save files
+/// from before versioning was introduced are treated as version 0.
+pub fn save_version(table: &toml::value::Table) -> i64 {
+    table.get(VERSION_KEY).and_then(toml::Value::as_integer).unwrap_or(0)
+}
+
+/// Upgrades a top-level save table, in place, to `CURRENT_VERSION` by running
+/// each needed migration in order.  Tables from a newer version of the game
+/// than this one can't be downgraded, so callers should check `save_version`
+/// and refuse to load them rather than calling this.
+pub fn migrate(table: &mut toml::value::Table) {
+    let mut version = save_version(table);
+    table.remove(VERSION_KEY);
+    for migration in MIGRATIONS.iter() {
+        if migration.from == version {
+            if cfg!(debug_assertions) {
+                println!(
+                    "Migrating save data from format {}: {}",
+                    version, migration.description
+                );
+            }
+            (migration.apply)(table);
+            version += 1;
+        }
+    }
+}
+
+// Migrations spell out their key names literally, rather than sharing the
+// constants used by the current loading code, so that they keep describing
+// the old formats even after those constants change.
+
+/// Version 0 stored one game under the top-level `game` key; version 1 keeps
+/// any number of games under `slots`, each in its own named table.
+fn migrate_single_game_to_slots(table: &mut toml::value::Table) {
+    const DEFAULT_SLOT_NAME: &str = "Slot 1";
+    let mut slots = pop_table(table, "slots");
+    if let Some(game) = table.remove("game") {
+        if !slots.contains_key(DEFAULT_SLOT_NAME) {
+            let mut slot = toml::value::Table::new();
+            slot.insert("game".to_string(), game);
+            slots.insert(
+                DEFAULT_SLOT_NAME.to_string(),
+                toml::Value::Table(slot),
+            );
+            table.insert(
+                "current_slot".to_string(),
+                DEFAULT_SLOT_NAME.to_string().to_toml(),
+            );
+        }
+    }
+    table.insert("slots".to_string(), toml::Value::Table(slots));
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use toml;
+
+    use super::{
+        migrate, save_version, CURRENT_VERSION, MIGRATIONS, VERSION_KEY,
+    };
+    use crate::save::util::to_table;
+
+    const SAVE_V0: &str = include_str!("testdata/save_v0.toml");
+    const SAVE_V1: &str = include_str!("testdata/save_v1.toml");
+
+    fn parse(string: &str) -> toml::value::Table {
+        to_table(string.parse::<toml::Value>().unwrap())
+    }
+
+    #[test]
+    fn migrations_are_in_order() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from, index as i64);
+        }
+        assert_eq!(MIGRATIONS.len() as i64, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn migrate_v0_to_v1() {
+        let mut table = parse(SAVE_V0);
+        migrate(&mut table);
+        let expected = parse(SAVE_V1);
+        assert_eq!(table.get("current_slot"), expected.get("current_slot"));
+        assert_eq!(table.get("slots"), expected.get("slots"));
+        assert_eq!(table.get("prefs"), parse(SAVE_V0).get("prefs"));
+        assert!(!table.contains_key("game"));
+        assert!(!table.contains_key(VERSION_KEY));
+    }
+
+    #[test]
+    fn fixture_versions() {
+        assert_eq!(save_version(&parse(SAVE_V0)), 0);
+        assert_eq!(save_version(&parse(SAVE_V1)), 1);
+    }
+
+    #[test]
+    fn current_version_is_unchanged() {
+        let mut table = parse(SAVE_V1);
+        migrate(&mut table);
+        let mut expected = parse(SAVE_V1);
+        expected.remove(VERSION_KEY);
+        assert_eq!(table, expected);
+    }
+}
+
+// ========================================================================= //
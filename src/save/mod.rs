@@ -18,6 +18,7 @@
 // +--------------------------------------------------------------------------+
 
 mod access;
+mod backup;
 mod color;
 pub mod column;
 mod crossword;
@@ -28,23 +29,26 @@ mod game;
 pub mod ice;
 mod location;
 pub mod memory;
+mod migrate;
 mod path;
 pub mod plane;
 mod prefs;
 mod puzzles;
 pub mod pyramid;
+pub mod random;
 pub mod tree;
 pub mod util;
+mod validate;
 
 pub use self::access::Access;
 pub use self::color::{MixedColor, PrimaryColor};
 pub use self::crossword::{CrosswordState, ValidChars};
-pub use self::data::SaveData;
+pub use self::data::{SaveData, SaveRecovery};
 pub use self::direction::Direction;
 pub use self::game::Game;
 pub use self::location::Location;
 pub use self::path::get_default_save_file_path;
-pub use self::prefs::Prefs;
+pub use self::prefs::{Prefs, MAX_VOLUME, MAX_WINDOW_SCALE};
 pub use self::puzzles::{
     AtticState, AutoState, BlackState, BlameState, BlindState, CubeState,
     DayState, DisconState, DotsState, DoubleState, FailureState, FictionState,
@@ -18,12 +18,18 @@
 // +--------------------------------------------------------------------------+
 
 use std::cmp;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use toml;
 
 use crate::gui::Point;
 use crate::save::util::Tomlable;
-use crate::save::MixedColor;
+use crate::save::{Direction, MixedColor};
+
+// ========================================================================= //
+
+// The solver stops looking once it has found this many solutions, since
+// that's enough to tell whether the solution is unique.
+const MAX_SOLUTIONS: usize = 2;
 
 // ========================================================================= //
 
@@ -83,11 +89,15 @@ impl PlaneGrid {
     }
 
     pub fn set_pipes_from_toml(&mut self, pipes: toml::value::Array) {
-        self.pipes.clear();
         let pipes = Vec::<Vec<Point>>::from_toml(toml::Value::Array(pipes));
-        for pipe in pipes.into_iter() {
+        self.set_pipes(&pipes);
+    }
+
+    pub fn set_pipes(&mut self, pipes: &[Vec<Point>]) {
+        self.pipes.clear();
+        for pipe in pipes.iter() {
             if !pipe.is_empty() {
-                let mut pipe = pipe.into_iter();
+                let mut pipe = pipe.iter().cloned();
                 let mut p1 = pipe.next().unwrap();
                 for p2 in pipe {
                     self.toggle_pipe(p1, p2);
@@ -327,7 +337,7 @@ impl PlaneGrid {
         true
     }
 
-    pub fn all_nodes_are_connected(&self) -> bool {
+    fn red_and_blue_nodes(&self) -> (Vec<Point>, Vec<Point>) {
         let mut red_nodes = Vec::new();
         let mut blue_nodes = Vec::new();
         for (&pt, &obj) in self.objects.iter() {
@@ -341,6 +351,11 @@ impl PlaneGrid {
                 _ => {}
             }
         }
+        (red_nodes, blue_nodes)
+    }
+
+    pub fn all_nodes_are_connected(&self) -> bool {
+        let (red_nodes, blue_nodes) = self.red_and_blue_nodes();
         let mut node_pairs = HashSet::new();
         for node1 in red_nodes.iter() {
             for node2 in blue_nodes.iter() {
@@ -392,6 +407,548 @@ impl PlaneGrid {
         }
         gray_nodes
     }
+
+    /// Searches for a set of pipes that connects every red or purple node to
+    /// every blue or purple node, as `all_nodes_are_connected` requires,
+    /// ignoring whatever pipes have been laid so far.  Returns `Ok(None)` if
+    /// there is no such set.
+    ///
+    /// Grids with gray or green nodes are solved by the colors their gray
+    /// nodes end up (see `gray_node_colors`), which the solver doesn't model,
+    /// so for those it returns an error rather than a misleading answer.
+    /// Solutions that differ only by a pipe taking a needless detour (running
+    /// alongside itself, where it could have cut across) are not counted as
+    /// different when deciding whether the solution is unique.
+    ///
+    /// Nothing in the game needs this; it's for checking new stages as
+    /// they're designed (see the `plane-solve` command).
+    pub fn solution(&self) -> Result<Option<PlaneSolution>, String> {
+        self.solution_extending(&[])
+    }
+
+    /// Like `solution`, but only searches for solutions that include each of
+    /// the given pipes.  All the pipes leaving a node must be given together,
+    /// or none of them.
+    pub fn solution_extending(
+        &self,
+        laid: &[Vec<Point>],
+    ) -> Result<Option<PlaneSolution>, String> {
+        if self.objects.values().any(|&obj| {
+            obj == PlaneObj::GrayNode || obj == PlaneObj::GreenNode
+        }) {
+            return Err(
+                "Can't solve grids with gray or green nodes.".to_string()
+            );
+        }
+        let (mut red_nodes, mut blue_nodes) = self.red_and_blue_nodes();
+        red_nodes.sort_by_key(|pt| (pt.y(), pt.x()));
+        blue_nodes.sort_by_key(|pt| (pt.y(), pt.x()));
+        let mut pairs = Vec::new();
+        for &node1 in red_nodes.iter() {
+            for &node2 in blue_nodes.iter() {
+                if node1 != node2 && !pairs.contains(&(node2, node1)) {
+                    pairs.push((node1, node2));
+                }
+            }
+        }
+        let mut search = PipeSearch::new(self);
+        for (from, to) in pairs {
+            // Neighboring nodes can only be connected directly, since any
+            // longer pipe between them would be a detour.
+            if are_adjacent(from, to) {
+                search.pipes.push(vec![from, to]);
+            } else {
+                search.pipes.push(vec![from]);
+                search.remaining.push((from, to));
+            }
+        }
+        for pipe in laid.iter() {
+            let reversed: Vec<Point> = pipe.iter().rev().cloned().collect();
+            // Pipes between neighboring nodes are already in place.
+            if search.pipes.contains(pipe) || search.pipes.contains(&reversed)
+            {
+                continue;
+            }
+            let (start, end) = (pipe[0], pipe[pipe.len() - 1]);
+            let cells = if search.remaining.contains(&(start, end)) {
+                pipe
+            } else {
+                &reversed
+            };
+            let index = search
+                .pipes
+                .iter()
+                .position(|other| other[..] == [cells[0]])
+                .ok_or_else(|| format!("Can't lay pipe {:?}.", pipe))?;
+            for &next in cells[1..].iter() {
+                search.occupy(index, next);
+            }
+        }
+        search.extend_pipes();
+        let mut found = search.found.into_iter();
+        Ok(found.next().map(|pipes| {
+            let is_unique = found.next().is_none();
+            PlaneSolution { pipes, is_unique }
+        }))
+    }
+}
+
+// ========================================================================= //
+
+pub struct PlaneSolution {
+    pipes: Vec<Vec<Point>>,
+    is_unique: bool,
+}
+
+impl PlaneSolution {
+    /// One pipe per pair of nodes that must be connected, in the form that
+    /// `PlaneGrid::set_pipes` accepts.
+    pub fn pipes(&self) -> &Vec<Vec<Point>> {
+        &self.pipes
+    }
+
+    /// Returns true if this is the only solution.
+    pub fn is_unique(&self) -> bool {
+        self.is_unique
+    }
+}
+
+// ========================================================================= //
+
+struct PipeSearch {
+    num_cols: i32,
+    num_rows: i32,
+    /// The object in each cell, in row-major order.
+    objects: Vec<Option<PlaneObj>>,
+    /// The pipes laid so far.  Each starts at the first node of one of the
+    /// pairs that must be connected, but which node it will end at is only
+    /// decided when it gets there.
+    pipes: Vec<Vec<Point>>,
+    /// Pairs of nodes that still need a pipe between them.
+    remaining: Vec<(Point, Point)>,
+    /// Whether a pipe already runs horizontally and vertically through each
+    /// cell.  An ordinary cell is marked both ways, so that only cross cells
+    /// can take a second pipe.
+    occupied: Vec<[bool; 2]>,
+    found: Vec<Vec<Vec<Point>>>,
+}
+
+impl PipeSearch {
+    fn new(grid: &PlaneGrid) -> PipeSearch {
+        let (num_cols, num_rows) =
+            (grid.num_cols as i32, grid.num_rows as i32);
+        let mut objects = vec![None; (num_cols * num_rows) as usize];
+        for (&coords, &obj) in grid.objects.iter() {
+            objects[(coords.y() * num_cols + coords.x()) as usize] = Some(obj);
+        }
+        PipeSearch {
+            num_cols,
+            num_rows,
+            objects,
+            pipes: Vec::new(),
+            remaining: Vec::new(),
+            occupied: vec![[false; 2]; (num_cols * num_rows) as usize],
+            found: Vec::new(),
+        }
+    }
+
+    fn index(&self, coords: Point) -> Option<usize> {
+        if (coords.x() >= 0 && coords.x() < self.num_cols)
+            && (coords.y() >= 0 && coords.y() < self.num_rows)
+        {
+            Some((coords.y() * self.num_cols + coords.x()) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the object at the given coordinates, treating everything
+    /// outside the grid as wall.
+    fn object_at(&self, coords: Point) -> Option<PlaneObj> {
+        match self.index(coords) {
+            Some(index) => self.objects[index],
+            None => Some(PlaneObj::Wall),
+        }
+    }
+
+    fn is_node(&self, coords: Point) -> bool {
+        self.object_at(coords).map(PlaneObj::is_node).unwrap_or(false)
+    }
+
+    /// Returns true if a pipe could run through the given cell, vertically or
+    /// horizontally.
+    fn is_free(&self, coords: Point, vertical: bool) -> bool {
+        match self.object_at(coords) {
+            None | Some(PlaneObj::Cross) => {
+                let index = self.index(coords).unwrap();
+                !self.occupied[index][vertical as usize]
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn is_finished(&self, pipe: &[Point]) -> bool {
+        pipe.len() > 1 && self.is_node(pipe[pipe.len() - 1])
+    }
+
+    fn extend_pipes(&mut self) {
+        if self.found.len() >= MAX_SOLUTIONS {
+            return;
+        }
+        // Extend whichever unfinished pipe has the fewest ways to go, so that
+        // forced steps get taken before any guesses are made.  Pipes that
+        // haven't left their node yet are interchangeable, so only the first
+        // of those from each node is worth considering.
+        let mut best: Option<(usize, Vec<Point>)> = None;
+        let mut waiting = Vec::new();
+        for (index, pipe) in self.pipes.iter().enumerate() {
+            if self.is_finished(pipe) {
+                continue;
+            }
+            if pipe.len() == 1 {
+                if waiting.contains(&pipe[0]) {
+                    continue;
+                }
+                waiting.push(pipe[0]);
+            }
+            let steps = self.next_steps(pipe);
+            if steps.is_empty() {
+                return;
+            }
+            let fewer =
+                best.as_ref().map(|(_, best)| steps.len() < best.len());
+            if fewer.unwrap_or(true) {
+                best = Some((index, steps));
+            }
+        }
+        let (index, steps) = match best {
+            Some(best) => best,
+            None => {
+                let mut pipes = self.pipes.clone();
+                pipes.sort_by_key(|pipe| {
+                    let (start, end) = (pipe[0], pipe[pipe.len() - 1]);
+                    (start.y(), start.x(), end.y(), end.x())
+                });
+                self.found.push(pipes);
+                return;
+            }
+        };
+        for next in steps {
+            self.occupy(index, next);
+            if !self.is_stuck() {
+                self.extend_pipes();
+            }
+            self.vacate(index);
+        }
+    }
+
+    /// Returns the directions an unfinished pipe could go in next.  A pipe in
+    /// a cross cell must keep going straight; and pipes leave their node in
+    /// the same order as `DIRECTIONS`, so that the same set of pipes isn't
+    /// found more than once.
+    fn next_dirs(&self, pipe: &[Point]) -> &'static [Direction] {
+        let head = pipe[pipe.len() - 1];
+        if pipe.len() == 1 {
+            let first = self
+                .pipes
+                .iter()
+                .filter(|other| other[0] == head && other.len() > 1)
+                .filter(|other| other.len() > 2 || !self.is_node(other[1]))
+                .filter_map(|other| direction_index(other[0], other[1]))
+                .map(|index| index + 1)
+                .max()
+                .unwrap_or(0);
+            &DIRECTIONS[first..]
+        } else if self.object_at(head) == Some(PlaneObj::Cross) {
+            let index = direction_index(pipe[pipe.len() - 2], head).unwrap();
+            &DIRECTIONS[index..(index + 1)]
+        } else {
+            &DIRECTIONS
+        }
+    }
+
+    /// Returns the cells that an unfinished pipe could be extended into.
+    fn next_steps(&self, pipe: &[Point]) -> Vec<Point> {
+        let head = pipe[pipe.len() - 1];
+        let mut steps = Vec::new();
+        for &dir in self.next_dirs(pipe) {
+            let next = head + dir.delta();
+            let allowed = if self.is_node(next) {
+                self.remaining.contains(&(pipe[0], next))
+                    && !self.runs_alongside(pipe, next)
+            } else if self.object_at(next) == Some(PlaneObj::Cross) {
+                self.is_free(next, dir.is_vertical())
+            } else {
+                self.is_free(next, dir.is_vertical())
+                    && !self.runs_alongside(pipe, next)
+            };
+            if allowed {
+                steps.push(next);
+            }
+        }
+        steps
+    }
+
+    /// Returns true if an ordinary cell next to `next` (other than the head
+    /// of the pipe) is already part of the pipe, in which case the pipe
+    /// could have cut across to `next` instead of going the long way round.
+    fn runs_alongside(&self, pipe: &[Point], next: Point) -> bool {
+        let head = pipe[pipe.len() - 1];
+        DIRECTIONS.iter().any(|dir| {
+            let coords = next + dir.delta();
+            coords != head
+                && self.object_at(coords) != Some(PlaneObj::Cross)
+                && pipe.contains(&coords)
+        })
+    }
+
+    /// Extends a pipe by one cell.
+    fn occupy(&mut self, index: usize, next: Point) {
+        let head = self.pipes[index][self.pipes[index].len() - 1];
+        self.pipes[index].push(next);
+        let from = self.pipes[index][0];
+        match self.object_at(next) {
+            None => {
+                let cell = self.index(next).unwrap();
+                self.occupied[cell] = [true, true];
+            }
+            Some(PlaneObj::Cross) => {
+                let cell = self.index(next).unwrap();
+                self.occupied[cell][(next.x() == head.x()) as usize] = true;
+            }
+            Some(_) => self.remaining.retain(|&pair| pair != (from, next)),
+        }
+    }
+
+    /// Undoes the last `occupy` of a pipe.
+    fn vacate(&mut self, index: usize) {
+        let end = self.pipes[index].pop().unwrap();
+        let head = self.pipes[index][self.pipes[index].len() - 1];
+        match self.object_at(end) {
+            None => {
+                let cell = self.index(end).unwrap();
+                self.occupied[cell] = [false, false];
+            }
+            Some(PlaneObj::Cross) => {
+                let cell = self.index(end).unwrap();
+                self.occupied[cell][(end.x() == head.x()) as usize] = false;
+            }
+            Some(_) => self.remaining.push((self.pipes[index][0], end)),
+        }
+    }
+
+    /// Returns true if the unfinished pipes can no longer all be completed,
+    /// either because some node has fewer free cells around it than it has
+    /// pipes left to take, or because there's no way to match the pipes
+    /// leaving each node with the nodes they could still reach.
+    fn is_stuck(&self) -> bool {
+        let unfinished: Vec<&Vec<Point>> =
+            self.pipes.iter().filter(|pipe| !self.is_finished(pipe)).collect();
+        for (index, &(_, to)) in self.remaining.iter().enumerate() {
+            if self.remaining[..index].iter().any(|&(_, other)| other == to) {
+                continue;
+            }
+            let needed = self
+                .remaining
+                .iter()
+                .filter(|&&(_, other)| other == to)
+                .count();
+            // A pipe can reach this node through a free cell next to it, or
+            // from a cell next to it that it already occupies.
+            let entries = DIRECTIONS
+                .iter()
+                .filter(|dir| {
+                    let coords = to + dir.delta();
+                    self.is_free(coords, dir.is_vertical())
+                        || unfinished.iter().any(|pipe| {
+                            pipe.len() > 1
+                                && pipe[pipe.len() - 1] == coords
+                                && self.remaining.contains(&(pipe[0], to))
+                        })
+                })
+                .count();
+            if entries < needed {
+                return true;
+            }
+        }
+        for (index, pipe) in unfinished.iter().enumerate() {
+            let from = pipe[0];
+            if unfinished[..index].iter().any(|other| other[0] == from) {
+                continue;
+            }
+            let pipes: Vec<&Vec<Point>> = unfinished
+                .iter()
+                .cloned()
+                .filter(|other| other[0] == from)
+                .collect();
+            let partners: Vec<Point> = self
+                .remaining
+                .iter()
+                .filter(|&&(other, _)| other == from)
+                .map(|&(_, to)| to)
+                .collect();
+            let waiting = pipes.iter().filter(|pipe| pipe.len() == 1).count();
+            let mut options = Vec::new();
+            if waiting > 0 {
+                let exits = self
+                    .next_dirs(&[from])
+                    .iter()
+                    .filter(|dir| {
+                        self.is_free(from + dir.delta(), dir.is_vertical())
+                    })
+                    .count();
+                if exits < waiting {
+                    return true;
+                }
+                let reachable = self.reachable_partners(&[from], &partners);
+                options.extend((0..waiting).map(|_| reachable.clone()));
+            }
+            for pipe in pipes.iter().filter(|pipe| pipe.len() > 1) {
+                options.push(self.reachable_partners(pipe, &partners));
+            }
+            if !has_perfect_matching(&options, partners.len()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the indices of the partners that an unfinished pipe could
+    /// still be extended to, ignoring the other unfinished pipes.
+    fn reachable_partners(
+        &self,
+        pipe: &[Point],
+        partners: &[Point],
+    ) -> Vec<usize> {
+        let mut reachable = Vec::new();
+        let mut visited = vec![false; 2 * self.objects.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back((pipe[pipe.len() - 1], self.next_dirs(pipe)));
+        while let Some((coords, dirs)) = queue.pop_front() {
+            for &dir in dirs.iter() {
+                let next = coords + dir.delta();
+                let vertical = dir.is_vertical();
+                if let Some(index) = partners.iter().position(|&p| p == next) {
+                    if !reachable.contains(&index) {
+                        reachable.push(index);
+                    }
+                } else if self.is_free(next, vertical) {
+                    let is_cross =
+                        self.object_at(next) == Some(PlaneObj::Cross);
+                    let cell = self.index(next).unwrap();
+                    let key = 2 * cell + (vertical && is_cross) as usize;
+                    if !visited[key] {
+                        visited[key] = true;
+                        let dirs = if is_cross {
+                            let index = direction_index(coords, next).unwrap();
+                            &DIRECTIONS[index..(index + 1)]
+                        } else {
+                            &DIRECTIONS[..]
+                        };
+                        queue.push_back((next, dirs));
+                    }
+                }
+            }
+        }
+        reachable
+    }
+}
+
+const DIRECTIONS: [Direction; 4] =
+    [Direction::East, Direction::South, Direction::West, Direction::North];
+
+fn are_adjacent(coords1: Point, coords2: Point) -> bool {
+    let delta = coords2 - coords1;
+    delta.x().abs() + delta.y().abs() == 1
+}
+
+fn direction_index(from: Point, to: Point) -> Option<usize> {
+    DIRECTIONS.iter().position(|dir| from + dir.delta() == to)
+}
+
+/// Returns true if each of the items can be matched with a different one of
+/// `num_targets` targets, given the targets each item could be matched with,
+/// and every target is used.
+fn has_perfect_matching(options: &[Vec<usize>], num_targets: usize) -> bool {
+    fn augment(
+        item: usize,
+        options: &[Vec<usize>],
+        matched: &mut Vec<Option<usize>>,
+        seen: &mut Vec<bool>,
+    ) -> bool {
+        for &target in options[item].iter() {
+            if !seen[target] {
+                seen[target] = true;
+                let free = match matched[target] {
+                    None => true,
+                    Some(other) => augment(other, options, matched, seen),
+                };
+                if free {
+                    matched[target] = Some(item);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+    if options.len() != num_targets {
+        return false;
+    }
+    let mut matched = vec![None; num_targets];
+    (0..options.len()).all(|item| {
+        augment(item, options, &mut matched, &mut vec![false; num_targets])
+    })
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use crate::gui::Point;
+    use crate::save::util::Tomlable;
+    use crate::save::{DayState, SimpleState};
+
+    #[test]
+    fn puzzle_stages_have_solutions() {
+        let mut solved_day = DayState::from_toml(toml::Value::Boolean(false));
+        solved_day.solve();
+        // Searching the whole of the last stage of "Plane as Day" takes a
+        // while in an unoptimized build, so lay the solution's pipes from one
+        // of its nodes first.
+        let node = Point::new(8, 2);
+        let laid: Vec<Vec<Point>> = solved_day
+            .grid()
+            .pipes()
+            .iter()
+            .filter(|pipe| pipe[0] == node || pipe[pipe.len() - 1] == node)
+            .cloned()
+            .collect();
+        assert_eq!(laid.len(), 4);
+
+        // Each stage of "Plane and Simple" and then of "Plane as Day", with
+        // the pipes to lay before searching, and whether the solution should
+        // be unique.  The third stage of "Plane as Day" leaves room for pipes
+        // to go either way around some of the walls.
+        let stages: Vec<(&[Vec<Point>], bool)> = vec![
+            (&[], true),
+            (&[], true),
+            (&[], true),
+            (&[], true),
+            (&[], true),
+            (&[], true),
+            (&[], false),
+            (&laid, true),
+        ];
+        let mut grids = SimpleState::stage_grids();
+        grids.extend(DayState::stage_grids());
+        assert_eq!(grids.len(), stages.len());
+        for (mut grid, (laid, is_unique)) in grids.into_iter().zip(stages) {
+            let solution = grid.solution_extending(laid).unwrap().unwrap();
+            assert_eq!(solution.is_unique(), is_unique);
+            grid.set_pipes(solution.pipes());
+            assert!(grid.all_nodes_are_connected());
+        }
+    }
 }
 
 // ========================================================================= //
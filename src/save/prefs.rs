@@ -19,15 +19,58 @@
 
 use toml;
 
+use crate::gui::{ScaleMode, ScenePacing, SpeechOutput, DEFAULT_LANGUAGE};
+
+// ========================================================================= //
+
+const DEFAULT_NUM_BACKUPS: u32 = 3;
+const MAX_NUM_BACKUPS: u32 = 20;
+
+/// Volumes are stored as percentages.
+pub const MAX_VOLUME: u32 = 100;
+
+pub const MAX_WINDOW_SCALE: u32 = 4;
+
+/// Cutscene speeds are stored as percentages of normal speed.
+const MIN_SCENE_SPEED: u32 = 50;
+const MAX_SCENE_SPEED: u32 = 400;
+const NORMAL_SCENE_SPEED: u32 = 100;
+
+const MAX_AUTO_ADVANCE_MILLIS: u32 = 10_000;
+
 // ========================================================================= //
 
 pub struct Prefs {
     fullscreen: bool,
+    window_scale: u32,
+    scale_mode: ScaleMode,
+    num_backups: u32,
+    master_volume: u32,
+    sound_volume: u32,
+    muted: bool,
+    color_blind: bool,
+    language: String,
+    scene_speed: u32,
+    auto_advance_millis: u32,
+    speech_output: SpeechOutput,
 }
 
 impl Prefs {
     pub fn with_defaults() -> Prefs {
-        Prefs { fullscreen: true }
+        Prefs {
+            fullscreen: true,
+            window_scale: 1,
+            scale_mode: ScaleMode::Fill,
+            num_backups: DEFAULT_NUM_BACKUPS,
+            master_volume: MAX_VOLUME,
+            sound_volume: MAX_VOLUME,
+            muted: false,
+            color_blind: false,
+            language: DEFAULT_LANGUAGE.to_string(),
+            scene_speed: NORMAL_SCENE_SPEED,
+            auto_advance_millis: 0,
+            speech_output: SpeechOutput::Off,
+        }
     }
 
     pub fn from_toml(table: &toml::value::Table) -> Prefs {
@@ -37,15 +80,120 @@ impl Prefs {
         {
             prefs.fullscreen = fullscreen;
         }
+        if let Some(scale) =
+            table.get(WINDOW_SCALE_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.window_scale =
+                scale.max(1).min(MAX_WINDOW_SCALE as i64) as u32;
+        }
+        if let Some(mode) = table
+            .get(SCALE_MODE_KEY)
+            .and_then(toml::Value::as_str)
+            .and_then(ScaleMode::from_key)
+        {
+            prefs.scale_mode = mode;
+        }
+        if let Some(num_backups) =
+            table.get(NUM_BACKUPS_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.num_backups =
+                num_backups.max(0).min(MAX_NUM_BACKUPS as i64) as u32;
+        }
+        if let Some(volume) =
+            table.get(MASTER_VOLUME_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.master_volume = clamp_volume(volume);
+        }
+        if let Some(volume) =
+            table.get(SOUND_VOLUME_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.sound_volume = clamp_volume(volume);
+        }
+        if let Some(muted) =
+            table.get(MUTED_KEY).and_then(toml::Value::as_bool)
+        {
+            prefs.muted = muted;
+        }
+        if let Some(color_blind) =
+            table.get(COLOR_BLIND_KEY).and_then(toml::Value::as_bool)
+        {
+            prefs.color_blind = color_blind;
+        }
+        if let Some(language) =
+            table.get(LANGUAGE_KEY).and_then(toml::Value::as_str)
+        {
+            prefs.language = language.to_string();
+        }
+        if let Some(speed) =
+            table.get(SCENE_SPEED_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.scene_speed =
+                speed.max(MIN_SCENE_SPEED as i64).min(MAX_SCENE_SPEED as i64)
+                    as u32;
+        }
+        if let Some(millis) =
+            table.get(AUTO_ADVANCE_KEY).and_then(toml::Value::as_integer)
+        {
+            prefs.auto_advance_millis =
+                millis.max(0).min(MAX_AUTO_ADVANCE_MILLIS as i64) as u32;
+        }
+        if let Some(output) = table
+            .get(SPEECH_OUTPUT_KEY)
+            .and_then(toml::Value::as_str)
+            .and_then(SpeechOutput::from_key)
+        {
+            prefs.speech_output = output;
+        }
         prefs
     }
 
     pub fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
+        table.insert(
+            AUTO_ADVANCE_KEY.to_string(),
+            toml::Value::Integer(self.auto_advance_millis as i64),
+        );
+        table.insert(
+            COLOR_BLIND_KEY.to_string(),
+            toml::Value::Boolean(self.color_blind),
+        );
         table.insert(
             FULLSCREEN_KEY.to_string(),
             toml::Value::Boolean(self.fullscreen),
         );
+        table.insert(
+            WINDOW_SCALE_KEY.to_string(),
+            toml::Value::Integer(self.window_scale as i64),
+        );
+        table.insert(
+            SCALE_MODE_KEY.to_string(),
+            toml::Value::String(self.scale_mode.key().to_string()),
+        );
+        table.insert(
+            NUM_BACKUPS_KEY.to_string(),
+            toml::Value::Integer(self.num_backups as i64),
+        );
+        table.insert(
+            LANGUAGE_KEY.to_string(),
+            toml::Value::String(self.language.clone()),
+        );
+        table.insert(
+            MASTER_VOLUME_KEY.to_string(),
+            toml::Value::Integer(self.master_volume as i64),
+        );
+        table.insert(
+            SOUND_VOLUME_KEY.to_string(),
+            toml::Value::Integer(self.sound_volume as i64),
+        );
+        table.insert(
+            SPEECH_OUTPUT_KEY.to_string(),
+            toml::Value::String(self.speech_output.key()),
+        );
+        table.insert(MUTED_KEY.to_string(), toml::Value::Boolean(self.muted));
+        table.insert(
+            SCENE_SPEED_KEY.to_string(),
+            toml::Value::Integer(self.scene_speed as i64),
+        );
         toml::Value::Table(table)
     }
 
@@ -53,12 +201,261 @@ impl Prefs {
         self.fullscreen
     }
 
-    #[allow(dead_code)]
     pub fn set_fullscreen(&mut self, fullscreen: bool) {
         self.fullscreen = fullscreen;
     }
+
+    /// Returns how many times larger than the game's native resolution the
+    /// window should be when not fullscreen.
+    pub fn window_scale(&self) -> u32 {
+        self.window_scale
+    }
+
+    pub fn set_window_scale(&mut self, scale: u32) {
+        self.window_scale = scale.clamp(1, MAX_WINDOW_SCALE);
+    }
+
+    /// Returns how the game should be scaled to fit the window.
+    pub fn scale_mode(&self) -> ScaleMode {
+        self.scale_mode
+    }
+
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        self.scale_mode = mode;
+    }
+
+    /// Returns the number of old copies of the save file to keep around.
+    pub fn num_backups(&self) -> u32 {
+        self.num_backups
+    }
+
+    /// Returns the overall volume, from 0 to `MAX_VOLUME`.
+    pub fn master_volume(&self) -> u32 {
+        self.master_volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: u32) {
+        self.master_volume = volume.min(MAX_VOLUME);
+    }
+
+    /// Returns the volume of sound effects, from 0 to `MAX_VOLUME`, before
+    /// the master volume is applied.
+    pub fn sound_volume(&self) -> u32 {
+        self.sound_volume
+    }
+
+    pub fn set_sound_volume(&mut self, volume: u32) {
+        self.sound_volume = volume.min(MAX_VOLUME);
+    }
+
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Returns true if puzzles should use a color-blind-friendly palette and
+    /// mark colored pieces with letters.
+    pub fn color_blind(&self) -> bool {
+        self.color_blind
+    }
+
+    pub fn set_color_blind(&mut self, color_blind: bool) {
+        self.color_blind = color_blind;
+    }
+
+    /// Returns the code (e.g. "en") of the language to show the game's text
+    /// in.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn set_language(&mut self, language: &str) {
+        self.language = language.to_string();
+    }
+
+    /// Returns how fast cutscenes play, as a percentage of normal speed,
+    /// from `MIN_SCENE_SPEED` to `MAX_SCENE_SPEED`.
+    pub fn scene_speed(&self) -> u32 {
+        self.scene_speed
+    }
+
+    pub fn set_scene_speed(&mut self, speed: u32) {
+        self.scene_speed = speed.clamp(MIN_SCENE_SPEED, MAX_SCENE_SPEED);
+    }
+
+    /// Returns how long, in milliseconds, a speech bubble stays up before a
+    /// cutscene continues on its own, or zero if it waits for a click.
+    pub fn auto_advance_millis(&self) -> u32 {
+        self.auto_advance_millis
+    }
+
+    pub fn set_auto_advance_millis(&mut self, millis: u32) {
+        self.auto_advance_millis = millis.min(MAX_AUTO_ADVANCE_MILLIS);
+    }
+
+    /// Returns where displayed text should be sent for a screen reader to
+    /// voice.
+    pub fn speech_output(&self) -> &SpeechOutput {
+        &self.speech_output
+    }
+
+    pub fn set_speech_output(&mut self, output: SpeechOutput) {
+        self.speech_output = output;
+    }
+
+    pub fn scene_pacing(&self) -> ScenePacing {
+        ScenePacing {
+            speed_percent: self.scene_speed,
+            auto_advance_millis: self.auto_advance_millis,
+        }
+    }
+
+    /// Returns the factor, from 0.0 to 1.0, that sound effect samples should
+    /// be scaled by, taking the master volume and mute setting into account.
+    pub fn sound_gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            let max = MAX_VOLUME as f32;
+            (self.master_volume as f32 / max)
+                * (self.sound_volume as f32 / max)
+        }
+    }
+}
+
+fn clamp_volume(volume: i64) -> u32 {
+    volume.max(0).min(MAX_VOLUME as i64) as u32
 }
 
+const AUTO_ADVANCE_KEY: &str = "auto_advance_millis";
+const COLOR_BLIND_KEY: &str = "color_blind";
 const FULLSCREEN_KEY: &str = "fullscreen";
+const LANGUAGE_KEY: &str = "language";
+const MASTER_VOLUME_KEY: &str = "master_volume";
+const MUTED_KEY: &str = "muted";
+const NUM_BACKUPS_KEY: &str = "num_backups";
+const SCALE_MODE_KEY: &str = "scale_mode";
+const SCENE_SPEED_KEY: &str = "scene_speed";
+const SOUND_VOLUME_KEY: &str = "sound_volume";
+const SPEECH_OUTPUT_KEY: &str = "speech_output";
+const WINDOW_SCALE_KEY: &str = "window_scale";
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use toml;
+
+    use super::{Prefs, MAX_SCENE_SPEED, MAX_VOLUME, MIN_SCENE_SPEED};
+    use crate::gui::{ScaleMode, SpeechOutput};
+
+    #[test]
+    fn display_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        prefs.set_fullscreen(false);
+        prefs.set_window_scale(3);
+        prefs.set_scale_mode(ScaleMode::Letterbox);
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert!(!prefs.fullscreen());
+        assert_eq!(prefs.window_scale(), 3);
+        assert_eq!(prefs.scale_mode(), ScaleMode::Letterbox);
+    }
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        let mut table = toml::value::Table::new();
+        table.insert("fullscreen".to_string(), toml::Value::Boolean(false));
+        table.insert("num_backups".to_string(), toml::Value::Integer(3));
+        let prefs = Prefs::from_toml(&table);
+        let mut defaults = Prefs::with_defaults();
+        defaults.set_fullscreen(false);
+        assert_eq!(prefs.to_toml(), defaults.to_toml());
+    }
+
+    #[test]
+    fn volume_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        assert_eq!(prefs.sound_gain(), 1.0);
+        prefs.set_master_volume(50);
+        prefs.set_sound_volume(40);
+        assert_eq!(prefs.sound_gain(), 0.2);
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert_eq!(prefs.master_volume(), 50);
+        assert_eq!(prefs.sound_volume(), 40);
+        assert!(!prefs.muted());
+    }
+
+    #[test]
+    fn mute_silences_sound() {
+        let mut prefs = Prefs::with_defaults();
+        prefs.set_muted(true);
+        assert_eq!(prefs.sound_gain(), 0.0);
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert!(prefs.muted());
+    }
+
+    #[test]
+    fn color_blind_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        assert!(!prefs.color_blind());
+        prefs.set_color_blind(true);
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert!(prefs.color_blind());
+    }
+
+    #[test]
+    fn language_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        assert_eq!(prefs.language(), "en");
+        prefs.set_language("fr");
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert_eq!(prefs.language(), "fr");
+    }
+
+    #[test]
+    fn scene_pacing_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        assert_eq!(prefs.scene_speed(), 100);
+        assert_eq!(prefs.auto_advance_millis(), 0);
+        prefs.set_scene_speed(200);
+        prefs.set_auto_advance_millis(3000);
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        let pacing = prefs.scene_pacing();
+        assert_eq!(pacing.speed_percent, 200);
+        assert_eq!(pacing.auto_advance_millis, 3000);
+
+        let mut table = toml::value::Table::new();
+        table.insert("scene_speed".to_string(), toml::Value::Integer(1));
+        let prefs = Prefs::from_toml(&table);
+        assert_eq!(prefs.scene_speed(), MIN_SCENE_SPEED);
+        table.insert("scene_speed".to_string(), toml::Value::Integer(9999));
+        let prefs = Prefs::from_toml(&table);
+        assert_eq!(prefs.scene_speed(), MAX_SCENE_SPEED);
+    }
+
+    #[test]
+    fn speech_output_round_trip() {
+        let mut prefs = Prefs::with_defaults();
+        assert_eq!(prefs.speech_output(), &SpeechOutput::Off);
+        let pipe = SpeechOutput::Pipe(PathBuf::from("/tmp/syzygy-speech"));
+        prefs.set_speech_output(pipe.clone());
+        let prefs = Prefs::from_toml(prefs.to_toml().as_table().unwrap());
+        assert_eq!(prefs.speech_output(), &pipe);
+    }
+
+    #[test]
+    fn out_of_range_volumes_are_clamped() {
+        let mut table = toml::value::Table::new();
+        table.insert("master_volume".to_string(), toml::Value::Integer(-5));
+        table.insert("sound_volume".to_string(), toml::Value::Integer(900));
+        let prefs = Prefs::from_toml(&table);
+        assert_eq!(prefs.master_volume(), 0);
+        assert_eq!(prefs.sound_volume(), MAX_VOLUME);
+    }
+}
 
 // ========================================================================= //
@@ -160,6 +160,16 @@ impl AtticState {
             && self.toggled.contains(&((row - 1) * 4 + (col - 1)))
     }
 
+    /// Returns a toggle that the player still needs to flip in order to
+    /// solve the puzzle, if any.
+    pub fn next_toggle(&self) -> Option<(i32, i32)> {
+        (0..16)
+            .find(|index| {
+                self.toggled.contains(index) != SOLVED_TOGGLED.contains(index)
+            })
+            .map(|index| (index % 4 + 1, index / 4 + 1))
+    }
+
     pub fn toggle(&mut self, pos: (i32, i32)) {
         let (col, row) = pos;
         if col >= 1 && col <= 4 && row >= 1 && row <= 4 {
@@ -249,6 +259,20 @@ mod tests {
         assert_eq!(state.toggled, vec![1, 3, 4].into_iter().collect());
     }
 
+    #[test]
+    fn next_toggle_leads_to_solution() {
+        let mut state = AtticState::new();
+        state.toggle((2, 1));
+        let mut moves = 0;
+        while let Some(position) = state.next_toggle() {
+            assert_ne!(state.access, Access::Solved);
+            state.toggle(position);
+            moves += 1;
+        }
+        assert_eq!(moves, SOLVED_TOGGLED.len() + 1);
+        assert_eq!(state.access, Access::Solved);
+    }
+
     #[test]
     fn from_empty_toml() {
         let state = AtticState::from_toml(toml::Value::Boolean(false));
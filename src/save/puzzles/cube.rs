@@ -17,15 +17,18 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+use rand::Rng;
 use toml;
 
 use super::PuzzleState;
+use crate::save::random::{seeded_rng, Scramble};
 use crate::save::util::{pop_array, to_table, Tomlable, ACCESS_KEY};
 use crate::save::{Access, Direction, Location};
 
 // ========================================================================= //
 
 const GRID_KEY: &str = "grid";
+const SCRAMBLE_KEY: &str = "scramble";
 
 const NUM_COLS: i32 = 4;
 const NUM_ROWS: i32 = 4;
@@ -39,10 +42,49 @@ const SOLVED_GRID: &[i32] = &[4, 4, 3, 3, 4, 2, 2, 3, 1, 2, 2, 0, 1, 1, 0, 0];
 pub struct CubeState {
     access: Access,
     grid: Vec<i32>,
+    initial_grid: Vec<i32>,
+    scramble: Option<Scramble>,
     is_initial: bool,
 }
 
 impl CubeState {
+    /// Deals a fresh instance of the puzzle, scrambled from the solved state
+    /// by `num_moves` random row and column rotations (or more, if those
+    /// happen to leave it solved).  The same seed always deals the same
+    /// instance, and resetting it returns to the scrambled state.
+    pub fn scrambled(scramble: Scramble) -> CubeState {
+        let mut rng = seeded_rng(scramble.seed);
+        let num_moves = scramble.num_moves;
+        let mut state = CubeState {
+            access: Access::Unvisited,
+            grid: SOLVED_GRID.to_vec(),
+            initial_grid: SOLVED_GRID.to_vec(),
+            scramble: Some(scramble),
+            is_initial: false,
+        };
+        let mut last_rank = None;
+        let mut moves = 0;
+        while moves < num_moves || (num_moves > 0 && state.fronts_solved()) {
+            let dir =
+                if rng.gen() { Direction::South } else { Direction::East };
+            let num_ranks =
+                if dir.is_vertical() { NUM_COLS } else { NUM_ROWS };
+            let rank = rng.gen_range(0, num_ranks);
+            // Turning the same rank twice in a row would only count as one
+            // move.
+            if Some((dir, rank)) == last_rank {
+                continue;
+            }
+            state.rotate_cubes(dir, rank, rng.gen_range(1, 4));
+            last_rank = Some((dir, rank));
+            moves += 1;
+        }
+        state.access = Access::Unvisited;
+        state.initial_grid = state.grid.clone();
+        state.is_initial = true;
+        state
+    }
+
     pub fn solve(&mut self) {
         self.access = Access::Solved;
         self.grid = SOLVED_GRID.to_vec();
@@ -73,11 +115,15 @@ impl CubeState {
                     rotate_horz(self.grid[index], dir.delta().x() * by);
             }
         }
-        self.is_initial = &self.grid as &[i32] == INITIAL_GRID;
-        if self.grid.iter().zip(SOLVED_GRID.iter()).all(fronts_match) {
+        self.is_initial = self.grid == self.initial_grid;
+        if self.fronts_solved() {
             self.access = Access::Solved;
         }
     }
+
+    fn fronts_solved(&self) -> bool {
+        self.grid.iter().zip(SOLVED_GRID.iter()).all(fronts_match)
+    }
 }
 
 impl PuzzleState for CubeState {
@@ -98,15 +144,22 @@ impl PuzzleState for CubeState {
     }
 
     fn reset(&mut self) {
-        self.grid = INITIAL_GRID.to_vec();
+        self.grid = self.initial_grid.clone();
         self.is_initial = true;
     }
+
+    fn is_scrambled(&self) -> bool {
+        self.scramble.is_some()
+    }
 }
 
 impl Tomlable for CubeState {
     fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
         table.insert(ACCESS_KEY.to_string(), self.access.to_toml());
+        if let Some(ref scramble) = self.scramble {
+            table.insert(SCRAMBLE_KEY.to_string(), scramble.to_toml());
+        }
         if !self.is_initial && !self.is_solved() {
             let grid = self
                 .grid
@@ -121,6 +174,11 @@ impl Tomlable for CubeState {
     fn from_toml(value: toml::Value) -> CubeState {
         let mut table = to_table(value);
         let access = Access::pop_from_table(&mut table, ACCESS_KEY);
+        let scramble = table.remove(SCRAMBLE_KEY).map(Scramble::from_toml);
+        let initial_grid = match scramble {
+            Some(scramble) => CubeState::scrambled(scramble).initial_grid,
+            None => INITIAL_GRID.to_vec(),
+        };
         let grid = if access == Access::Solved {
             SOLVED_GRID.to_vec()
         } else {
@@ -131,12 +189,12 @@ impl Tomlable for CubeState {
                 .map(|idx| idx as i32)
                 .collect();
             if grid.len() != (NUM_COLS * NUM_ROWS) as usize {
-                grid = INITIAL_GRID.to_vec();
+                grid = initial_grid.clone();
             }
             grid
         };
-        let is_initial = &grid as &[i32] == INITIAL_GRID;
-        CubeState { access, grid, is_initial }
+        let is_initial = grid == initial_grid;
+        CubeState { access, grid, initial_grid, scramble, is_initial }
     }
 }
 
@@ -240,8 +298,9 @@ mod tests {
     use super::{
         rotate_vert, CubeState, INITIAL_GRID, NUM_COLS, NUM_ROWS, SOLVED_GRID,
     };
+    use crate::save::random::{Difficulty, Scramble};
     use crate::save::util::{Tomlable, ACCESS_KEY};
-    use crate::save::Access;
+    use crate::save::{Access, Direction, PuzzleState};
 
     #[test]
     fn grid_sizes() {
@@ -293,6 +352,47 @@ mod tests {
         assert_eq!(state.grid, SOLVED_GRID.to_vec());
         assert!(!state.is_initial);
     }
+
+    #[test]
+    fn scrambled_is_repeatable() {
+        let scramble = |seed, num_moves| Scramble { seed, num_moves };
+        let state = CubeState::scrambled(scramble(1234, 10));
+        assert_eq!(state.access, Access::Unvisited);
+        assert!(!state.fronts_solved());
+        assert!(state.is_initial);
+        let again = CubeState::scrambled(scramble(1234, 10));
+        assert_eq!(again.grid, state.grid);
+        let other = CubeState::scrambled(scramble(4321, 10));
+        assert_ne!(other.grid, state.grid);
+        let unscrambled = CubeState::scrambled(scramble(1234, 0));
+        assert_eq!(unscrambled.grid, SOLVED_GRID);
+
+        let mut state = state;
+        let scrambled = state.grid.clone();
+        state.rotate_cubes(Direction::East, 0, 1);
+        assert!(state.can_reset());
+        state.reset();
+        assert_eq!(state.grid, scrambled);
+        assert!(!state.can_reset());
+    }
+
+    #[test]
+    fn scrambled_round_trip() {
+        let scramble = Scramble::new(1234, Difficulty::Hard);
+        let mut state = CubeState::scrambled(scramble);
+        let scrambled = state.grid.clone();
+        let reloaded = CubeState::from_toml(state.to_toml());
+        assert_eq!(reloaded.grid, scrambled);
+        assert!(!reloaded.can_reset());
+
+        state.rotate_cubes(Direction::East, 0, 1);
+        let grid = state.grid.clone();
+        let mut reloaded = CubeState::from_toml(state.to_toml());
+        assert_eq!(reloaded.grid, grid);
+        assert!(reloaded.can_reset());
+        reloaded.reset();
+        assert_eq!(reloaded.grid, scrambled);
+    }
 }
 
 // ========================================================================= //
@@ -203,6 +203,20 @@ mod tests {
             Some((Device::Mirror, Direction::South))
         );
     }
+
+    #[test]
+    fn solved_grid_satisfies_detectors() {
+        let grid = DisconState::solved_grid();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+        let grid = DisconState::initial_grid();
+        assert!(!grid.lasers().all_detectors_satisfied(&grid));
+    }
+
+    #[test]
+    fn initial_grid_has_solution() {
+        let grid = DisconState::initial_grid().solution().unwrap();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+    }
 }
 
 // ========================================================================= //
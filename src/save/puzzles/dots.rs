@@ -212,6 +212,20 @@ mod tests {
             Some((Device::Splitter, Direction::South))
         );
     }
+
+    #[test]
+    fn solved_grid_satisfies_detectors() {
+        let grid = DotsState::solved_grid();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+        let grid = DotsState::initial_grid();
+        assert!(!grid.lasers().all_detectors_satisfied(&grid));
+    }
+
+    #[test]
+    fn initial_grid_has_solution() {
+        let grid = DotsState::initial_grid().solution().unwrap();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+    }
 }
 
 // ========================================================================= //
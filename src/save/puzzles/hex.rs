@@ -17,14 +17,17 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+use rand::Rng;
 use std::collections::VecDeque;
 use toml;
 
+use crate::save::random::{seeded_rng, Scramble};
 use crate::save::util::{rotate_deque, to_table, Tomlable, ACCESS_KEY};
 use crate::save::{Access, Location, PuzzleState};
 
 // ========================================================================= //
 
+const SCRAMBLE_KEY: &str = "scramble";
 const TOKENS_KEY: &str = "tokens";
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
@@ -64,10 +67,47 @@ const WHEELS: &[[usize; 6]] = &[
 pub struct HexState {
     access: Access,
     tokens: Vec<u8>,
+    initial_tokens: Vec<u8>,
+    scramble: Option<Scramble>,
     is_initial: bool,
 }
 
 impl HexState {
+    /// Deals a fresh instance of the puzzle, scrambled from the solved state
+    /// by `num_moves` random wheel rotations (or more, if those happen to
+    /// leave it solved).  The same seed always deals the same instance, and
+    /// resetting it returns to the scrambled state.
+    pub fn scrambled(scramble: Scramble) -> HexState {
+        let mut rng = seeded_rng(scramble.seed);
+        let num_moves = scramble.num_moves;
+        let mut state = HexState {
+            access: Access::Unvisited,
+            tokens: SOLVED_TOKENS.to_vec(),
+            initial_tokens: SOLVED_TOKENS.to_vec(),
+            scramble: Some(scramble),
+            is_initial: false,
+        };
+        let mut last_wheel = None;
+        let mut moves = 0;
+        while moves < num_moves
+            || (num_moves > 0 && (&state.tokens as &[u8]) == SOLVED_TOKENS)
+        {
+            // Turning the same wheel twice in a row would only count as one
+            // move.
+            let wheel = rng.gen_range(0, WHEELS.len());
+            if Some(wheel) == last_wheel {
+                continue;
+            }
+            state.rotate_wheel_cw(wheel, rng.gen_range(1, 6));
+            last_wheel = Some(wheel);
+            moves += 1;
+        }
+        state.access = Access::Unvisited;
+        state.initial_tokens = state.tokens.clone();
+        state.is_initial = true;
+        state
+    }
+
     pub fn solve(&mut self) {
         self.access = Access::Solved;
         self.tokens = SOLVED_TOKENS.to_vec();
@@ -87,7 +127,7 @@ impl HexState {
         for (index, token) in tokens.into_iter().enumerate() {
             self.tokens[wheel[index]] = token;
         }
-        self.is_initial = (&self.tokens as &[u8]) == INITIAL_TOKENS;
+        self.is_initial = self.tokens == self.initial_tokens;
         if (&self.tokens as &[u8]) == SOLVED_TOKENS {
             self.access = Access::Solved;
         }
@@ -112,15 +152,22 @@ impl PuzzleState for HexState {
     }
 
     fn reset(&mut self) {
-        self.tokens = INITIAL_TOKENS.to_vec();
+        self.tokens = self.initial_tokens.clone();
         self.is_initial = true;
     }
+
+    fn is_scrambled(&self) -> bool {
+        self.scramble.is_some()
+    }
 }
 
 impl Tomlable for HexState {
     fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
         table.insert(ACCESS_KEY.to_string(), self.access.to_toml());
+        if let Some(ref scramble) = self.scramble {
+            table.insert(SCRAMBLE_KEY.to_string(), scramble.to_toml());
+        }
         if !self.is_solved() && !self.is_initial {
             let tokens = self
                 .tokens
@@ -135,6 +182,11 @@ impl Tomlable for HexState {
     fn from_toml(value: toml::Value) -> HexState {
         let mut table = to_table(value);
         let access = Access::pop_from_table(&mut table, ACCESS_KEY);
+        let scramble = table.remove(SCRAMBLE_KEY).map(Scramble::from_toml);
+        let initial_tokens = match scramble {
+            Some(scramble) => HexState::scrambled(scramble).initial_tokens,
+            None => INITIAL_TOKENS.to_vec(),
+        };
         let tokens = if access.is_solved() {
             SOLVED_TOKENS.to_vec()
         } else {
@@ -145,13 +197,13 @@ impl Tomlable for HexState {
             let mut tokens_sorted = tokens.clone();
             tokens_sorted.sort();
             if tokens_sorted != init_sorted {
-                INITIAL_TOKENS.to_vec()
+                initial_tokens.clone()
             } else {
                 tokens
             }
         };
-        let is_initial = (&tokens as &[u8]) == INITIAL_TOKENS;
-        HexState { access, tokens, is_initial }
+        let is_initial = tokens == initial_tokens;
+        HexState { access, tokens, initial_tokens, scramble, is_initial }
     }
 }
 
@@ -162,8 +214,9 @@ mod tests {
     use toml;
 
     use super::{HexState, INITIAL_TOKENS, SOLVED_TOKENS, TOKENS_KEY};
+    use crate::save::random::{Difficulty, Scramble};
     use crate::save::util::{Tomlable, ACCESS_KEY};
-    use crate::save::Access;
+    use crate::save::{Access, PuzzleState};
 
     #[cfg_attr(rustfmt, rustfmt_skip)]
     const ROUND_TRIP_TOKENS: &[u8] = &[
@@ -219,6 +272,47 @@ mod tests {
         assert_eq!(state.tokens, INITIAL_TOKENS.to_vec());
         assert!(state.is_initial);
     }
+
+    #[test]
+    fn scrambled_is_repeatable() {
+        let scramble = |seed, num_moves| Scramble { seed, num_moves };
+        let state = HexState::scrambled(scramble(1234, 10));
+        assert_eq!(state.access, Access::Unvisited);
+        assert_ne!(state.tokens.as_slice(), SOLVED_TOKENS);
+        assert!(state.is_initial);
+        let again = HexState::scrambled(scramble(1234, 10));
+        assert_eq!(again.tokens, state.tokens);
+        let other = HexState::scrambled(scramble(4321, 10));
+        assert_ne!(other.tokens, state.tokens);
+        let unscrambled = HexState::scrambled(scramble(1234, 0));
+        assert_eq!(unscrambled.tokens, SOLVED_TOKENS);
+
+        let mut state = state;
+        let scrambled = state.tokens.clone();
+        state.rotate_wheel_cw(0, 1);
+        assert!(state.can_reset());
+        state.reset();
+        assert_eq!(state.tokens, scrambled);
+        assert!(!state.can_reset());
+    }
+
+    #[test]
+    fn scrambled_round_trip() {
+        let mut state =
+            HexState::scrambled(Scramble::new(1234, Difficulty::Hard));
+        let scrambled = state.tokens.clone();
+        let reloaded = HexState::from_toml(state.to_toml());
+        assert_eq!(reloaded.tokens, scrambled);
+        assert!(!reloaded.can_reset());
+
+        state.rotate_wheel_cw(0, 1);
+        let tokens = state.tokens.clone();
+        let mut reloaded = HexState::from_toml(state.to_toml());
+        assert_eq!(reloaded.tokens, tokens);
+        assert!(reloaded.can_reset());
+        reloaded.reset();
+        assert_eq!(reloaded.tokens, scrambled);
+    }
 }
 
 // ========================================================================= //
@@ -17,10 +17,11 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
-use rand::{self, Rng, SeedableRng};
+use rand::{Rng, SeedableRng};
 use toml;
 
 use super::PuzzleState;
+use crate::save::random;
 use crate::save::util::{to_table, Tomlable, ACCESS_KEY};
 use crate::save::{Access, Location};
 
@@ -109,7 +110,7 @@ impl LineState {
 
     pub fn reseed(&mut self) {
         for value in self.seed.iter_mut() {
-            *value = rand::random();
+            *value = random::random();
         }
         self.update_grids();
     }
@@ -199,7 +200,7 @@ impl Tomlable for LineState {
                 }
             }
             while index < 8 {
-                seed[index] = rand::random();
+                seed[index] = random::random();
                 index += 1;
             }
         }
@@ -193,6 +193,14 @@ mod tests {
         assert_eq!(state.access, Access::Solved);
         assert!(state.grid().is_modified());
     }
+
+    #[test]
+    fn some_reachable_grids_are_dead_ends() {
+        // Not every reachable state of this puzzle can still be solved,
+        // which is why the puzzle offers undo and reset.
+        let grid = MeetState::initial_grid();
+        assert_eq!(grid.count_dead_ends(), Some(360));
+    }
 }
 
 // ========================================================================= //
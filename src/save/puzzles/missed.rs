@@ -219,6 +219,20 @@ mod tests {
             Some((Device::Mixer, Direction::East))
         );
     }
+
+    #[test]
+    fn solved_grid_satisfies_detectors() {
+        let grid = MissedState::solved_grid();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+        let grid = MissedState::initial_grid();
+        assert!(!grid.lasers().all_detectors_satisfied(&grid));
+    }
+
+    #[test]
+    fn initial_grid_has_solution() {
+        let grid = MissedState::initial_grid().solution().unwrap();
+        assert!(grid.lasers().all_detectors_satisfied(&grid));
+    }
 }
 
 // ========================================================================= //
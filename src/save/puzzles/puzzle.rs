@@ -65,6 +65,13 @@ pub trait PuzzleState: Tomlable {
 
     fn reset(&mut self);
 
+    /// Returns true if this puzzle was dealt from a `Scramble` rather than
+    /// set up as authored, in which case it must be saved even before it has
+    /// been visited.
+    fn is_scrambled(&self) -> bool {
+        false
+    }
+
     fn replay(&mut self) {
         self.reset();
         *self.access_mut() = Access::BeginReplay;
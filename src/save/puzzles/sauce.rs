@@ -94,6 +94,12 @@ impl SauceState {
         WORD_CLUES[self.current as usize].1
     }
 
+    /// Returns the answer to the current clue.
+    #[cfg(test)]
+    pub fn current_answer(&self) -> &'static str {
+        WORD_CLUES[self.current as usize].0
+    }
+
     pub fn go_next(&mut self) {
         let num_clues = WORD_CLUES.len() as i32;
         let mut next = (self.current + 1) % num_clues;
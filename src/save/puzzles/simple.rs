@@ -119,6 +119,21 @@ impl SimpleState {
         true
     }
 
+    /// Returns the grid for each stage, in order, without any pipes.
+    #[cfg(test)]
+    pub fn stage_grids() -> Vec<PlaneGrid> {
+        (FIRST_STAGE..=LAST_STAGE)
+            .map(|stage| {
+                let mut state =
+                    SimpleState::from_toml(toml::Value::Boolean(false));
+                while state.stage < stage {
+                    state.advance_stage();
+                }
+                state.grid
+            })
+            .collect()
+    }
+
     fn advance_stage(&mut self) {
         debug_assert!(self.stage >= FIRST_STAGE);
         debug_assert!(self.stage < LAST_STAGE);
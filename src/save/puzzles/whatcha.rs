@@ -21,12 +21,14 @@ use toml;
 
 use super::PuzzleState;
 use crate::save::column::Columns;
+use crate::save::random::Scramble;
 use crate::save::util::{pop_array, to_table, Tomlable, ACCESS_KEY};
 use crate::save::{Access, Location};
 
 // ========================================================================= //
 
 const COLUMNS_KEY: &str = "columns";
+const SCRAMBLE_KEY: &str = "scramble";
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
 const COLUMNS_SPEC: &[(&str, i32, i32, &[(usize, i32)])] = &[
@@ -43,9 +45,23 @@ const COLUMNS_SPEC: &[(&str, i32, i32, &[(usize, i32)])] = &[
 pub struct WhatchaState {
     access: Access,
     columns: Columns,
+    scramble: Option<Scramble>,
 }
 
 impl WhatchaState {
+    /// Deals a fresh instance of the puzzle, with the columns scrambled from
+    /// their solved positions.  The same seed always deals the same instance,
+    /// and resetting it returns to the scrambled positions.
+    pub fn scrambled(scramble: Scramble) -> WhatchaState {
+        let mut columns = Columns::from_toml(COLUMNS_SPEC, Vec::new());
+        columns.scramble(scramble);
+        WhatchaState {
+            access: Access::Unvisited,
+            columns,
+            scramble: Some(scramble),
+        }
+    }
+
     pub fn solve(&mut self) {
         self.access = Access::Solved;
         self.columns.solve();
@@ -87,12 +103,19 @@ impl PuzzleState for WhatchaState {
     fn reset(&mut self) {
         self.columns.reset();
     }
+
+    fn is_scrambled(&self) -> bool {
+        self.scramble.is_some()
+    }
 }
 
 impl Tomlable for WhatchaState {
     fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
         table.insert(ACCESS_KEY.to_string(), self.access.to_toml());
+        if let Some(ref scramble) = self.scramble {
+            table.insert(SCRAMBLE_KEY.to_string(), scramble.to_toml());
+        }
         if !self.is_solved() && self.columns.can_reset() {
             table.insert(COLUMNS_KEY.to_string(), self.columns.to_toml());
         }
@@ -102,14 +125,16 @@ impl Tomlable for WhatchaState {
     fn from_toml(value: toml::Value) -> WhatchaState {
         let mut table = to_table(value);
         let access = Access::pop_from_table(&mut table, ACCESS_KEY);
-        let mut columns = Columns::from_toml(
-            COLUMNS_SPEC,
-            pop_array(&mut table, COLUMNS_KEY),
-        );
+        let scramble = table.remove(SCRAMBLE_KEY).map(Scramble::from_toml);
+        let mut columns = Columns::from_toml(COLUMNS_SPEC, Vec::new());
+        if let Some(scramble) = scramble {
+            columns.scramble(scramble);
+        }
+        columns.set_positions_from_toml(pop_array(&mut table, COLUMNS_KEY));
         if access.is_solved() {
             columns.solve();
         }
-        WhatchaState { access, columns }
+        WhatchaState { access, columns, scramble }
     }
 }
 
@@ -120,8 +145,10 @@ mod tests {
     use toml;
 
     use super::{WhatchaState, COLUMNS_SPEC};
+    use crate::save::column::Columns;
+    use crate::save::random::{Difficulty, Scramble};
     use crate::save::util::{Tomlable, ACCESS_KEY};
-    use crate::save::Access;
+    use crate::save::{Access, PuzzleState};
 
     #[test]
     fn toml_round_trip() {
@@ -170,6 +197,51 @@ mod tests {
             .collect();
         assert_eq!(actual_positions, solved_positions);
     }
+
+    #[test]
+    fn scrambled_columns_reset_to_scramble() {
+        let mut columns = Columns::from_toml(COLUMNS_SPEC, Vec::new());
+        columns.scramble(Scramble { seed: 1234, num_moves: 10 });
+        assert!(!columns.is_solved());
+        assert!(!columns.can_reset());
+        let positions = |columns: &Columns| -> Vec<i32> {
+            (0..columns.num_columns())
+                .map(|col| columns.column_position(col))
+                .collect()
+        };
+        let scrambled = positions(&columns);
+        let mut again = Columns::from_toml(COLUMNS_SPEC, Vec::new());
+        again.scramble(Scramble { seed: 1234, num_moves: 10 });
+        assert_eq!(positions(&again), scrambled);
+
+        columns.rotate_column(0, 1);
+        assert!(columns.can_reset());
+        columns.reset();
+        assert_eq!(positions(&columns), scrambled);
+    }
+
+    #[test]
+    fn scrambled_round_trip() {
+        let scramble = Scramble::new(1234, Difficulty::Hard);
+        let mut state = WhatchaState::scrambled(scramble);
+        let positions = |state: &WhatchaState| -> Vec<i32> {
+            (0..state.columns().num_columns())
+                .map(|col| state.columns().column_position(col))
+                .collect()
+        };
+        let scrambled = positions(&state);
+        let reloaded = WhatchaState::from_toml(state.to_toml());
+        assert_eq!(positions(&reloaded), scrambled);
+        assert!(!reloaded.can_reset());
+
+        state.rotate_column(0, 1);
+        let rotated = positions(&state);
+        let mut reloaded = WhatchaState::from_toml(state.to_toml());
+        assert_eq!(positions(&reloaded), rotated);
+        assert!(reloaded.can_reset());
+        reloaded.reset();
+        assert_eq!(positions(&reloaded), scrambled);
+    }
 }
 
 // ========================================================================= //
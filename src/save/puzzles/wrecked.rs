@@ -17,10 +17,12 @@
 // | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
 // +--------------------------------------------------------------------------+
 
+use rand::Rng;
 use std::collections::VecDeque;
 use toml;
 
 use super::PuzzleState;
+use crate::save::random::{seeded_rng, Scramble};
 use crate::save::util::{rotate_deque, to_table, Tomlable, ACCESS_KEY};
 use crate::save::{Access, Direction, Location};
 
@@ -30,6 +32,7 @@ const NUM_COLS: i32 = 9;
 const NUM_ROWS: i32 = 7;
 
 const GRID_KEY: &str = "grid";
+const SCRAMBLE_KEY: &str = "scramble";
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
 const INITIAL_GRID: &[i8] = &[
@@ -58,6 +61,8 @@ const SOLVED_GRID: &[i8] = &[
 pub struct WreckedState {
     access: Access,
     grid: Vec<i8>,
+    initial_grid: Vec<i8>,
+    scramble: Option<Scramble>,
     is_initial: bool,
 }
 
@@ -66,6 +71,46 @@ impl WreckedState {
         WreckedState::from_toml(toml::Value::Boolean(false))
     }
 
+    /// Deals a fresh instance of the puzzle, scrambled from the solved state
+    /// by `num_moves` random row and column shifts (or more, if those happen
+    /// to leave it solved).  The same seed always deals the same instance,
+    /// and resetting it returns to the scrambled state.
+    pub fn scrambled(scramble: Scramble) -> WreckedState {
+        let mut rng = seeded_rng(scramble.seed);
+        let num_moves = scramble.num_moves;
+        let mut state = WreckedState {
+            access: Access::Unvisited,
+            grid: SOLVED_GRID.to_vec(),
+            initial_grid: SOLVED_GRID.to_vec(),
+            scramble: Some(scramble),
+            is_initial: false,
+        };
+        let mut last_rank = None;
+        let mut moves = 0;
+        while moves < num_moves
+            || (num_moves > 0 && (&state.grid as &[i8]) == SOLVED_GRID)
+        {
+            let (dir, rank) = if rng.gen() {
+                (Direction::South, rng.gen_range(0, NUM_COLS))
+            } else {
+                (Direction::East, rng.gen_range(0, NUM_ROWS))
+            };
+            let num_tiles = state.num_tiles_in(dir, rank);
+            // Shifting the same rank twice in a row would only count as one
+            // move, and shifting a rank with one tile does nothing.
+            if Some((dir, rank)) == last_rank || num_tiles < 2 {
+                continue;
+            }
+            state.shift_tiles(dir, rank, rng.gen_range(1, num_tiles));
+            last_rank = Some((dir, rank));
+            moves += 1;
+        }
+        state.access = Access::Unvisited;
+        state.initial_grid = state.grid.clone();
+        state.is_initial = true;
+        state
+    }
+
     pub fn solve(&mut self) {
         self.access = Access::Solved;
         self.grid = SOLVED_GRID.to_vec();
@@ -127,11 +172,30 @@ impl WreckedState {
                 }
             }
         }
-        self.is_initial = &self.grid as &[i8] == INITIAL_GRID;
+        self.is_initial = self.grid == self.initial_grid;
         if &self.grid as &[i8] == SOLVED_GRID {
             self.access = Access::Solved;
         }
     }
+
+    /// Returns the number of tiles that shifting the given row (for East or
+    /// West) or column (for North or South) would move.
+    fn num_tiles_in(&self, dir: Direction, rank: i32) -> i32 {
+        let (cols, rows) = if dir.is_vertical() {
+            ((rank..(rank + 1)), (0..NUM_ROWS))
+        } else {
+            ((0..NUM_COLS), (rank..(rank + 1)))
+        };
+        let mut count = 0;
+        for row in rows {
+            for col in cols.clone() {
+                if self.tile_at(col, row).is_some() {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
 }
 
 impl PuzzleState for WreckedState {
@@ -152,15 +216,22 @@ impl PuzzleState for WreckedState {
     }
 
     fn reset(&mut self) {
-        self.grid = INITIAL_GRID.to_vec();
+        self.grid = self.initial_grid.clone();
         self.is_initial = true;
     }
+
+    fn is_scrambled(&self) -> bool {
+        self.scramble.is_some()
+    }
 }
 
 impl Tomlable for WreckedState {
     fn to_toml(&self) -> toml::Value {
         let mut table = toml::value::Table::new();
         table.insert(ACCESS_KEY.to_string(), self.access.to_toml());
+        if let Some(ref scramble) = self.scramble {
+            table.insert(SCRAMBLE_KEY.to_string(), scramble.to_toml());
+        }
         if !self.is_initial && !self.is_solved() {
             table.insert(GRID_KEY.to_string(), self.grid.to_toml());
         }
@@ -170,23 +241,28 @@ impl Tomlable for WreckedState {
     fn from_toml(value: toml::Value) -> WreckedState {
         let mut table = to_table(value);
         let mut access = Access::pop_from_table(&mut table, ACCESS_KEY);
+        let scramble = table.remove(SCRAMBLE_KEY).map(Scramble::from_toml);
+        let initial_grid = match scramble {
+            Some(scramble) => WreckedState::scrambled(scramble).initial_grid,
+            None => INITIAL_GRID.to_vec(),
+        };
         let grid = if access.is_solved() {
             SOLVED_GRID.to_vec()
         } else {
             let mut grid = Vec::<i8>::pop_from_table(&mut table, GRID_KEY);
-            let mut init_sorted = INITIAL_GRID.to_vec();
+            let mut init_sorted = initial_grid.clone();
             init_sorted.sort();
             let mut grid_sorted = grid.clone();
             grid_sorted.sort();
             if grid_sorted != init_sorted {
-                grid = INITIAL_GRID.to_vec()
+                grid = initial_grid.clone()
             } else {
                 let init_neg: Vec<bool> =
-                    INITIAL_GRID.iter().map(|&tile| tile < 0).collect();
+                    initial_grid.iter().map(|&tile| tile < 0).collect();
                 let grid_neg: Vec<bool> =
                     grid.iter().map(|&tile| tile < 0).collect();
                 if grid_neg != init_neg {
-                    grid = INITIAL_GRID.to_vec();
+                    grid = initial_grid.clone();
                 }
             }
             if &grid as &[i8] == SOLVED_GRID {
@@ -194,8 +270,8 @@ impl Tomlable for WreckedState {
             }
             grid
         };
-        let is_initial = &grid as &[i8] == INITIAL_GRID;
-        WreckedState { access, grid, is_initial }
+        let is_initial = grid == initial_grid;
+        WreckedState { access, grid, initial_grid, scramble, is_initial }
     }
 }
 
@@ -206,8 +282,9 @@ mod tests {
     use toml;
 
     use super::{WreckedState, GRID_KEY, INITIAL_GRID, SOLVED_GRID};
+    use crate::save::random::{Difficulty, Scramble};
     use crate::save::util::{Tomlable, ACCESS_KEY};
-    use crate::save::{Access, Direction};
+    use crate::save::{Access, Direction, PuzzleState};
 
     #[test]
     fn toml_round_trip() {
@@ -289,6 +366,47 @@ mod tests {
         assert_eq!(state.tile_at(8, 4), Some(1));
         assert_eq!(state.tile_at(8, 6), Some(1));
     }
+
+    #[test]
+    fn scrambled_is_repeatable() {
+        let scramble = |seed, num_moves| Scramble { seed, num_moves };
+        let state = WreckedState::scrambled(scramble(1234, 10));
+        assert_eq!(state.access, Access::Unvisited);
+        assert_ne!(&state.grid as &[i8], SOLVED_GRID);
+        assert!(state.is_initial);
+        let again = WreckedState::scrambled(scramble(1234, 10));
+        assert_eq!(again.grid, state.grid);
+        let other = WreckedState::scrambled(scramble(4321, 10));
+        assert_ne!(other.grid, state.grid);
+        let unscrambled = WreckedState::scrambled(scramble(1234, 0));
+        assert_eq!(&unscrambled.grid as &[i8], SOLVED_GRID);
+
+        let mut state = state;
+        let scrambled = state.grid.clone();
+        state.shift_tiles(Direction::East, 0, 1);
+        assert!(state.can_reset());
+        state.reset();
+        assert_eq!(state.grid, scrambled);
+        assert!(!state.can_reset());
+    }
+
+    #[test]
+    fn scrambled_round_trip() {
+        let scramble = Scramble::new(1234, Difficulty::Hard);
+        let mut state = WreckedState::scrambled(scramble);
+        let scrambled = state.grid.clone();
+        let reloaded = WreckedState::from_toml(state.to_toml());
+        assert_eq!(reloaded.grid, scrambled);
+        assert!(!reloaded.can_reset());
+
+        state.shift_tiles(Direction::East, 0, 1);
+        let grid = state.grid.clone();
+        let mut reloaded = WreckedState::from_toml(state.to_toml());
+        assert_eq!(reloaded.grid, grid);
+        assert!(reloaded.can_reset());
+        reloaded.reset();
+        assert_eq!(reloaded.grid, scrambled);
+    }
 }
 
 // ========================================================================= //
@@ -22,6 +22,7 @@ use std::cmp;
 use std::collections::{HashMap, HashSet};
 use toml;
 
+use crate::save::random;
 use crate::save::util::Tomlable;
 
 // ========================================================================= //
@@ -451,7 +452,7 @@ impl Board {
                 println!("Found single best move (score = {})", best_score);
             }
         }
-        rand::seq::sample_iter(&mut rand::thread_rng(), best_moves, 1)
+        random::with_rng(|rng| rand::seq::sample_iter(rng, best_moves, 1))
             .expect("no moves were possible")
             .pop()
             .unwrap()
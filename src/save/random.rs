@@ -0,0 +1,186 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use rand::chacha::ChaChaRng;
+use rand::{self, Rng, SeedableRng};
+use std::cell::RefCell;
+use std::cmp;
+use toml;
+
+use crate::save::util::{to_table, Tomlable};
+
+// ========================================================================= //
+
+thread_local! {
+    static GAME_RNG: RefCell<ChaChaRng> =
+        RefCell::new(ChaChaRng::from_seed(&[rand::random()]));
+}
+
+/// Reseeds the random number generator used by puzzle logic, so that a
+/// recorded session can be replayed exactly.
+pub fn set_seed(seed: u32) {
+    GAME_RNG.with(|rng| *rng.borrow_mut() = ChaChaRng::from_seed(&[seed]));
+}
+
+/// Picks a fresh seed for `set_seed` from the OS.
+pub fn new_seed() -> u32 {
+    rand::random()
+}
+
+/// Calls `func` with the random number generator used by puzzle logic.  All
+/// randomness that affects game state must come from here rather than from
+/// `rand::thread_rng()`, or replays will diverge.
+pub fn with_rng<T, F: FnOnce(&mut ChaChaRng) -> T>(func: F) -> T {
+    GAME_RNG.with(|rng| func(&mut rng.borrow_mut()))
+}
+
+/// Returns a random number generator of its own, separate from the one used
+/// by puzzle logic, for dealing out a puzzle instance that the same seed will
+/// always deal again.
+pub fn seeded_rng(seed: u32) -> ChaChaRng {
+    ChaChaRng::from_seed(&[seed])
+}
+
+/// Returns a random value from the puzzle logic random number generator.
+pub fn random<T: rand::Rand>() -> T {
+    with_rng(|rng| rng.gen())
+}
+
+// ========================================================================= //
+
+const SEED_KEY: &str = "seed";
+const MOVES_KEY: &str = "moves";
+
+const ALL_DIFFICULTIES: &[Difficulty] =
+    &[Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+/// How far from solved a dealt puzzle instance starts out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn all() -> &'static [Difficulty] {
+        ALL_DIFFICULTIES
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Difficulty> {
+        Difficulty::all().iter().copied().find(|diff| diff.key() == key)
+    }
+
+    /// The number of random moves to scramble a puzzle by.
+    pub fn num_moves(self) -> u32 {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Medium => 6,
+            Difficulty::Hard => 12,
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// The seed and number of moves that a puzzle instance was dealt with, which
+/// is all that needs saving in order to deal the same instance again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Scramble {
+    pub seed: u32,
+    pub num_moves: u32,
+}
+
+impl Scramble {
+    pub fn new(seed: u32, difficulty: Difficulty) -> Scramble {
+        Scramble { seed, num_moves: difficulty.num_moves() }
+    }
+}
+
+impl Tomlable for Scramble {
+    fn to_toml(&self) -> toml::Value {
+        let mut table = toml::value::Table::new();
+        table.insert(SEED_KEY.to_string(), self.seed.to_toml());
+        table.insert(MOVES_KEY.to_string(), self.num_moves.to_toml());
+        toml::Value::Table(table)
+    }
+
+    fn from_toml(value: toml::Value) -> Scramble {
+        let mut table = to_table(value);
+        let seed = u32::pop_from_table(&mut table, SEED_KEY);
+        // Scrambles are re-dealt on every load, so don't trust a save file
+        // to ask for more moves than the hardest difficulty uses.
+        let num_moves = cmp::min(
+            u32::pop_from_table(&mut table, MOVES_KEY),
+            Difficulty::Hard.num_moves(),
+        );
+        Scramble { seed, num_moves }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::{random, seeded_rng, set_seed};
+
+    #[test]
+    fn same_seed_gives_same_values() {
+        set_seed(1234);
+        let first: Vec<u32> = (0..8).map(|_| random()).collect();
+        set_seed(1234);
+        let second: Vec<u32> = (0..8).map(|_| random()).collect();
+        assert_eq!(first, second);
+        set_seed(4321);
+        let third: Vec<u32> = (0..8).map(|_| random()).collect();
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn seeded_rng_is_separate_from_puzzle_rng() {
+        set_seed(1234);
+        let mut rng = seeded_rng(1234);
+        let first: Vec<u32> = (0..8).map(|_| rng.gen()).collect();
+        let second: Vec<u32> = (0..8).map(|_| random()).collect();
+        assert_eq!(first, second);
+        let mut rng = seeded_rng(1234);
+        let third: Vec<u32> = (0..8).map(|_| rng.gen()).collect();
+        assert_eq!(first, third);
+    }
+}
+
+// ========================================================================= //
@@ -27,6 +27,7 @@ use crate::gui::Point;
 // ========================================================================= //
 
 pub const ACCESS_KEY: &str = "access";
+pub const HINTS_KEY: &str = "hints";
 
 pub fn pop_array(
     table: &mut toml::value::Table,
@@ -0,0 +1,271 @@
+// +--------------------------------------------------------------------------+
+// | Copyright 2016 Matthew D. Steele <mdsteele@alum.mit.edu>                 |
+// |                                                                          |
+// | This file is part of System Syzygy.                                      |
+// |                                                                          |
+// | System Syzygy is free software: you can redistribute it and/or modify it |
+// | under the terms of the GNU General Public License as published by the    |
+// | Free Software Foundation, either version 3 of the License, or (at your   |
+// | option) any later version.                                               |
+// |                                                                          |
+// | System Syzygy is distributed in the hope that it will be useful, but     |
+// | WITHOUT ANY WARRANTY; without even the implied warranty of               |
+// | MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU        |
+// | General Public License for details.                                      |
+// |                                                                          |
+// | You should have received a copy of the GNU General Public License along  |
+// | with System Syzygy.  If not, see <http://www.gnu.org/licenses/>.         |
+// +--------------------------------------------------------------------------+
+
+use std::fmt;
+use toml;
+
+use super::game::Game;
+use super::location::Location;
+
+// ========================================================================= //
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IssueKind {
+    /// The key could not be used at all, and was discarded.
+    Dropped,
+    /// The key's value was invalid, and was replaced with a different one.
+    Coerced,
+}
+
+/// A single piece of save data that could not be loaded as-is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaveIssue {
+    pub slot: Option<String>,
+    pub location: Option<Location>,
+    pub field: String,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for SaveIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref slot) = self.slot {
+            write!(formatter, "{}: ", slot)?;
+        }
+        if let Some(location) = self.location {
+            write!(formatter, "{}: ", location.name())?;
+        }
+        let verb = match self.kind {
+            IssueKind::Dropped => "dropped",
+            IssueKind::Coerced => "reset",
+        };
+        write!(formatter, "{} ({})", self.field, verb)
+    }
+}
+
+// ========================================================================= //
+
+/// Compares the TOML that a game was loaded from against the game that was
+/// actually loaded, and reports every key that was dropped or coerced along
+/// the way.
+pub fn validate_game(
+    slot: &str,
+    input: &toml::Value,
+    game: &Game,
+) -> Vec<SaveIssue> {
+    let mut issues = Vec::new();
+    let input = match input.as_table() {
+        Some(table) => table,
+        None => {
+            issues.push(SaveIssue {
+                slot: Some(slot.to_string()),
+                location: None,
+                field: "game".to_string(),
+                kind: IssueKind::Coerced,
+            });
+            return issues;
+        }
+    };
+    let output = game.to_toml();
+    let output = output.as_table().unwrap();
+    for (key, value) in input.iter() {
+        let location = Location::all()
+            .iter()
+            .cloned()
+            .find(|&loc| loc != Location::Map && loc.key() == key);
+        // Unvisited puzzles are left out of the game's TOML, so compare those
+        // against the puzzle state itself.
+        let expected = output
+            .get(key)
+            .cloned()
+            .or_else(|| location.map(|loc| game.puzzle_state(loc).to_toml()));
+        let mut fields = Vec::new();
+        match (location, expected) {
+            (Some(_), Some(expected)) => {
+                diff_values("", value, &expected, &mut fields)
+            }
+            (None, Some(expected)) => {
+                diff_values(key, value, &expected, &mut fields)
+            }
+            (_, None) => fields.push((key.clone(), IssueKind::Dropped)),
+        }
+        issues.extend(fields.into_iter().map(|(field, kind)| SaveIssue {
+            slot: Some(slot.to_string()),
+            location,
+            field,
+            kind,
+        }));
+    }
+    issues
+}
+
+/// Compares a TOML table that some non-game data (such as prefs) was loaded
+/// from against that data's re-serialized form.
+pub fn validate_table(
+    name: &str,
+    input: &toml::Value,
+    output: &toml::Value,
+) -> Vec<SaveIssue> {
+    let mut fields = Vec::new();
+    diff_values(name, input, output, &mut fields);
+    fields
+        .into_iter()
+        .map(|(field, kind)| SaveIssue {
+            slot: None,
+            location: None,
+            field,
+            kind,
+        })
+        .collect()
+}
+
+fn diff_values(
+    field: &str,
+    input: &toml::Value,
+    output: &toml::Value,
+    issues: &mut Vec<(String, IssueKind)>,
+) {
+    match (input, output) {
+        (toml::Value::Table(input), toml::Value::Table(output)) => {
+            for (key, value) in input.iter() {
+                let subfield = if field.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", field, key)
+                };
+                match output.get(key) {
+                    Some(expected) => {
+                        diff_values(&subfield, value, expected, issues)
+                    }
+                    None => issues.push((subfield, IssueKind::Dropped)),
+                }
+            }
+        }
+        _ => {
+            if input != output {
+                let field =
+                    if field.is_empty() { "(all)" } else { field }.to_string();
+                issues.push((field, IssueKind::Coerced));
+            }
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use toml;
+
+    use super::{validate_game, IssueKind, SaveIssue};
+    use crate::save::util::{Tomlable, ACCESS_KEY};
+    use crate::save::{Access, Game, Location};
+
+    #[test]
+    fn game_round_trip_has_no_issues() {
+        let mut table = toml::value::Table::new();
+        for &location in Location::all() {
+            if location != Location::Map {
+                let mut state = toml::value::Table::new();
+                state.insert(ACCESS_KEY.to_string(), Access::Solved.to_toml());
+                table.insert(
+                    location.key().to_string(),
+                    toml::Value::Table(state),
+                );
+            }
+        }
+        let game = Game::from_toml(toml::Value::Table(table));
+        let saved = game.to_toml();
+        let game = Game::from_toml(saved.clone());
+        assert_eq!(validate_game("Slot 1", &saved, &game), vec![]);
+    }
+
+    #[test]
+    fn reports_dropped_and_coerced_keys() {
+        let mut state = toml::value::Table::new();
+        state.insert(ACCESS_KEY.to_string(), "sovled".to_string().to_toml());
+        state.insert("bogus".to_string(), toml::Value::Integer(7));
+        let mut table = toml::value::Table::new();
+        table.insert(
+            Location::BlackAndBlue.key().to_string(),
+            toml::Value::Table(state),
+        );
+        table.insert("ever_clicked_info".to_string(), 17.to_toml());
+        table.insert("unknown_key".to_string(), true.to_toml());
+        let input = toml::Value::Table(table);
+
+        let game = Game::from_toml(input.clone());
+        let issues = validate_game("Slot 1", &input, &game);
+        let issue = |location, field: &str, kind| SaveIssue {
+            slot: Some("Slot 1".to_string()),
+            location,
+            field: field.to_string(),
+            kind,
+        };
+        assert_eq!(issues.len(), 4);
+        assert!(issues.contains(&issue(
+            Some(Location::BlackAndBlue),
+            "access",
+            IssueKind::Coerced
+        )));
+        assert!(issues.contains(&issue(
+            Some(Location::BlackAndBlue),
+            "bogus",
+            IssueKind::Dropped
+        )));
+        assert!(issues.contains(&issue(
+            None,
+            "ever_clicked_info",
+            IssueKind::Coerced
+        )));
+        assert!(issues.contains(&issue(
+            None,
+            "unknown_key",
+            IssueKind::Dropped
+        )));
+    }
+
+    #[test]
+    fn reports_clamped_scramble_moves() {
+        let mut scramble = toml::value::Table::new();
+        scramble.insert("seed".to_string(), 1234.to_toml());
+        scramble.insert("moves".to_string(), 4000000000u32.to_toml());
+        let mut state = toml::value::Table::new();
+        state.insert("scramble".to_string(), toml::Value::Table(scramble));
+        let mut table = toml::value::Table::new();
+        table.insert(
+            Location::HexSpangled.key().to_string(),
+            toml::Value::Table(state),
+        );
+        let input = toml::Value::Table(table);
+
+        let game = Game::from_toml(input.clone());
+        let issues = validate_game("Slot 1", &input, &game);
+        assert_eq!(
+            issues,
+            vec![SaveIssue {
+                slot: Some("Slot 1".to_string()),
+                location: Some(Location::HexSpangled),
+                field: "scramble.moves".to_string(),
+                kind: IssueKind::Coerced,
+            }]
+        );
+    }
+}
+
+// ========================================================================= //